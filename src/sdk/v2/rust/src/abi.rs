@@ -0,0 +1,168 @@
+//! Contract interfaces and decoding of contract calls.
+//!
+//! Interfaces use the JSON ABI format the node's own contracts ship with,
+//! either a bare list of entries or wrapped as `{"contractName", "abi"}`.
+//! Contract calls travel in a transaction's `data` as the function name and
+//! its positional arguments:
+//!
+//! ```json
+//! {"type": "contract_call", "function": "approve", "args": ["0x...", 5]}
+//! ```
+
+use crate::error::{Result, VernachainError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbiParam {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiEntry {
+    /// `function`, `constructor`, `event`, ...
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<AbiParam>,
+    #[serde(default)]
+    pub outputs: Vec<AbiParam>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_mutability: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "AbiDocument", into = "AbiDocument")]
+pub struct ContractAbi {
+    pub contract_name: Option<String>,
+    pub entries: Vec<AbiEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum AbiDocument {
+    Wrapped {
+        #[serde(
+            rename = "contractName",
+            default,
+            skip_serializing_if = "Option::is_none"
+        )]
+        contract_name: Option<String>,
+        abi: Vec<AbiEntry>,
+    },
+    Bare(Vec<AbiEntry>),
+}
+
+impl From<AbiDocument> for ContractAbi {
+    fn from(document: AbiDocument) -> Self {
+        match document {
+            AbiDocument::Wrapped { contract_name, abi } => Self {
+                contract_name,
+                entries: abi,
+            },
+            AbiDocument::Bare(entries) => Self {
+                contract_name: None,
+                entries,
+            },
+        }
+    }
+}
+
+impl From<ContractAbi> for AbiDocument {
+    fn from(abi: ContractAbi) -> Self {
+        AbiDocument::Wrapped {
+            contract_name: abi.contract_name,
+            abi: abi.entries,
+        }
+    }
+}
+
+impl ContractAbi {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn function(&self, name: &str) -> Option<&AbiEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.entry_type == "function" && entry.name.as_deref() == Some(name))
+    }
+
+    pub fn event(&self, name: &str) -> Option<&AbiEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.entry_type == "event" && entry.name.as_deref() == Some(name))
+    }
+
+    /// Names `call`'s arguments after the function's inputs. Fails with
+    /// `ValidationError` if the interface has no such function or the
+    /// argument count doesn't match.
+    pub fn decode_call(&self, call: &ContractCallData) -> Result<DecodedCall> {
+        let function = self.function(&call.function).ok_or_else(|| {
+            VernachainError::ValidationError(format!("no function {} in ABI", call.function))
+        })?;
+        if function.inputs.len() != call.args.len() {
+            return Err(VernachainError::ValidationError(format!(
+                "{} takes {} arguments, call has {}",
+                call.function,
+                function.inputs.len(),
+                call.args.len()
+            )));
+        }
+        let args = function
+            .inputs
+            .iter()
+            .zip(&call.args)
+            .map(|(param, value)| DecodedArg {
+                name: param.name.clone(),
+                param_type: param.param_type.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        Ok(DecodedCall {
+            function: call.function.clone(),
+            args,
+        })
+    }
+}
+
+/// A contract call as carried in a transaction's `data`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractCallData {
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+}
+
+impl ContractCallData {
+    /// The call in a transaction's `data`, if it holds one.
+    pub fn from_data(data: &HashMap<String, Value>) -> Option<Self> {
+        let function = data.get("function")?.as_str()?.to_string();
+        let args = match data.get("args") {
+            Some(Value::Array(args)) => args.clone(),
+            Some(_) => return None,
+            None => Vec::new(),
+        };
+        Some(Self { function, args })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedArg {
+    pub name: String,
+    pub param_type: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodedCall {
+    pub function: String,
+    pub args: Vec<DecodedArg>,
+}
@@ -0,0 +1,288 @@
+//! Contract ABIs looked up by address, for decoding without supplying them by
+//! hand.
+//!
+//! The registry asks the node for the ABI of each verified contract once and
+//! keeps it in memory. With a cache directory it also keeps each ABI on disk,
+//! as `<address>.json`, so later runs don't fetch it again. ABIs never change
+//! for a deployed address, so cached entries don't expire; an unreadable cache
+//! file is fetched again and replaced. A contract without an ABI may still be
+//! verified later, so the node is asked again once `recheck_missing_after`
+//! has passed.
+
+use crate::{
+    abi::ContractAbi,
+    client::VernachainClient,
+    describe::{describe_transaction, TransactionSummary},
+    error::{Result, VernachainError},
+    types::Transaction,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+const DEFAULT_MISSING_TTL: Duration = Duration::from_secs(300);
+
+pub struct AbiRegistry {
+    client: VernachainClient,
+    cache_dir: Option<PathBuf>,
+    missing_ttl: Duration,
+    /// By lower-case address.
+    known: Mutex<HashMap<String, ContractAbi>>,
+    /// When the node last had no ABI for an address.
+    missing: Mutex<HashMap<String, Instant>>,
+}
+
+impl AbiRegistry {
+    pub fn new(client: &VernachainClient) -> Self {
+        Self {
+            client: client.clone(),
+            cache_dir: None,
+            missing_ttl: DEFAULT_MISSING_TTL,
+            known: Mutex::new(HashMap::new()),
+            missing: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps fetched ABIs in `dir`, which is created on the first write.
+    pub fn with_cache_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.cache_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// How long to trust the node's answer that a contract has no ABI before
+    /// asking again, 5 minutes by default.
+    pub fn recheck_missing_after(mut self, ttl: Duration) -> Self {
+        self.missing_ttl = ttl;
+        self
+    }
+
+    /// Registers an ABI by hand, e.g. for an unverified contract.
+    pub fn insert(&self, address: &str, abi: ContractAbi) {
+        self.remember(&address.to_ascii_lowercase(), Some(abi));
+    }
+
+    /// The ABI of the contract at `address`, or `None` if the node has none,
+    /// such as for unverified contracts and plain accounts.
+    pub async fn get(&self, address: &str) -> Result<Option<ContractAbi>> {
+        let key = address.to_ascii_lowercase();
+        if let Some(abi) = self.cached(&key) {
+            return Ok(Some(abi));
+        }
+        if self.recently_missing(&key) {
+            return Ok(None);
+        }
+        if let Some(abi) = self.read_cache(&key).await? {
+            self.remember(&key, Some(abi.clone()));
+            return Ok(Some(abi));
+        }
+
+        let abi = match self.client.get_contract_abi(address).await {
+            Ok(abi) => Some(abi),
+            Err(VernachainError::Api {
+                status: 400 | 404, ..
+            }) => None,
+            Err(e) => return Err(e),
+        };
+        if let Some(abi) = &abi {
+            self.write_cache(&key, abi).await?;
+        }
+        self.remember(&key, abi.clone());
+        Ok(abi)
+    }
+
+    /// The ABIs of the contracts `transactions` were sent to, keyed by
+    /// address as `describe_transaction` takes them.
+    pub async fn abis_for(
+        &self,
+        transactions: &[Transaction],
+    ) -> Result<HashMap<String, ContractAbi>> {
+        let mut abis = HashMap::new();
+        for tx in transactions {
+            let is_call = tx
+                .data
+                .as_ref()
+                .is_some_and(|data| data.contains_key("function"));
+            if !is_call || abis.contains_key(&tx.recipient) {
+                continue;
+            }
+            if let Some(abi) = self.get(&tx.recipient).await? {
+                abis.insert(tx.recipient.clone(), abi);
+            }
+        }
+        Ok(abis)
+    }
+
+    /// `describe_transaction` with the recipient's ABI looked up here.
+    pub async fn describe(&self, tx: &Transaction) -> Result<TransactionSummary> {
+        let abis = self.abis_for(std::slice::from_ref(tx)).await?;
+        Ok(describe_transaction(tx, &abis))
+    }
+
+    fn cached(&self, key: &str) -> Option<ContractAbi> {
+        self.known
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    fn recently_missing(&self, key: &str) -> bool {
+        self.missing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .is_some_and(|since| since.elapsed() < self.missing_ttl)
+    }
+
+    fn remember(&self, key: &str, abi: Option<ContractAbi>) {
+        let mut missing = self.missing.lock().unwrap_or_else(|e| e.into_inner());
+        match abi {
+            Some(abi) => {
+                missing.remove(key);
+                self.known
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(key.to_string(), abi);
+            }
+            None => {
+                missing.insert(key.to_string(), Instant::now());
+            }
+        }
+    }
+
+    fn cache_path(&self, key: &str) -> Option<PathBuf> {
+        // Keys come from the network, so never let one name a path.
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return None;
+        }
+        Some(self.cache_dir.as_ref()?.join(format!("{}.json", key)))
+    }
+
+    async fn read_cache(&self, key: &str) -> Result<Option<ContractAbi>> {
+        let Some(path) = self.cache_path(key) else {
+            return Ok(None);
+        };
+        let contents = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        match ContractAbi::from_json(&contents) {
+            Ok(abi) => Ok(Some(abi)),
+            Err(e) => {
+                warn!(
+                    "ignoring unreadable ABI cache file {}: {}",
+                    path.display(),
+                    e
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    async fn write_cache(&self, key: &str, abi: &ContractAbi) -> Result<()> {
+        let Some(path) = self.cache_path(key) else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        tokio::fs::write(&temporary, serde_json::to_string_pretty(abi)?).await?;
+        tokio::fs::rename(&temporary, &path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    const ADDRESS: &str = "0xabc1";
+
+    fn abi_json() -> serde_json::Value {
+        json!({
+            "contractName": "Token",
+            "abi": [{ "type": "function", "name": "transfer", "inputs": [] }],
+        })
+    }
+
+    /// Answers 404 for the ABI once, then the ABI.
+    async fn verified_later() -> MockServer {
+        let server = MockServer::start().await;
+        let abi_path = format!("/api/v1/contracts/{}/abi", ADDRESS);
+        Mock::given(method("GET"))
+            .and(path(abi_path.as_str()))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({ "detail": "unverified" })),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(abi_path.as_str()))
+            .respond_with(ResponseTemplate::new(200).set_body_json(abi_json()))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn remembers_missing_abis_for_a_while() {
+        let server = verified_later().await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        let registry = AbiRegistry::new(&client);
+
+        assert!(registry.get(ADDRESS).await.unwrap().is_none());
+        assert!(registry.get(ADDRESS).await.unwrap().is_none());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn asks_again_once_a_missing_abi_expires() {
+        let server = verified_later().await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        let registry = AbiRegistry::new(&client).recheck_missing_after(Duration::ZERO);
+
+        assert!(registry.get(ADDRESS).await.unwrap().is_none());
+        let abi = registry.get(ADDRESS).await.unwrap().unwrap();
+        assert_eq!(abi.contract_name.as_deref(), Some("Token"));
+        registry.get(ADDRESS).await.unwrap().unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn replaces_unreadable_cache_files() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v1/contracts/{}/abi", ADDRESS)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(abi_json()))
+            .mount(&server)
+            .await;
+        let dir = std::env::temp_dir().join(format!(
+            "vernachain-abis-{}-{}",
+            std::process::id(),
+            rand_core::RngCore::next_u64(&mut rand_core::OsRng)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join(format!("{}.json", ADDRESS));
+        std::fs::write(&file, "{ truncated").unwrap();
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+
+        let registry = AbiRegistry::new(&client).with_cache_dir(&dir);
+        let abi = registry.get(ADDRESS).await.unwrap().unwrap();
+        let cached = ContractAbi::from_json(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(cached, abi);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Mixed-case checksummed addresses.
+//!
+//! As with EIP-55, the checksum lives in the case of the hex letters: a
+//! letter is upper case when the matching nibble of the SHA-256 hash of the
+//! lower-case digits is 8 or more. All-lower-case and all-upper-case
+//! addresses carry no checksum and are accepted as they are; a mixed-case
+//! address must match its checksum, which catches most mistyped or truncated
+//! copies.
+
+use crate::error::{Result, VernachainError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+/// Hex digits in a contract address.
+const CONTRACT_ADDRESS_LEN: usize = 40;
+
+/// Hex digits in an account address, the hex-encoded public key.
+const ACCOUNT_ADDRESS_LEN: usize = 64;
+
+/// A validated account or contract address. Displays with its checksum;
+/// equality ignores case and the `0x` prefix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Address {
+    /// The address in lower case, with any prefix.
+    lower: String,
+    prefixed: bool,
+}
+
+impl Address {
+    /// Parses a 40- or 64-digit hex address, with or without `0x`. Fails with
+    /// `ValidationError` on anything else or on a mixed-case address whose
+    /// checksum doesn't match.
+    pub fn parse(address: &str) -> Result<Self> {
+        let (digits, prefixed) = match address.strip_prefix("0x") {
+            Some(digits) => (digits, true),
+            None => (address, false),
+        };
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid(format!("address {:?} is not hex", address)));
+        }
+        if digits.len() != CONTRACT_ADDRESS_LEN && digits.len() != ACCOUNT_ADDRESS_LEN {
+            return Err(invalid(format!(
+                "address {:?} must have {} or {} hex digits, got {}",
+                address,
+                CONTRACT_ADDRESS_LEN,
+                ACCOUNT_ADDRESS_LEN,
+                digits.len()
+            )));
+        }
+        let parsed = Self {
+            lower: address.to_ascii_lowercase(),
+            prefixed,
+        };
+        let has_lower = digits.bytes().any(|b| b.is_ascii_lowercase());
+        let has_upper = digits.bytes().any(|b| b.is_ascii_uppercase());
+        if has_lower && has_upper && parsed.checksummed_digits() != digits {
+            return Err(invalid(format!(
+                "address {:?} has an invalid checksum; expected {}",
+                address,
+                parsed.to_checksum()
+            )));
+        }
+        Ok(parsed)
+    }
+
+    /// The address in checksummed mixed case, keeping any `0x` prefix.
+    pub fn to_checksum(&self) -> String {
+        let prefix = if self.prefixed { "0x" } else { "" };
+        format!("{}{}", prefix, self.checksummed_digits())
+    }
+
+    /// The address in lower case, as the node reports it.
+    pub fn as_str(&self) -> &str {
+        &self.lower
+    }
+
+    /// The lower-case hex digits, without the prefix.
+    pub(crate) fn digits(&self) -> &str {
+        &self.lower[if self.prefixed { 2 } else { 0 }..]
+    }
+
+    fn checksummed_digits(&self) -> String {
+        let hash = Sha256::digest(self.digits().as_bytes());
+        self.digits()
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+}
+
+impl PartialEq for Address {
+    fn eq(&self, other: &Self) -> bool {
+        self.digits() == other.digits()
+    }
+}
+
+impl Eq for Address {}
+
+impl Hash for Address {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.digits().hash(state);
+    }
+}
+
+impl FromStr for Address {
+    type Err = VernachainError;
+
+    fn from_str(address: &str) -> Result<Self> {
+        Address::parse(address)
+    }
+}
+
+impl TryFrom<String> for Address {
+    type Error = VernachainError;
+
+    fn try_from(address: String) -> Result<Self> {
+        Address::parse(&address)
+    }
+}
+
+impl From<Address> for String {
+    fn from(address: Address) -> Self {
+        address.lower
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+/// Checksums `address` for display, or returns it unchanged if it isn't a
+/// hex address.
+pub(crate) fn display(address: &str) -> String {
+    Address::parse(&address.to_ascii_lowercase())
+        .map(|address| address.to_checksum())
+        .unwrap_or_else(|_| address.to_string())
+}
+
+/// Rejects `address` only if it is a mixed-case hex address with a bad
+/// checksum, leaving other address formats to the node.
+pub(crate) fn check(address: &str) -> Result<()> {
+    match Address::parse(address) {
+        Err(e) if Address::parse(&address.to_ascii_lowercase()).is_ok() => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn invalid(message: String) -> VernachainError {
+    VernachainError::ValidationError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "0x5AAEb6053f3e94c9B9A09F33669435e7ef1BEaEd";
+    const ACCOUNT: &str = "abaBabaBAbaBAbabABABABABabAbaBabABABABababAbaBabaBabAbaBaBaBAbAB";
+
+    #[test]
+    fn checksums_round_trip() {
+        for checksummed in [CONTRACT, ACCOUNT] {
+            let lower = Address::parse(&checksummed.to_ascii_lowercase()).unwrap();
+            assert_eq!(lower.to_checksum(), checksummed);
+            assert_eq!(lower.to_string(), checksummed);
+
+            let parsed = Address::parse(checksummed).unwrap();
+            assert_eq!(parsed, lower);
+            assert_eq!(parsed.as_str(), checksummed.to_ascii_lowercase());
+            assert_eq!(Address::parse(&parsed.to_checksum()).unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn accepts_single_case_addresses() {
+        let upper = format!("0x{}", CONTRACT[2..].to_ascii_uppercase());
+        assert!(Address::parse(&upper).is_ok());
+        assert_eq!(
+            Address::parse(&CONTRACT[2..]).unwrap(),
+            Address::parse(CONTRACT).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_checksums_and_lengths() {
+        let flipped = CONTRACT.replacen('A', "a", 1);
+        assert!(Address::parse(&flipped).is_err());
+        assert!(check(&flipped).is_err());
+        assert!(check("validator-1").is_ok());
+        assert!(Address::parse(&CONTRACT[..41]).is_err());
+        assert!(Address::parse("0xzz").is_err());
+        assert_eq!(display("validator-1"), "validator-1");
+        assert_eq!(display(&CONTRACT.to_ascii_lowercase()), CONTRACT);
+    }
+}
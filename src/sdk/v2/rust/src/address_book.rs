@@ -0,0 +1,390 @@
+//! Labeled addresses kept in a local file.
+//!
+//! A book is stored as JSON, or encrypted with a passphrase: the key is
+//! derived with PBKDF2-HMAC-SHA256 and the entries sealed with
+//! ChaCha20-Poly1305, so a wrong passphrase or a tampered file fails to open
+//! instead of yielding garbage. Files are replaced atomically on save.
+
+use crate::error::{Result, VernachainError};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+const FORMAT_VERSION: u32 = 1;
+const KDF_ITERATIONS: u32 = 600_000;
+/// Iteration counts accepted from a file, so a corrupted or hostile one
+/// can't make opening it hang or weaken the key to nothing.
+const KDF_ITERATION_RANGE: std::ops::RangeInclusive<u32> = 100_000..=10_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressEntry {
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Entries {
+    entries: BTreeMap<String, AddressEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    kdf_iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Key for an encrypted book, derived once when it is opened.
+struct Sealing {
+    key: [u8; 32],
+    salt: [u8; 16],
+    iterations: u32,
+}
+
+#[derive(Default)]
+pub struct AddressBook {
+    entries: BTreeMap<String, AddressEntry>,
+    path: Option<PathBuf>,
+    sealing: Option<Sealing>,
+}
+
+impl AddressBook {
+    /// An empty book that lives only in memory until `save_to` is called.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a plain JSON book, starting an empty one if `path` doesn't exist
+    /// yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let entries = match read_if_exists(path)? {
+            Some(contents) => parse::<Entries>(path, &contents)?.entries,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            entries,
+            path: Some(path.to_path_buf()),
+            sealing: None,
+        })
+    }
+
+    /// Opens a book encrypted with `passphrase`, starting an empty one if
+    /// `path` doesn't exist yet. A wrong passphrase is a `ValidationError`.
+    pub fn open_encrypted(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let (entries, sealing) = match read_if_exists(path)? {
+            Some(contents) => {
+                let file: EncryptedFile = parse(path, &contents)?;
+                if file.version != FORMAT_VERSION {
+                    return Err(VernachainError::ConfigurationError(format!(
+                        "{}: unsupported address book version {}",
+                        path.display(),
+                        file.version
+                    )));
+                }
+                if !KDF_ITERATION_RANGE.contains(&file.kdf_iterations) {
+                    return Err(corrupt(
+                        path,
+                        format!("unsupported kdf_iterations {}", file.kdf_iterations),
+                    ));
+                }
+                let salt = decode_hex::<16>(path, &file.salt)?;
+                let nonce = decode_hex::<12>(path, &file.nonce)?;
+                let ciphertext = hex::decode(&file.ciphertext)
+                    .map_err(|e| corrupt(path, format!("ciphertext: {}", e)))?;
+                let sealing = Sealing::derive(passphrase, salt, file.kdf_iterations);
+                let plaintext = sealing
+                    .cipher()
+                    .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                    .map_err(|_| {
+                        VernachainError::ValidationError(format!(
+                            "{}: wrong passphrase or corrupted address book",
+                            path.display()
+                        ))
+                    })?;
+                let text = String::from_utf8(plaintext).map_err(|e| corrupt(path, e))?;
+                (parse::<Entries>(path, &text)?.entries, sealing)
+            }
+            None => {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                (
+                    BTreeMap::new(),
+                    Sealing::derive(passphrase, salt, KDF_ITERATIONS),
+                )
+            }
+        };
+        Ok(Self {
+            entries,
+            path: Some(path.to_path_buf()),
+            sealing: Some(sealing),
+        })
+    }
+
+    /// Adds or replaces `label`, returning the entry it replaced.
+    pub fn insert(
+        &mut self,
+        label: impl Into<String>,
+        address: impl Into<String>,
+    ) -> Option<AddressEntry> {
+        self.insert_entry(
+            label,
+            AddressEntry {
+                address: address.into(),
+                note: None,
+            },
+        )
+    }
+
+    pub fn insert_entry(
+        &mut self,
+        label: impl Into<String>,
+        entry: AddressEntry,
+    ) -> Option<AddressEntry> {
+        self.entries.insert(label.into(), entry)
+    }
+
+    pub fn remove(&mut self, label: &str) -> Option<AddressEntry> {
+        self.entries.remove(label)
+    }
+
+    pub fn get(&self, label: &str) -> Option<&AddressEntry> {
+        self.entries.get(label)
+    }
+
+    /// The address saved under `label`, or a `ValidationError` naming it.
+    pub fn address(&self, label: &str) -> Result<&str> {
+        self.get(label)
+            .map(|entry| entry.address.as_str())
+            .ok_or_else(|| {
+                VernachainError::ValidationError(format!("no address book entry {:?}", label))
+            })
+    }
+
+    /// The address saved under `name`, or `name` itself when it isn't a
+    /// label, for inputs that take either.
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.get(name).map_or(name, |entry| entry.address.as_str())
+    }
+
+    /// The label of an address, ignoring case.
+    pub fn label_of(&self, address: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(_, entry)| entry.address.eq_ignore_ascii_case(address))
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Entries ordered by label.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AddressEntry)> {
+        self.entries
+            .iter()
+            .map(|(label, entry)| (label.as_str(), entry))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the book back to the file it was opened from.
+    pub fn save(&self) -> Result<()> {
+        let path = self.path.as_deref().ok_or_else(|| {
+            VernachainError::ConfigurationError("address book has no file; use save_to".to_string())
+        })?;
+        self.write(path)
+    }
+
+    /// Writes the book to `path` and saves there from now on.
+    pub fn save_to(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.write(path.as_ref())?;
+        self.path = Some(path.as_ref().to_path_buf());
+        Ok(())
+    }
+
+    /// Encrypts the book with `passphrase` from the next save on.
+    pub fn set_passphrase(&mut self, passphrase: &str) {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        self.sealing = Some(Sealing::derive(passphrase, salt, KDF_ITERATIONS));
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        let entries = serde_json::to_string_pretty(&Entries {
+            entries: self.entries.clone(),
+        })?;
+        let contents = match &self.sealing {
+            Some(sealing) => {
+                let mut nonce = [0u8; 12];
+                OsRng.fill_bytes(&mut nonce);
+                let ciphertext = sealing
+                    .cipher()
+                    .encrypt(Nonce::from_slice(&nonce), entries.as_bytes())
+                    .map_err(|e| VernachainError::InternalError(e.to_string()))?;
+                serde_json::to_string_pretty(&EncryptedFile {
+                    version: FORMAT_VERSION,
+                    kdf_iterations: sealing.iterations,
+                    salt: hex::encode(sealing.salt),
+                    nonce: hex::encode(nonce),
+                    ciphertext: hex::encode(ciphertext),
+                })?
+            }
+            None => entries,
+        };
+
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+impl Sealing {
+    fn derive(passphrase: &str, salt: [u8; 16], iterations: u32) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, iterations, &mut key);
+        Self {
+            key,
+            salt,
+            iterations,
+        }
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+}
+
+fn read_if_exists(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(path: &Path, contents: &str) -> Result<T> {
+    serde_json::from_str(contents).map_err(|e| corrupt(path, e))
+}
+
+fn decode_hex<const N: usize>(path: &Path, value: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(value).map_err(|e| corrupt(path, e))?;
+    bytes
+        .try_into()
+        .map_err(|_| corrupt(path, format!("expected {} bytes", N)))
+}
+
+fn corrupt(path: &Path, error: impl std::fmt::Display) -> VernachainError {
+    VernachainError::ConfigurationError(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "vernachain-book-{}-{}-{}.json",
+            name,
+            std::process::id(),
+            OsRng.next_u64()
+        ))
+    }
+
+    /// Saves a one-entry book encrypted with "hunter2" and returns its path.
+    /// It uses the fewest iterations accepted, to keep the tests quick.
+    fn saved_book(name: &str) -> PathBuf {
+        let path = temp_path(name);
+        let mut book = AddressBook::new();
+        book.insert("alice", "ab".repeat(32));
+        let iterations = *KDF_ITERATION_RANGE.start();
+        book.sealing = Some(Sealing::derive("hunter2", [7; 16], iterations));
+        book.save_to(&path).unwrap();
+        path
+    }
+
+    /// Rewrites one field of a saved encrypted file.
+    fn rewrite(path: &Path, change: impl FnOnce(&mut EncryptedFile)) {
+        let mut file: EncryptedFile =
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        change(&mut file);
+        fs::write(path, serde_json::to_string(&file).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn encrypted_books_round_trip() {
+        let path = saved_book("round-trip");
+        assert!(!fs::read_to_string(&path).unwrap().contains("alice"));
+
+        let book = AddressBook::open_encrypted(&path, "hunter2").unwrap();
+        assert_eq!(book.address("alice").unwrap(), "ab".repeat(32));
+        assert_eq!(book.len(), 1);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn wrong_passphrase_is_a_validation_error() {
+        let path = saved_book("passphrase");
+        let result = AddressBook::open_encrypted(&path, "hunter3");
+        assert!(matches!(result, Err(VernachainError::ValidationError(_))));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails_to_open() {
+        let path = saved_book("tampered");
+        rewrite(&path, |file| {
+            let flipped = if file.ciphertext.starts_with('0') {
+                "1"
+            } else {
+                "0"
+            };
+            file.ciphertext.replace_range(..1, flipped);
+        });
+        let result = AddressBook::open_encrypted(&path, "hunter2");
+        assert!(matches!(result, Err(VernachainError::ValidationError(_))));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_other_versions_and_iteration_counts() {
+        let path = saved_book("version");
+        rewrite(&path, |file| file.version = FORMAT_VERSION + 1);
+        let result = AddressBook::open_encrypted(&path, "hunter2");
+        assert!(matches!(
+            result,
+            Err(VernachainError::ConfigurationError(_))
+        ));
+
+        for iterations in [0, 1_000, u32::MAX] {
+            rewrite(&path, |file| {
+                file.version = FORMAT_VERSION;
+                file.kdf_iterations = iterations;
+            });
+            let result = AddressBook::open_encrypted(&path, "hunter2");
+            assert!(matches!(
+                result,
+                Err(VernachainError::ConfigurationError(_))
+            ));
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
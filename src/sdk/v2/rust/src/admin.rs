@@ -0,0 +1,115 @@
+//! Privileged node management, behind the `admin` feature.
+//!
+//! Admin endpoints take their own token, separate from the API key, so a
+//! client only gets them when built with `admin_token`. Admin calls are never
+//! retried, since most of them change the node.
+
+use crate::{client::Transport, decode, error::Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PruneResult {
+    pub pruned_blocks: u64,
+    pub freed_bytes: u64,
+    /// Oldest block the node still keeps in full.
+    #[serde(default)]
+    pub oldest_retained_block: u64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: crate::types::ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub id: String,
+    /// Where the node wrote the snapshot, on its own filesystem.
+    pub path: String,
+    pub block_number: u64,
+    pub size_bytes: u64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: crate::types::ExtraFields,
+}
+
+/// Admin endpoints of one node; see `VernachainClient::admin`.
+#[derive(Clone)]
+pub struct NodeAdmin {
+    transport: Arc<dyn Transport>,
+    api_prefix: String,
+    strict: bool,
+}
+
+impl NodeAdmin {
+    pub(crate) fn new(transport: Arc<dyn Transport>, api_prefix: &str, strict: bool) -> Self {
+        Self {
+            transport,
+            api_prefix: api_prefix.to_string(),
+            strict,
+        }
+    }
+
+    /// Connects to a peer at `address`, given as `host:port`.
+    pub async fn add_peer(&self, address: &str) -> Result<()> {
+        self.send("POST", "/admin/peers", Some(json!({ "address": address })))
+            .await
+    }
+
+    pub async fn remove_peer(&self, peer_id: &str) -> Result<()> {
+        self.send("DELETE", &format!("/admin/peers/{}", peer_id), None)
+            .await
+    }
+
+    pub async fn set_log_level(&self, level: LogLevel) -> Result<()> {
+        self.send("PUT", "/admin/log-level", Some(json!({ "level": level })))
+            .await
+    }
+
+    /// Drops full block data older than the latest `keep_recent_blocks` on
+    /// every shard. Headers are kept.
+    pub async fn prune(&self, keep_recent_blocks: u64) -> Result<PruneResult> {
+        self.request(
+            "POST",
+            "/admin/prune",
+            Some(json!({ "keep_recent_blocks": keep_recent_blocks })),
+        )
+        .await
+    }
+
+    /// Has the node write a snapshot of its state to its data directory.
+    pub async fn snapshot(&self) -> Result<NodeSnapshot> {
+        self.request("POST", "/admin/snapshot", None).await
+    }
+
+    async fn send(
+        &self,
+        method: &str,
+        endpoint: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<()> {
+        let path = format!("{}{}", self.api_prefix, endpoint);
+        self.transport.send_raw(method, &path, body).await?;
+        Ok(())
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        endpoint: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T> {
+        let path = format!("{}{}", self.api_prefix, endpoint);
+        let data = self.transport.send(method, &path, body).await?;
+        decode::from_value(data, self.strict)
+    }
+}
@@ -0,0 +1,207 @@
+//! Exact decimal amounts, for showing and accepting values without the
+//! rounding that `f64` arithmetic introduces.
+//!
+//! An `Amount` counts the native currency's smallest unit, so `"0.1 VERNA"`
+//! parsed and formatted again is still `"0.1 VERNA"`. `parse_units` and
+//! `format_units` do the same conversion for tokens with other decimals.
+
+use crate::error::{Result, VernachainError};
+use std::{fmt, str::FromStr};
+
+/// Symbol of the native currency.
+pub const NATIVE_SYMBOL: &str = "VERNA";
+
+/// Decimal places of the native currency.
+pub const NATIVE_DECIMALS: u8 = 18;
+
+/// A quantity of the native currency, in its smallest unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(u128);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub fn from_units(units: u128) -> Self {
+        Amount(units)
+    }
+
+    pub fn units(&self) -> u128 {
+        self.0
+    }
+
+    /// Parses `"1.5"` or `"1.5 VERNA"`. Other symbols are rejected, as are
+    /// more than `NATIVE_DECIMALS` fractional digits.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let value = match text.split_once(char::is_whitespace) {
+            Some((value, symbol)) => {
+                let symbol = symbol.trim();
+                if !symbol.eq_ignore_ascii_case(NATIVE_SYMBOL) {
+                    return Err(invalid(format!(
+                        "expected an amount in {}, got {:?}",
+                        NATIVE_SYMBOL, symbol
+                    )));
+                }
+                value
+            }
+            None => text,
+        };
+        parse_units(value, NATIVE_DECIMALS).map(Amount)
+    }
+
+    /// Converts an amount the node reported as a float. The float's shortest
+    /// decimal form is taken, so `0.1` is exactly 0.1 VERNA; beyond
+    /// `NATIVE_DECIMALS` places it is rounded to the nearest unit.
+    pub fn from_f64(value: f64) -> Result<Self> {
+        if !(value.is_finite() && value >= 0.0) {
+            return Err(invalid(format!("invalid amount {}", value)));
+        }
+        let decimals = NATIVE_DECIMALS as usize;
+        let mut text = value.to_string();
+        if matches!(text.split_once('.'), Some((_, fraction)) if fraction.len() > decimals) {
+            text = format!("{:.*}", decimals, value);
+        }
+        parse_units(&text, NATIVE_DECIMALS).map(Amount)
+    }
+
+    /// The amount as the float the request types take.
+    pub fn to_f64(&self) -> f64 {
+        format_units(self.0, NATIVE_DECIMALS)
+            .parse()
+            .expect("formatted units are a valid float")
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = VernachainError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Amount::parse(text)
+    }
+}
+
+/// Formats as `"1.5 VERNA"`, without trailing zeros.
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            format_units(self.0, NATIVE_DECIMALS),
+            NATIVE_SYMBOL
+        )
+    }
+}
+
+/// Converts a decimal string such as `"12.05"` to base units of a currency
+/// with `decimals` decimal places.
+pub fn parse_units(value: &str, decimals: u8) -> Result<u128> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !digits(whole) || !digits(fraction) {
+        return Err(invalid(format!("invalid amount {:?}", value)));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(invalid(format!(
+            "amount {:?} has more than {} decimal places",
+            value, decimals
+        )));
+    }
+    let scale = 10u128
+        .checked_pow(decimals as u32)
+        .ok_or_else(|| invalid(format!("unsupported decimals {}", decimals)))?;
+    let overflow = || invalid(format!("amount {:?} is too large", value));
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| overflow())?
+    };
+    let fraction: u128 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<u128>().map_err(|_| overflow())?
+            * 10u128.pow((decimals as usize - fraction.len()) as u32)
+    };
+    whole
+        .checked_mul(scale)
+        .and_then(|units| units.checked_add(fraction))
+        .ok_or_else(overflow)
+}
+
+/// Formats base units of a currency with `decimals` decimal places, e.g.
+/// `format_units(1_050_000, 6)` is `"1.05"`.
+pub fn format_units(units: u128, decimals: u8) -> String {
+    let digits = units.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+fn invalid(message: String) -> VernachainError {
+    VernachainError::ValidationError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_units() {
+        let cases = [
+            ("0", 6, 0),
+            ("1", 6, 1_000_000),
+            ("1.05", 6, 1_050_000),
+            (".5", 6, 500_000),
+            ("0.000001", 6, 1),
+            ("12", 0, 12),
+            ("0.1", 18, 100_000_000_000_000_000),
+        ];
+        for (text, decimals, units) in cases {
+            assert_eq!(parse_units(text, decimals).unwrap(), units, "{}", text);
+        }
+        assert_eq!(format_units(1_050_000, 6), "1.05");
+        assert_eq!(format_units(1_000_000, 6), "1");
+        assert_eq!(format_units(1, 6), "0.000001");
+        assert_eq!(format_units(0, 6), "0");
+        assert_eq!(format_units(12, 0), "12");
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for text in ["", ".", "-1", "1e5", "1.2.3", " 1", "abc"] {
+            assert!(parse_units(text, 6).is_err(), "{:?}", text);
+        }
+        assert!(parse_units("0.0000001", 6).is_err());
+        assert!(parse_units("1", 39).is_err());
+        assert!(parse_units(&u128::MAX.to_string(), 1).is_err());
+    }
+
+    #[test]
+    fn amounts_round_trip_without_float_error() {
+        let amount = Amount::parse("0.1 VERNA").unwrap();
+        assert_eq!(amount.to_string(), "0.1 VERNA");
+        assert_eq!(Amount::parse("0.1").unwrap(), amount);
+        assert_eq!(Amount::parse("0.1 verna").unwrap(), amount);
+        assert_eq!(Amount::from_f64(0.1).unwrap(), amount);
+        assert_eq!(amount.to_f64(), 0.1);
+        assert!(Amount::parse("0.1 ETH").is_err());
+        assert!(Amount::from_f64(-1.0).is_err());
+        assert!(Amount::from_f64(f64::NAN).is_err());
+    }
+}
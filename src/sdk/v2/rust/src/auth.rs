@@ -0,0 +1,283 @@
+//! Request authentication.
+//!
+//! An `AuthProvider` supplies the bearer token for each request and
+//! WebSocket connection, so tokens can change over the client's lifetime.
+//! `StaticToken` covers fixed API keys, and `OAuth2ClientCredentials` fetches
+//! tokens from an OAuth2 server and refreshes them before they expire.
+//!
+//! `HmacAuth` signs every HTTP request for deployments that reject unsigned
+//! calls. The signature is the HMAC-SHA256, keyed with the shared secret, of
+//!
+//! ```text
+//! <unix timestamp>\n<METHOD>\n<path and query>\n<body>
+//! ```
+//!
+//! and travels in `X-Vernachain-Signature` as `sha256=<hex>`, the same form
+//! webhook deliveries use, next to `X-Vernachain-Key` and
+//! `X-Vernachain-Timestamp`. Nodes reject timestamps outside their replay
+//! window, so the host clock must be roughly right.
+
+use crate::{
+    error::{Result, VernachainError},
+    webhooks,
+};
+use async_trait::async_trait;
+use reqwest::Client as HttpClient;
+use serde::Deserialize;
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// Tokens are refreshed this long before their reported expiry.
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+#[async_trait]
+pub trait AuthProvider: Send + Sync {
+    /// The bearer token to send with the next request. An empty token sends
+    /// no credentials.
+    async fn token(&self) -> Result<String>;
+}
+
+/// The provider a client's transports and WebSocket connections share.
+/// Replacing it takes effect for every later request at once.
+pub(crate) struct Credentials {
+    provider: RwLock<Option<Arc<dyn AuthProvider>>>,
+}
+
+impl Credentials {
+    pub(crate) fn new(provider: Option<Arc<dyn AuthProvider>>) -> Self {
+        Self {
+            provider: RwLock::new(provider),
+        }
+    }
+
+    pub(crate) fn set(&self, provider: Option<Arc<dyn AuthProvider>>) {
+        *self.provider.write().unwrap_or_else(|e| e.into_inner()) = provider;
+    }
+}
+
+#[async_trait]
+impl AuthProvider for Credentials {
+    async fn token(&self) -> Result<String> {
+        let provider = self
+            .provider
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        match provider {
+            Some(provider) => provider.token().await,
+            None => Ok(String::new()),
+        }
+    }
+}
+
+/// A fixed API key.
+#[derive(Clone)]
+pub struct StaticToken(String);
+
+impl StaticToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+}
+
+#[async_trait]
+impl AuthProvider for StaticToken {
+    async fn token(&self) -> Result<String> {
+        Ok(self.0.clone())
+    }
+}
+
+impl fmt::Debug for StaticToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StaticToken(..)")
+    }
+}
+
+/// OAuth2 client-credentials grant. The token is cached and fetched again
+/// shortly before it expires.
+pub struct OAuth2ClientCredentials {
+    http_client: HttpClient,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: Option<String>,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+struct CachedToken {
+    access_token: String,
+    refresh_at: Option<Instant>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self) -> bool {
+        match self.refresh_at {
+            Some(refresh_at) => Instant::now() < refresh_at,
+            None => true,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+impl OAuth2ClientCredentials {
+    pub fn new(
+        token_url: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            http_client: HttpClient::new(),
+            token_url: token_url.into(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            scope: None,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    async fn fetch(&self) -> Result<CachedToken> {
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        if let Some(scope) = &self.scope {
+            form.push(("scope", scope));
+        }
+        let response = self
+            .http_client
+            .post(&self.token_url)
+            .form(&form)
+            .send()
+            .await?;
+        match response.status().as_u16() {
+            400 | 401 => return Err(VernachainError::AuthenticationError),
+            status if !(200..300).contains(&status) => {
+                return Err(VernachainError::NetworkError(response.text().await?));
+            }
+            _ => {}
+        }
+
+        let token: TokenResponse = response.json().await?;
+        // Tokens without an expiry are kept until the process restarts.
+        let refresh_at = token.expires_in.map(|seconds| {
+            Instant::now() + Duration::from_secs(seconds).saturating_sub(REFRESH_MARGIN)
+        });
+        Ok(CachedToken {
+            access_token: token.access_token,
+            refresh_at,
+        })
+    }
+}
+
+#[async_trait]
+impl AuthProvider for OAuth2ClientCredentials {
+    async fn token(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref().filter(|token| token.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+        let token = self.fetch().await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+}
+
+impl fmt::Debug for OAuth2ClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuth2ClientCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+pub const KEY_HEADER: &str = "X-Vernachain-Key";
+pub const TIMESTAMP_HEADER: &str = "X-Vernachain-Timestamp";
+pub const SIGNATURE_HEADER: &str = webhooks::SIGNATURE_HEADER;
+
+#[derive(Clone)]
+pub struct HmacAuth {
+    key_id: String,
+    secret: String,
+}
+
+impl HmacAuth {
+    pub fn new(key_id: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self {
+            key_id: key_id.into(),
+            secret: secret.into(),
+        }
+    }
+
+    /// Headers authenticating a request sent at `timestamp`, in Unix seconds.
+    pub fn headers(
+        &self,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        timestamp: i64,
+    ) -> [(&'static str, String); 3] {
+        let mut payload =
+            format!("{}\n{}\n{}\n", timestamp, method.to_ascii_uppercase(), path).into_bytes();
+        payload.extend_from_slice(body);
+        [
+            (KEY_HEADER, self.key_id.clone()),
+            (TIMESTAMP_HEADER, timestamp.to_string()),
+            (SIGNATURE_HEADER, webhooks::sign(&self.secret, &payload)),
+        ]
+    }
+}
+
+impl fmt::Debug for HmacAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacAuth")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_the_timestamp_method_path_and_body() {
+        // hmac.new(b"s3cret", b"1700000000\nPOST\n/api/v1/transactions?shard_id=0\n"
+        //     b'{"amount":1.5}', hashlib.sha256).hexdigest()
+        let headers = HmacAuth::new("key-1", "s3cret").headers(
+            "post",
+            "/api/v1/transactions?shard_id=0",
+            br#"{"amount":1.5}"#,
+            1_700_000_000,
+        );
+        assert_eq!(
+            headers,
+            [
+                (KEY_HEADER, "key-1".to_string()),
+                (TIMESTAMP_HEADER, "1700000000".to_string()),
+                (
+                    SIGNATURE_HEADER,
+                    "sha256=8dc860de93e66a012883964eb32a3d264bca8030248c0f6dae10859aebd33926"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+}
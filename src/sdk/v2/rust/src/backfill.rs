@@ -0,0 +1,233 @@
+//! Fetching long ranges of history in parallel.
+//!
+//! A `Backfiller` splits a block range into chunks and fetches several at
+//! once, retrying a failed chunk as its `RetryPolicy` allows. Chunks are
+//! handed over in block order however they finish, so a handler always sees
+//! a contiguous prefix of the range and can record its position after each
+//! one, e.g. with a `CursorStore`.
+
+use crate::{
+    client::VernachainClient,
+    concurrency::AdaptiveConcurrency,
+    error::{Result, VernachainError},
+    retry::RetryPolicy,
+    types::{Block, Log, LogFilter, ShardId},
+};
+use std::{collections::VecDeque, future::Future, ops::RangeInclusive, sync::Arc};
+use tokio::task::JoinHandle;
+
+const DEFAULT_CHUNK_SIZE: u64 = 100;
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// How far a backfill has got, reported after each chunk is handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillProgress {
+    pub blocks: RangeInclusive<u64>,
+    /// Every block of the range up to and including this one has been
+    /// handled.
+    pub completed_through: Option<u64>,
+    pub completed_blocks: u64,
+}
+
+impl BackfillProgress {
+    pub fn total_blocks(&self) -> u64 {
+        range_len(&self.blocks)
+    }
+
+    /// Share of the range handled so far, from 0.0 to 1.0.
+    pub fn fraction(&self) -> f64 {
+        match self.total_blocks() {
+            0 => 1.0,
+            total => self.completed_blocks as f64 / total as f64,
+        }
+    }
+}
+
+type ProgressCallback = Arc<dyn Fn(&BackfillProgress) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Backfiller {
+    client: VernachainClient,
+    chunk_size: u64,
+    concurrency: usize,
+    retry: RetryPolicy,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl Backfiller {
+    /// Fetches chunks of 100 blocks, 8 at a time, retrying each up to 3 times.
+    pub fn new(client: &VernachainClient) -> Self {
+        Self {
+            client: client.clone(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            retry: RetryPolicy::default(),
+            on_progress: None,
+        }
+    }
+
+    /// Blocks per chunk.
+    pub fn chunk_size(mut self, blocks: u64) -> Self {
+        self.chunk_size = blocks.max(1);
+        self
+    }
+
+    /// Most chunks fetched at once; fewer are while the node rate limits or
+    /// slows down. Finished chunks waiting for an earlier one count towards
+    /// the limit, so memory stays bounded too.
+    pub fn concurrency(mut self, chunks: usize) -> Self {
+        self.concurrency = chunks.max(1);
+        self
+    }
+
+    /// Retries for a chunk whose fetch fails, on top of the client's own.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Called after each chunk is handled.
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&BackfillProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Fetches every chunk of `blocks` with `fetch` and passes the results to
+    /// `handle` in block order. Stops at the first chunk that still fails
+    /// after retrying, or the first error from `handle`; later chunks
+    /// already in flight are abandoned.
+    pub async fn run<T, F, Fut, H, HFut>(
+        &self,
+        blocks: RangeInclusive<u64>,
+        fetch: F,
+        mut handle: H,
+    ) -> Result<()>
+    where
+        T: Send + 'static,
+        F: Fn(VernachainClient, RangeInclusive<u64>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+        H: FnMut(RangeInclusive<u64>, T) -> HFut,
+        HFut: Future<Output = Result<()>>,
+    {
+        let fetch = Arc::new(fetch);
+        let limit = Arc::new(AdaptiveConcurrency::new(self.concurrency));
+        let mut chunks = chunks(blocks.clone(), self.chunk_size);
+        let mut in_flight = InFlight(VecDeque::new());
+        let mut progress = BackfillProgress {
+            completed_through: None,
+            completed_blocks: 0,
+            blocks,
+        };
+        loop {
+            while in_flight.0.len() < self.concurrency {
+                let Some(chunk) = chunks.next() else {
+                    break;
+                };
+                let (fetch, limit) = (fetch.clone(), limit.clone());
+                let (client, retry, range) = (self.client.clone(), self.retry, chunk.clone());
+                let task = tokio::spawn(async move {
+                    retry
+                        .run("GET", || limit.run(fetch(client.clone(), range.clone())))
+                        .await
+                });
+                in_flight.0.push_back((chunk, task));
+            }
+            let Some((chunk, task)) = in_flight.0.pop_front() else {
+                return Ok(());
+            };
+            let value = task.await.map_err(|e| {
+                VernachainError::InternalError(format!("backfill task failed: {}", e))
+            })??;
+            handle(chunk.clone(), value).await?;
+
+            progress.completed_through = Some(*chunk.end());
+            progress.completed_blocks += range_len(&chunk);
+            if let Some(callback) = &self.on_progress {
+                callback(&progress);
+            }
+        }
+    }
+
+    /// Blocks of `shard_id` in `blocks`, handed to `handle` a chunk at a time
+    /// in ascending order.
+    pub async fn blocks<H, HFut>(
+        &self,
+        shard_id: ShardId,
+        blocks: RangeInclusive<u64>,
+        handle: H,
+    ) -> Result<()>
+    where
+        H: FnMut(RangeInclusive<u64>, Vec<Block>) -> HFut,
+        HFut: Future<Output = Result<()>>,
+    {
+        let fetch = move |client: VernachainClient, chunk: RangeInclusive<u64>| async move {
+            let mut blocks = Vec::with_capacity(range_len(&chunk) as usize);
+            for number in chunk {
+                blocks.push(client.get_block(number, shard_id).await?);
+            }
+            Ok(blocks)
+        };
+        self.run(blocks, fetch, handle).await
+    }
+
+    /// Logs matching `filter` in `blocks`, handed to `handle` a chunk at a
+    /// time in chain order. The filter needs a shard, and its own block range
+    /// is ignored.
+    pub async fn logs<H, HFut>(
+        &self,
+        filter: LogFilter,
+        blocks: RangeInclusive<u64>,
+        handle: H,
+    ) -> Result<()>
+    where
+        H: FnMut(RangeInclusive<u64>, Vec<Log>) -> HFut,
+        HFut: Future<Output = Result<()>>,
+    {
+        if filter.shard_id.is_none() {
+            return Err(VernachainError::ValidationError(
+                "backfill log filter needs a shard".to_string(),
+            ));
+        }
+        let fetch = move |client: VernachainClient, chunk: RangeInclusive<u64>| {
+            let filter = filter
+                .clone()
+                .from_block(*chunk.start())
+                .to_block(*chunk.end());
+            async move { client.get_logs(&filter).await }
+        };
+        self.run(blocks, fetch, handle).await
+    }
+}
+
+/// Fetches in block order, aborted if the backfill stops early.
+struct InFlight<T>(VecDeque<(RangeInclusive<u64>, JoinHandle<Result<T>>)>);
+
+impl<T> Drop for InFlight<T> {
+    fn drop(&mut self) {
+        for (_, task) in &self.0 {
+            task.abort();
+        }
+    }
+}
+
+fn chunks(blocks: RangeInclusive<u64>, size: u64) -> impl Iterator<Item = RangeInclusive<u64>> {
+    let end = *blocks.end();
+    let mut next = (!blocks.is_empty()).then(|| *blocks.start());
+    std::iter::from_fn(move || {
+        let start = next?;
+        let chunk_end = start.saturating_add(size - 1).min(end);
+        next = chunk_end.checked_add(1).filter(|n| *n <= end);
+        Some(start..=chunk_end)
+    })
+}
+
+fn range_len(blocks: &RangeInclusive<u64>) -> u64 {
+    if blocks.is_empty() {
+        0
+    } else {
+        blocks.end() - blocks.start() + 1
+    }
+}
@@ -0,0 +1,414 @@
+//! `vernachain`, a command-line client for Vernachain nodes.
+//!
+//! The node is taken from `--config`, or else the `VERNACHAIN_*` environment
+//! variables read by `ClientConfig::from_env`, with `--url`, `--network` and
+//! `--api-key` overriding either. Results are printed as JSON.
+//!
+//! Amounts are exact decimals of the native currency, with or without the
+//! symbol: `1.5` and `"1.5 VERNA"` are the same.
+//!
+//! With `--address-book`, labels from that book can be used wherever an
+//! account or contract address is expected. An encrypted book is opened with
+//! the passphrase in `VERNACHAIN_ADDRESS_BOOK_PASSPHRASE`.
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use std::{collections::HashMap, path::PathBuf, process::ExitCode, time::Duration};
+use vernachain_sdk::{
+    generate_keypair, AddressBook, Amount, BridgeTransferRequest, ChainEvent, ChainId,
+    ClientConfig, ContractDeployRequest, ContractType, Keypair, Result, ShardId, StakeRequest,
+    TransactionRequest, VernachainClient, VernachainError,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "vernachain",
+    version,
+    about = "Command-line client for Vernachain nodes"
+)]
+struct Cli {
+    #[command(flatten)]
+    node: NodeArgs,
+    /// Address book whose labels can stand in for addresses.
+    #[arg(long, global = true)]
+    address_book: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct NodeArgs {
+    /// TOML client configuration; see `ClientConfig`.
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Node URL, overriding the configured network.
+    #[arg(long, global = true)]
+    url: Option<String>,
+    /// `mainnet`, `testnet` or `devnet`.
+    #[arg(long, global = true)]
+    network: Option<String>,
+    /// API key sent with every request.
+    #[arg(long, global = true)]
+    api_key: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print an account's balance.
+    Balance { address: String },
+    /// Fund an account from a test network's faucet.
+    Faucet {
+        address: String,
+        amount: Amount,
+        /// Solved captcha or access token, if the faucet asks for one.
+        #[arg(long)]
+        captcha: Option<String>,
+    },
+    /// Send a transfer from the account in a key file.
+    Send(SendArgs),
+    /// Deploy a contract.
+    Deploy(DeployArgs),
+    /// Call a contract method without sending a transaction.
+    Call {
+        contract: String,
+        method: String,
+        /// Call parameters as JSON.
+        #[arg(default_value = "{}")]
+        params: String,
+    },
+    /// Stake with a validator.
+    Stake {
+        validator: String,
+        amount: Amount,
+        /// Restake rewards as they are paid out.
+        #[arg(long)]
+        auto_compound: Option<bool>,
+    },
+    /// Withdraw stake from a validator.
+    Unstake { validator: String, amount: Amount },
+    /// Bridge funds to another chain.
+    Bridge(BridgeArgs),
+    /// Print a shard's new blocks as they arrive, one JSON object per line.
+    Subscribe {
+        #[arg(long, default_value_t = 0)]
+        shard: u64,
+    },
+    /// Create and inspect account keys.
+    #[command(subcommand)]
+    Keys(KeysCommand),
+}
+
+#[derive(Args)]
+struct SendArgs {
+    /// File holding the sender's secret key, as hex or a PKCS#8 PEM document,
+    /// used to sign the transfer.
+    #[arg(long)]
+    key: PathBuf,
+    #[arg(long)]
+    to: String,
+    #[arg(long)]
+    amount: Amount,
+    #[arg(long)]
+    gas_price: Option<f64>,
+    #[arg(long)]
+    gas_limit: Option<u64>,
+    /// Wait until the transaction is this many blocks deep.
+    #[arg(long)]
+    confirmations: Option<u64>,
+    /// How long `--confirmations` waits, in seconds.
+    #[arg(long, default_value_t = 120)]
+    timeout: u64,
+}
+
+#[derive(Args)]
+struct DeployArgs {
+    /// `ERC20`, `ERC721`, `MULTISIG`, `PROXY` or a custom contract kind.
+    #[arg(long = "type")]
+    contract_type: String,
+    /// Constructor parameters as a JSON object.
+    #[arg(long, default_value = "{}")]
+    params: String,
+    #[arg(long, default_value_t = 0)]
+    shard: u64,
+    #[arg(long)]
+    gas_limit: Option<u64>,
+}
+
+#[derive(Args)]
+struct BridgeArgs {
+    /// Target chain, e.g. `ethereum`.
+    #[arg(long)]
+    chain: String,
+    #[arg(long)]
+    amount: Amount,
+    #[arg(long)]
+    recipient: String,
+    #[arg(long)]
+    gas_limit: Option<u64>,
+    /// Wait up to this many seconds for the transfer to complete.
+    #[arg(long)]
+    wait: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum KeysCommand {
+    /// Create a random key and print its address.
+    Generate {
+        /// Write the secret key to this new file instead of printing it. On
+        /// Unix only the owner can read it.
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Write a PKCS#8 PEM document instead of a hex seed.
+        #[arg(long)]
+        pem: bool,
+    },
+    /// Print the address and public key of a key file.
+    Show { key: PathBuf },
+    /// Sign a message with a key file and print the hex signature.
+    Sign { key: PathBuf, message: String },
+}
+
+#[derive(Serialize)]
+struct KeyInfo {
+    address: String,
+    public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let client = match cli.command {
+        Command::Keys(command) => return keys(command),
+        _ => connect(cli.node)?,
+    };
+    let book = match &cli.address_book {
+        Some(path) => match std::env::var("VERNACHAIN_ADDRESS_BOOK_PASSPHRASE") {
+            Ok(passphrase) => AddressBook::open_encrypted(path, &passphrase)?,
+            Err(_) => AddressBook::open(path)?,
+        },
+        None => AddressBook::new(),
+    };
+
+    match cli.command {
+        Command::Balance { address } => print(&client.get_balance(book.resolve(&address)).await?),
+        Command::Faucet {
+            address,
+            amount,
+            captcha,
+        } => {
+            let address = book.resolve(&address);
+            let grant = match captcha {
+                Some(token) => {
+                    client
+                        .request_faucet_funds_with_captcha(address, amount.to_f64(), &token)
+                        .await?
+                }
+                None => {
+                    client
+                        .request_faucet_funds(address, amount.to_f64())
+                        .await?
+                }
+            };
+            print(&grant)
+        }
+        Command::Send(args) => {
+            let keypair = load_key(&args.key)?;
+            let mut builder = TransactionRequest::builder()
+                .sender(keypair.address())
+                .recipient(book.resolve(&args.to))
+                .amount(args.amount.to_f64())
+                .shard_id(client.resolve_shard(&keypair.address()).await?);
+            if let Some(gas_price) = args.gas_price {
+                builder = builder.gas_price(gas_price);
+            }
+            if let Some(gas_limit) = args.gas_limit {
+                builder = builder.gas_limit(gas_limit);
+            }
+            let mut request = builder.build()?;
+            request.nonce = Some(client.nonces().reserve(&client, &request.sender).await?);
+            request.timestamp = Some(chrono::Utc::now());
+            keypair.sign_transaction(&mut request)?;
+            let tx = match args.confirmations {
+                Some(confirmations) => {
+                    let timeout = Duration::from_secs(args.timeout);
+                    client
+                        .create_transaction_and_wait(request, confirmations, timeout)
+                        .await?
+                }
+                None => client.create_transaction(request).await?,
+            };
+            print(&tx)
+        }
+        Command::Deploy(args) => {
+            let request = ContractDeployRequest {
+                contract_type: ContractType::from(args.contract_type),
+                params: parse_json::<HashMap<String, serde_json::Value>>(&args.params)?,
+                shard_id: ShardId(args.shard),
+                gas_limit: args.gas_limit,
+                salt: None,
+            };
+            print(&client.deploy_contract(request).await?)
+        }
+        Command::Call {
+            contract,
+            method,
+            params,
+        } => {
+            let params = parse_json(&params)?;
+            print(
+                &client
+                    .call_contract(book.resolve(&contract), &method, params)
+                    .await?,
+            )
+        }
+        Command::Stake {
+            validator,
+            amount,
+            auto_compound,
+        } => {
+            let request = StakeRequest {
+                validator_address: book.resolve(&validator).to_string(),
+                amount: amount.to_f64(),
+                auto_compound,
+            };
+            print(&client.stake(request).await?)
+        }
+        Command::Unstake { validator, amount } => print(
+            &client
+                .unstake(amount.to_f64(), book.resolve(&validator))
+                .await?,
+        ),
+        Command::Bridge(args) => {
+            let request = BridgeTransferRequest {
+                target_chain: ChainId::new(args.chain),
+                amount: args.amount.to_f64(),
+                recipient: args.recipient,
+                gas_limit: args.gas_limit,
+            };
+            let transfer = match args.wait {
+                Some(secs) => {
+                    client
+                        .bridge_transfer_and_wait(request, Duration::from_secs(secs))
+                        .await?
+                }
+                None => client.bridge_transfer(request).await?,
+            };
+            print(&transfer)
+        }
+        Command::Subscribe { shard } => {
+            let mut blocks = client.subscribe_blocks(ShardId(shard)).await?;
+            while let Some(event) = blocks.next_message().await {
+                match event {
+                    ChainEvent::NewBlock(block) => {
+                        println!("{}", serde_json::to_string(&block)?);
+                    }
+                    ChainEvent::Reorg { dropped, .. } => {
+                        eprintln!("reorg: {} blocks dropped", dropped.len());
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Keys(_) => unreachable!("handled before connecting"),
+    }
+}
+
+fn connect(node: NodeArgs) -> Result<VernachainClient> {
+    let mut config = match &node.config {
+        Some(path) => ClientConfig::from_file(path)?,
+        None => ClientConfig::from_env()?,
+    };
+    if node.url.is_some() {
+        config.url = node.url;
+    }
+    if node.network.is_some() {
+        config.url = None;
+        config.network = node.network;
+    }
+    if node.api_key.is_some() {
+        config.api_key = node.api_key;
+    }
+    config.into_builder()?.build()
+}
+
+fn keys(command: KeysCommand) -> Result<()> {
+    match command {
+        KeysCommand::Generate { out, pem } => {
+            let keypair = generate_keypair();
+            let secret = if pem {
+                keypair.to_pem()?
+            } else {
+                keypair.to_secret_hex()
+            };
+            let secret_key = match out {
+                Some(path) => {
+                    write_secret(&path, &secret)?;
+                    None
+                }
+                None => Some(secret),
+            };
+            print(&KeyInfo {
+                secret_key,
+                ..key_info(&keypair)
+            })
+        }
+        KeysCommand::Show { key } => print(&key_info(&load_key(&key)?)),
+        KeysCommand::Sign { key, message } => print(&load_key(&key)?.sign(message.as_bytes())),
+    }
+}
+
+fn key_info(keypair: &Keypair) -> KeyInfo {
+    KeyInfo {
+        address: keypair.address(),
+        public_key: keypair.public_key().to_hex(),
+        secret_key: None,
+    }
+}
+
+/// Creates `path` holding `secret`, refusing to replace an existing file so
+/// the mode is always set and no older key is lost.
+fn write_secret(path: &PathBuf, secret: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    std::io::Write::write_all(&mut file, secret.as_bytes())?;
+    Ok(())
+}
+
+/// Reads a secret key saved as a hex seed or a PEM document.
+fn load_key(path: &PathBuf) -> Result<Keypair> {
+    let contents = std::fs::read_to_string(path)?;
+    let contents = contents.trim();
+    if contents.starts_with("-----BEGIN") {
+        Keypair::from_pem(contents)
+    } else {
+        Keypair::from_secret_hex(contents)
+    }
+}
+
+fn parse_json<T: serde::de::DeserializeOwned>(text: &str) -> Result<T> {
+    serde_json::from_str(text)
+        .map_err(|e| VernachainError::ValidationError(format!("invalid JSON argument: {}", e)))
+}
+
+fn print<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    subscription::{Subscription, SubscriptionEvent},
+    types::*,
+};
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+/// Delivers blocks from `next_block` up to the head from history, then
+/// follows a block subscription, filling any blocks it skipped. Reorgs of
+/// delivered blocks are passed on; blocks of a reorg that haven't been
+/// delivered yet arrive as new blocks.
+pub(crate) struct BlockStream {
+    client: VernachainClient,
+    shard_id: ShardId,
+    next_block: u64,
+    tx: broadcast::Sender<SubscriptionEvent<ChainEvent>>,
+}
+
+impl BlockStream {
+    pub(crate) fn new(
+        client: VernachainClient,
+        shard_id: ShardId,
+        from_block: u64,
+        tx: broadcast::Sender<SubscriptionEvent<ChainEvent>>,
+    ) -> Self {
+        Self {
+            client,
+            shard_id,
+            next_block: from_block,
+            tx,
+        }
+    }
+
+    pub(crate) async fn run(mut self, blocks: Subscription<ChainEvent>) {
+        let shutdown = self.client.shutdown_token().clone();
+        let result = tokio::select! {
+            _ = shutdown.cancelled() => Ok(()),
+            result = self.follow(blocks) => result,
+        };
+        match result {
+            Ok(()) | Err(VernachainError::Cancelled) => {}
+            Err(e) => error!("Block stream on shard {} stopped: {}", self.shard_id, e),
+        }
+    }
+
+    async fn follow(&mut self, mut blocks: Subscription<ChainEvent>) -> Result<()> {
+        let head = self.client.get_latest_block(self.shard_id).await?;
+        self.backfill(head.number).await?;
+        while let Some(event) = blocks.recv().await {
+            match event {
+                SubscriptionEvent::Message(ChainEvent::NewBlock(block)) => {
+                    self.apply(block).await?
+                }
+                SubscriptionEvent::Message(ChainEvent::Reorg {
+                    common_ancestor,
+                    dropped,
+                    added,
+                }) => {
+                    let delivered = dropped
+                        .first()
+                        .is_some_and(|block| block.number < self.next_block);
+                    if !delivered {
+                        for block in added {
+                            self.apply(block).await?;
+                        }
+                        continue;
+                    }
+                    self.next_block = match added.last() {
+                        Some(block) => block.number + 1,
+                        None => dropped[0].number,
+                    };
+                    self.send(ChainEvent::Reorg {
+                        common_ancestor,
+                        dropped,
+                        added,
+                    })?;
+                }
+                SubscriptionEvent::MissedMessages(skipped) => {
+                    // The next block fills the gap from history.
+                    warn!(
+                        "Block stream on shard {} missed {} blocks",
+                        self.shard_id, skipped
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delivers blocks `next_block..=to` from history.
+    async fn backfill(&mut self, to: u64) -> Result<()> {
+        while self.next_block <= to {
+            let block = self
+                .client
+                .get_block(self.next_block, self.shard_id)
+                .await?;
+            self.send(ChainEvent::NewBlock(block))?;
+            self.next_block += 1;
+        }
+        Ok(())
+    }
+
+    async fn apply(&mut self, block: Block) -> Result<()> {
+        if block.number < self.next_block {
+            return Ok(());
+        }
+        if block.number > self.next_block {
+            self.backfill(block.number - 1).await?;
+        }
+        self.next_block = block.number + 1;
+        self.send(ChainEvent::NewBlock(block))
+    }
+
+    fn send(&self, event: ChainEvent) -> Result<()> {
+        self.tx
+            .send(SubscriptionEvent::Message(event))
+            .map(|_| ())
+            .map_err(|_| VernachainError::Cancelled)
+    }
+}
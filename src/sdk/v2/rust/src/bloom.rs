@@ -0,0 +1,186 @@
+//! Block-level log bloom filters.
+//!
+//! Each block header carries a 2048-bit bloom over the addresses and topics of
+//! the logs it contains. For every item, three bits are set: the low 11 bits of
+//! each of the first three big-endian 16-bit words of `sha256(item)`, where
+//! `item` is the lowercase address or topic string. A clear bit proves absence;
+//! set bits only mean the block may match.
+
+use crate::{
+    error::{Result, VernachainError},
+    types::{BlockHeader, LogFilter},
+};
+use sha2::{Digest, Sha256};
+
+pub const BLOOM_BYTES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bloom([u8; BLOOM_BYTES]);
+
+impl Bloom {
+    pub fn from_hex(data: &str) -> Result<Self> {
+        let bytes = hex::decode(data.trim_start_matches("0x"))
+            .map_err(|e| VernachainError::ValidationError(format!("invalid bloom: {}", e)))?;
+        let bytes: [u8; BLOOM_BYTES] = bytes.try_into().map_err(|b: Vec<u8>| {
+            VernachainError::ValidationError(format!(
+                "bloom must be {} bytes, got {}",
+                BLOOM_BYTES,
+                b.len()
+            ))
+        })?;
+        Ok(Self(bytes))
+    }
+
+    pub fn contains(&self, item: &str) -> bool {
+        bit_indices(item)
+            .iter()
+            .all(|&bit| self.0[BLOOM_BYTES - 1 - bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// False only when no log in the block can match `filter`'s addresses and
+    /// topics. Event names are not part of the bloom and are ignored.
+    pub fn may_contain(&self, filter: &LogFilter) -> bool {
+        let address_match =
+            filter.addresses.is_empty() || filter.addresses.iter().any(|a| self.contains(a));
+        address_match && filter.topics.iter().flatten().all(|t| self.contains(t))
+    }
+}
+
+/// Whether a block with `header` could hold logs matching `filter`, taking the
+/// filter's shard and block range into account. Headers without a bloom are
+/// always treated as possible matches.
+pub fn may_contain(header: &BlockHeader, filter: &LogFilter) -> bool {
+    if filter
+        .shard_id
+        .is_some_and(|shard| shard != header.shard_id)
+        || filter.from_block.is_some_and(|from| header.number < from)
+        || filter.to_block.is_some_and(|to| header.number > to)
+    {
+        return false;
+    }
+    match header.logs_bloom.as_deref().map(Bloom::from_hex) {
+        Some(Ok(bloom)) => bloom.may_contain(filter),
+        _ => true,
+    }
+}
+
+fn bit_indices(item: &str) -> [usize; 3] {
+    let digest = Sha256::digest(item.to_ascii_lowercase().as_bytes());
+    let word = |i: usize| (u16::from_be_bytes([digest[2 * i], digest[2 * i + 1]]) & 0x7ff) as usize;
+    [word(0), word(1), word(2)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ShardId;
+
+    const ADDRESS: &str = "0xAbCd000000000000000000000000000000000001";
+    const TOPIC: &str = "Transfer(address,address,uint256)";
+
+    /// The bloom over `ADDRESS` and `TOPIC`, as Python's
+    /// `int.to_bytes(256, "big")` gives it with each item's bits set, or with
+    /// its bytes reversed.
+    fn bloom_hex(reversed: bool) -> String {
+        let mut bytes = [0u8; BLOOM_BYTES];
+        for (index, mask) in [
+            (33, 0x10),
+            (39, 0x08),
+            (84, 0x40),
+            (119, 0x01),
+            (123, 0x20),
+            (225, 0x10),
+        ] {
+            let index = if reversed {
+                BLOOM_BYTES - 1 - index
+            } else {
+                index
+            };
+            bytes[index] = mask;
+        }
+        hex::encode(bytes)
+    }
+
+    fn header(logs_bloom: Option<String>) -> BlockHeader {
+        serde_json::from_value(serde_json::json!({
+            "number": 10,
+            "hash": "0xaa",
+            "previous_hash": "0x99",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "validator": "validator",
+            "shard_id": 0,
+            "merkle_root": "",
+            "state_root": "",
+            "transaction_count": 1,
+            "logs_bloom": logs_bloom,
+        }))
+        .unwrap()
+    }
+
+    fn filter(address: &str, topic: &str) -> LogFilter {
+        LogFilter {
+            addresses: vec![address.to_string()],
+            topics: vec![None, Some(topic.to_string())],
+            ..LogFilter::default()
+        }
+    }
+
+    #[test]
+    fn contains_the_items_it_was_built_from() {
+        let bloom = Bloom::from_hex(&format!("0x{}", bloom_hex(false))).unwrap();
+        assert!(bloom.contains(ADDRESS));
+        assert!(bloom.contains(&ADDRESS.to_ascii_lowercase()));
+        assert!(bloom.contains(TOPIC));
+        assert!(!bloom.contains("Approval(address,address,uint256)"));
+        assert!(!bloom.contains("0x0000000000000000000000000000000000000002"));
+
+        // The same bits counted from the other end of the filter.
+        let reversed = Bloom::from_hex(&bloom_hex(true)).unwrap();
+        assert!(!reversed.contains(ADDRESS));
+        assert!(!reversed.contains(TOPIC));
+    }
+
+    #[test]
+    fn matches_filters_on_addresses_and_topics() {
+        let bloom = Bloom::from_hex(&bloom_hex(false)).unwrap();
+        assert!(bloom.may_contain(&filter(ADDRESS, TOPIC)));
+        assert!(bloom.may_contain(&LogFilter::default()));
+        assert!(!bloom.may_contain(&filter(ADDRESS, "Approval(address,address,uint256)")));
+        assert!(!bloom.may_contain(&filter("0x0000000000000000000000000000000000000002", TOPIC)));
+    }
+
+    #[test]
+    fn headers_without_a_usable_bloom_may_match() {
+        let filter = filter("0x0000000000000000000000000000000000000002", TOPIC);
+        assert!(!may_contain(&header(Some(bloom_hex(false))), &filter));
+        assert!(may_contain(&header(None), &filter));
+        assert!(may_contain(
+            &header(Some("0xnot-a-bloom".to_string())),
+            &filter
+        ));
+        assert!(may_contain(&header(Some("00ff".to_string())), &filter));
+    }
+
+    #[test]
+    fn checks_shard_and_block_range_first() {
+        let header = header(Some(bloom_hex(false)));
+        let matching = filter(ADDRESS, TOPIC);
+        assert!(may_contain(&header, &matching));
+        for filter in [
+            LogFilter {
+                shard_id: Some(ShardId(1)),
+                ..matching.clone()
+            },
+            LogFilter {
+                from_block: Some(11),
+                ..matching.clone()
+            },
+            LogFilter {
+                to_block: Some(9),
+                ..matching.clone()
+            },
+        ] {
+            assert!(!may_contain(&header, &filter));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Client-side checks for bridge relayer attestations.
+
+use crate::{merkle, types::BridgeProof};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use std::collections::HashSet;
+
+/// Verifies that the transfer is included under the proof's root and that at
+/// least `threshold` distinct validators from `trusted_validators` signed it.
+///
+/// `trusted_validators` are hex-encoded ed25519 public keys the caller already
+/// trusts; keys named inside the proof itself are never taken on faith.
+pub fn verify_bridge_proof(
+    proof: &BridgeProof,
+    trusted_validators: &[&str],
+    threshold: usize,
+) -> bool {
+    if !merkle::fold_path(&proof.tx_hash, &proof.merkle_proof).eq_ignore_ascii_case(&proof.root) {
+        return false;
+    }
+
+    let trusted: HashSet<String> = trusted_validators
+        .iter()
+        .map(|key| key.to_ascii_lowercase())
+        .collect();
+    let mut signers = HashSet::new();
+    for attestation in &proof.signatures {
+        let public_key = attestation.public_key.to_ascii_lowercase();
+        if !trusted.contains(&public_key) || signers.contains(&public_key) {
+            continue;
+        }
+        if verify_signature(
+            &public_key,
+            &attestation.signature,
+            proof.tx_hash.as_bytes(),
+        ) {
+            signers.insert(public_key);
+        }
+    }
+
+    signers.len() >= threshold
+}
+
+pub(crate) fn verify_signature(public_key: &str, signature: &str, message: &[u8]) -> bool {
+    let Ok(key_bytes) = hex::decode(public_key) else {
+        return false;
+    };
+    let Ok(key_bytes) = <[u8; 32]>::try_from(key_bytes.as_slice()) else {
+        return false;
+    };
+    let Ok(key) = VerifyingKey::from_bytes(&key_bytes) else {
+        return false;
+    };
+    let Ok(sig_bytes) = hex::decode(signature) else {
+        return false;
+    };
+    // Validators signing with PyNaCl may hand back the signed message
+    // (signature followed by the payload); the signature is its first 64 bytes.
+    let Some(sig_bytes) = sig_bytes.get(..64) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_slice(sig_bytes) else {
+        return false;
+    };
+    key.verify(message, &signature).is_ok()
+}
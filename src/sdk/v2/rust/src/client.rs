@@ -1,230 +1,3272 @@
 use crate::{
+    abi::ContractAbi,
+    auth::{AuthProvider, Credentials, HmacAuth, StaticToken},
+    block_stream::BlockStream,
+    codec,
+    concurrency::AdaptiveConcurrency,
+    connection::{ConnectionEvent, ConnectionMonitor},
+    cursor::CursorStore,
+    decode,
+    encoding::BinaryEncoding,
     error::{Result, VernachainError},
+    history::{HistoryFormat, HistoryWriter},
+    log_stream::LogStream,
+    logging, merkle,
+    network::Network,
+    nonce::NonceManager,
+    poll::poll_until,
+    portfolio::{AccountHoldings, Portfolio},
+    reorg::ReorgTracker,
+    retry::RetryPolicy,
+    routing::AddressRouter,
+    snapshot::{SnapshotBlock, SnapshotFormat, SnapshotWriter},
+    sponsor,
+    subscription::{Subscription, SubscriptionEvent},
+    templates::ContractTemplate,
     types::*,
+    ws::{self, ConnectionManager, Keepalive, MessageSource, WsStream},
 };
-use futures_util::{SinkExt, StreamExt};
-use reqwest::{header::{HeaderMap, HeaderValue}, Client as HttpClient};
-use serde::de::DeserializeOwned;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt, TryStreamExt};
+use rand_core::{OsRng, RngCore};
+use reqwest::{
+    header::{HeaderValue, ACCEPT, CONTENT_TYPE},
+    Client as HttpClient,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
-use std::sync::Arc;
-use tokio::sync::broadcast;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use tracing::{debug, error, info};
+use std::{
+    collections::HashSet,
+    future::Future,
+    ops::{Range, RangeInclusive},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{io::AsyncWrite, sync::broadcast};
+use tokio_tungstenite::{
+    connect_async_tls_with_config, tungstenite::protocol::Message, Connector,
+};
+use tokio_util::{
+    sync::{CancellationToken, DropGuard},
+    task::TaskTracker,
+};
+use tracing::{debug_span, error, info, warn, Instrument};
 use url::Url;
 
-#[derive(Clone)]
-pub struct VernachainClient {
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const HISTORY_PAGE_SIZE: u32 = 100;
+const PORTFOLIO_CONCURRENCY: usize = 8;
+const MULTICALL_CONCURRENCY: usize = 16;
+const DEFAULT_BULK_CONCURRENCY: usize = 16;
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+const DEFAULT_SUBSCRIPTION_BUFFER: usize = 100;
+const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(20);
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Minimum gas price increase, as a factor, for a replacement to be accepted.
+const REPLACEMENT_GAS_BUMP: f64 = 1.1;
+
+/// API versions this SDK can speak, newest first.
+const SUPPORTED_API_VERSIONS: &[&str] = &["v2", "v1"];
+const DEFAULT_API_VERSION: &str = "v1";
+
+#[derive(Deserialize)]
+struct BalanceResponse {
+    balance: f64,
+}
+
+#[derive(Deserialize)]
+struct CodeResponse {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct StorageResponse {
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct GasPriceResponse {
+    gas_price: f64,
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    results: Vec<BatchItemResult>,
+}
+
+#[derive(Serialize)]
+struct MulticallRequest<'a> {
+    calls: &'a [ContractCall],
+}
+
+#[derive(Deserialize)]
+struct MulticallResponse {
+    results: Vec<ContractCallResult>,
+}
+
+#[derive(Serialize)]
+struct AccountTransactionsQuery<'a> {
+    shard_id: ShardId,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct TimeRangeQuery {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct GasStatsQuery {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    resolution: StatsResolution,
+}
+
+#[derive(Serialize)]
+struct StatsHistoryQuery {
+    metric: StatsMetric,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    resolution: StatsResolution,
+}
+
+/// Carries the client's calls to the node. Each call is a REST-style method
+/// and path, API prefix included, and yields the decoded response body.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value>;
+
+    /// Like `send`, for calls returning many blocks, headers or logs, which a
+    /// transport may fetch in a more compact encoding.
+    async fn send_bulk(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.send(method, path, body).await
+    }
+
+    /// Like `send`, returning the response body undecoded. Transports that
+    /// don't see the body serialize the decoded value back to JSON.
+    async fn send_raw(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&self.send(method, path, body).await?)?)
+    }
+
+    /// The node calls currently go to, for transports that switch between
+    /// several. A change is reported as `ConnectionEvent::Failover`.
+    fn endpoint(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Calls the node's REST API directly. This is the default transport.
+pub struct RestTransport {
     http_client: HttpClient,
     base_url: String,
+    binary_encoding: Option<BinaryEncoding>,
+    auth: Option<Arc<dyn AuthProvider>>,
+    hmac: Option<HmacAuth>,
+    log: bool,
+}
+
+impl RestTransport {
+    pub fn new(http_client: HttpClient, base_url: &str) -> Self {
+        Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            binary_encoding: None,
+            auth: None,
+            hmac: None,
+            log: false,
+        }
+    }
+
+    /// Sends the provider's bearer token with every request.
+    pub fn with_auth(mut self, auth: Arc<dyn AuthProvider>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Signs every request with `auth`.
+    pub fn with_hmac_auth(mut self, auth: HmacAuth) -> Self {
+        self.hmac = Some(auth);
+        self
+    }
+
+    /// Asks for bulk responses in `encoding`, falling back to JSON when the
+    /// node doesn't offer it.
+    pub fn with_binary_encoding(mut self, encoding: BinaryEncoding) -> Self {
+        self.binary_encoding = Some(encoding);
+        self
+    }
+
+    /// Logs every request and response, redacted, at debug level.
+    pub fn with_logging(mut self) -> Self {
+        self.log = true;
+        self
+    }
+
+    async fn execute(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+        accept: Option<String>,
+        request_id: &str,
+    ) -> Result<(reqwest::Response, Option<Instant>)> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = self.http_client.request(
+            method.parse().map_err(|_| VernachainError::InternalError("Invalid HTTP method".into()))?,
+            &url,
+        );
+
+        let request = authorize(request, self.auth.as_deref()).await?;
+        let mut request = with_body(request, self.hmac.as_ref(), method, path, body.as_ref())?
+            .header(REQUEST_ID_HEADER, request_id);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+
+        send_logged(&self.http_client, request, self.log).await
+    }
+}
+
+#[async_trait]
+impl Transport for RestTransport {
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let request_id = new_request_id();
+        let span = debug_span!("vernachain_request", method, path, request_id = %request_id);
+        async {
+            let (response, sent) = self.execute(method, path, body, None, &request_id).await?;
+            let body = read_body(response, &request_id, sent).await?;
+            Ok(serde_json::from_slice(&body)?)
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn send_bulk(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let Some(encoding) = self.binary_encoding else {
+            return self.send(method, path, body).await;
+        };
+        let request_id = new_request_id();
+        let span = debug_span!("vernachain_request", method, path, request_id = %request_id);
+        async {
+            let (response, sent) = self
+                .execute(method, path, body.clone(), Some(encoding.accept()), &request_id)
+                .await?;
+            if response.status() == reqwest::StatusCode::NOT_ACCEPTABLE {
+                return self.send(method, path, body).await;
+            }
+
+            let received = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(BinaryEncoding::from_content_type);
+            let bytes = read_body(response, &request_id, sent).await?;
+            match received {
+                Some(received) => received.decode(&bytes),
+                None => Ok(serde_json::from_slice(&bytes)?),
+            }
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn send_raw(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<Vec<u8>> {
+        let request_id = new_request_id();
+        let span = debug_span!("vernachain_request", method, path, request_id = %request_id);
+        async {
+            let (response, sent) = self.execute(method, path, body, None, &request_id).await?;
+            read_body(response, &request_id, sent).await
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+async fn authorize(
+    request: reqwest::RequestBuilder,
+    auth: Option<&dyn AuthProvider>,
+) -> Result<reqwest::RequestBuilder> {
+    let Some(auth) = auth else {
+        return Ok(request);
+    };
+    let token = auth.token().await?;
+    if token.is_empty() {
+        Ok(request)
+    } else {
+        Ok(request.bearer_auth(token))
+    }
+}
+
+/// Attaches a JSON body and, with `hmac`, the signature over exactly the bytes
+/// sent.
+fn with_body(
+    request: reqwest::RequestBuilder,
+    hmac: Option<&HmacAuth>,
+    method: &str,
+    path: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<reqwest::RequestBuilder> {
+    let bytes = match body {
+        Some(data) => serde_json::to_vec(data)?,
+        None => Vec::new(),
+    };
+    let mut request = request;
+    if let Some(hmac) = hmac {
+        for (name, value) in hmac.headers(method, path, &bytes, Utc::now().timestamp()) {
+            request = request.header(name, value);
+        }
+    }
+    if body.is_some() {
+        request = request.header(CONTENT_TYPE, "application/json").body(bytes);
+    }
+    Ok(request)
+}
+
+/// Sends `request`, logging it when `log` is set. Returns when it was sent if
+/// the response should be logged too.
+async fn send_logged(
+    http_client: &HttpClient,
+    request: reqwest::RequestBuilder,
+    log: bool,
+) -> Result<(reqwest::Response, Option<Instant>)> {
+    let request = request.build()?;
+    if log {
+        logging::log_request(&request);
+    }
+    let sent = log.then(Instant::now);
+    Ok((http_client.execute(request).await?, sent))
+}
+
+/// Reads the response body, mapping error statuses onto the SDK's errors and
+/// logging the response if it was sent at `sent`.
+async fn read_body(
+    response: reqwest::Response,
+    request_id: &str,
+    sent: Option<Instant>,
+) -> Result<Vec<u8>> {
+    let status = response.status();
+    // Prefer the node's id if it assigned its own.
+    let request_id = response
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or(request_id)
+        .to_string();
+    let body = response.bytes().await?.to_vec();
+    if let Some(sent) = sent {
+        logging::log_response(status, sent.elapsed(), &body);
+    }
+
+    match status.as_u16() {
+        401 => Err(VernachainError::AuthenticationError),
+        429 => Err(VernachainError::RateLimitError),
+        status if !(200..300).contains(&status) => {
+            let message = String::from_utf8_lossy(&body).into_owned();
+            warn!("Request {} failed with {}: {}", request_id, status, message);
+            Err(VernachainError::Api {
+                status,
+                message,
+                request_id,
+            })
+        }
+        _ => Ok(body),
+    }
+}
+
+/// A random id for `X-Request-Id`.
+fn new_request_id() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// JSON-RPC method under which gateways expose the REST API.
+const JSON_RPC_METHOD: &str = "vernachain_request";
+
+/// Calls deployments that serve JSON-RPC 2.0 instead of REST. Every call is
+/// sent as a `vernachain_request` method call whose params carry the REST
+/// method, path and body; a gateway's error codes follow HTTP status codes.
+pub struct JsonRpcTransport {
+    http_client: HttpClient,
+    url: String,
+    next_id: AtomicU64,
+    auth: Option<Arc<dyn AuthProvider>>,
+    hmac: Option<HmacAuth>,
+    log: bool,
+}
+
+impl JsonRpcTransport {
+    pub fn new(http_client: HttpClient, url: &str) -> Self {
+        Self {
+            http_client,
+            url: url.to_string(),
+            next_id: AtomicU64::new(1),
+            auth: None,
+            hmac: None,
+            log: false,
+        }
+    }
+
+    /// Sends the provider's bearer token with every call.
+    pub fn with_auth(mut self, auth: Arc<dyn AuthProvider>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Signs every call with `auth`.
+    pub fn with_hmac_auth(mut self, auth: HmacAuth) -> Self {
+        self.hmac = Some(auth);
+        self
+    }
+
+    /// Logs every call and response, redacted, at debug level.
+    pub fn with_logging(mut self) -> Self {
+        self.log = true;
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[async_trait]
+impl Transport for JsonRpcTransport {
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let request_id = new_request_id();
+        let span = debug_span!("vernachain_request", method, path, request_id = %request_id);
+        self.call(method, path, body, &request_id)
+            .instrument(span)
+            .await
+    }
+}
+
+impl JsonRpcTransport {
+    async fn call(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+        request_id: &str,
+    ) -> Result<serde_json::Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": JSON_RPC_METHOD,
+            "params": { "method": method, "path": path, "body": body },
+        });
+        let url = Url::parse(&self.url).map_err(|e| VernachainError::InternalError(e.to_string()))?;
+        let signed_path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let request = authorize(self.http_client.post(url), self.auth.as_deref()).await?;
+        let request = with_body(
+            request,
+            self.hmac.as_ref(),
+            "POST",
+            &signed_path,
+            Some(&call),
+        )?;
+        let request = request.header(REQUEST_ID_HEADER, request_id);
+        let (response, sent) = send_logged(&self.http_client, request, self.log).await?;
+        let body = read_body(response, request_id, sent).await?;
+
+        let response: JsonRpcResponse = serde_json::from_slice(&body)?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(match error.code {
+                401 => VernachainError::AuthenticationError,
+                429 => VernachainError::RateLimitError,
+                code => VernachainError::Api {
+                    // Codes outside the HTTP range are JSON-RPC's own.
+                    status: u16::try_from(code)
+                        .ok()
+                        .filter(|status| (400..600).contains(status))
+                        .unwrap_or(500),
+                    message: error.message,
+                    request_id: request_id.to_string(),
+                },
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Ok(serde_json::Value::Null),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct VernachainClient {
+    transport: Arc<dyn Transport>,
+    retry: RetryPolicy,
+    strict: bool,
+    /// Transport for admin endpoints, carrying the admin token.
+    #[cfg(feature = "admin")]
+    admin: Option<Arc<dyn Transport>>,
     ws_url: String,
+    /// TLS settings for WebSocket connections, when the builder customized
+    /// them.
+    ws_connector: Option<Connector>,
+    auth: Arc<Credentials>,
+    api_prefix: String,
+    capabilities: Option<Arc<Capabilities>>,
+    router: Arc<AddressRouter>,
+    nonces: Arc<NonceManager>,
+    /// Shared by the helpers that fan out into many requests, so they back
+    /// off together when the node pushes back.
+    bulk: Arc<AdaptiveConcurrency>,
+    connection: Arc<ConnectionMonitor>,
+    subscription_buffer: usize,
+    keepalive: Keepalive,
+    /// Shared subscription connection, when multiplexing is enabled. Only
+    /// used once the node has advertised `ws_multiplex`.
+    connections: Option<Arc<ConnectionManager>>,
+    tasks: TaskTracker,
+    shutdown: CancellationToken,
+    /// Cancels `shutdown` once the last user-held clone is dropped. Clones
+    /// moved into background tasks leave this unset so they don't keep the
+    /// tasks alive.
+    _shutdown_on_drop: Option<Arc<DropGuard>>,
+}
+
+/// Configures a `VernachainClient` before any connection is made.
+#[derive(Clone)]
+pub struct VernachainClientBuilder {
+    node_url: String,
+    ws_url: Option<String>,
     api_key: Option<String>,
+    auth: Option<Arc<dyn AuthProvider>>,
+    subscription_buffer: usize,
+    bulk_concurrency: usize,
+    keepalive: Keepalive,
+    multiplex: bool,
+    json_rpc: bool,
+    binary_encoding: Option<BinaryEncoding>,
+    compression: bool,
+    /// PEM certificate chain and PKCS#8 key presented to the node.
+    client_identity: Option<(Vec<u8>, Vec<u8>)>,
+    ca_certificates: Vec<Vec<u8>>,
+    hmac: Option<HmacAuth>,
+    log_requests: bool,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    retry: RetryPolicy,
+    pool: PoolSettings,
+    strict: bool,
+    transport: Option<Arc<dyn Transport>>,
+    #[cfg(feature = "admin")]
+    admin_token: Option<String>,
+}
+
+/// Connection settings passed through to the HTTP client. `None` keeps
+/// reqwest's default.
+#[derive(Debug, Clone, Default)]
+struct PoolSettings {
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Duration>,
+    http2_keep_alive_interval: Option<Duration>,
+    tcp_nodelay: Option<bool>,
+}
+
+impl VernachainClientBuilder {
+    pub fn new(node_url: &str) -> Self {
+        Self {
+            node_url: node_url.to_string(),
+            ws_url: None,
+            api_key: None,
+            auth: None,
+            subscription_buffer: DEFAULT_SUBSCRIPTION_BUFFER,
+            bulk_concurrency: DEFAULT_BULK_CONCURRENCY,
+            keepalive: Keepalive {
+                ping_interval: DEFAULT_PING_INTERVAL,
+                idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            },
+            multiplex: false,
+            json_rpc: false,
+            binary_encoding: None,
+            compression: true,
+            client_identity: None,
+            ca_certificates: Vec::new(),
+            hmac: None,
+            log_requests: false,
+            timeout: None,
+            connect_timeout: None,
+            retry: RetryPolicy::none(),
+            pool: PoolSettings::default(),
+            strict: false,
+            #[cfg(feature = "admin")]
+            admin_token: None,
+            transport: None,
+        }
+    }
+
+    /// Sends WebSocket traffic to `ws_url` instead of deriving it from the
+    /// node URL, for deployments that serve HTTP and WebSocket separately.
+    pub fn with_ws_url(mut self, ws_url: impl Into<String>) -> Self {
+        self.ws_url = Some(ws_url.into());
+        self
+    }
+
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Token for the node's admin endpoints; see `VernachainClient::admin`.
+    /// Admin calls are signed and encoded like the others, so this can't be
+    /// combined with a custom `transport`.
+    #[cfg(feature = "admin")]
+    pub fn admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    /// Produces the bearer token per request instead of using a fixed API
+    /// key, e.g. `auth::OAuth2ClientCredentials`. Takes precedence over
+    /// `api_key`.
+    pub fn auth_provider(mut self, provider: impl AuthProvider + 'static) -> Self {
+        self.auth = Some(Arc::new(provider));
+        self
+    }
+
+    /// Number of messages each subscription buffers for a slow consumer
+    /// before it starts reporting `SubscriptionEvent::MissedMessages`.
+    pub fn subscription_buffer(mut self, capacity: usize) -> Self {
+        self.subscription_buffer = capacity;
+        self
+    }
+
+    /// Most requests that batch helpers such as `multicall` and
+    /// `get_portfolio` keep in flight together. Fewer are sent while the node
+    /// rate limits or slows down.
+    pub fn bulk_concurrency(mut self, max: usize) -> Self {
+        self.bulk_concurrency = max;
+        self
+    }
+
+    /// How often subscription sockets ping the node while no messages arrive.
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.keepalive.ping_interval = interval;
+        self
+    }
+
+    /// How long a subscription socket may stay silent, pongs included, before
+    /// it is treated as dead and the subscription ends.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.keepalive.idle_timeout = timeout;
+        self
+    }
+
+    /// Carries every subscription over one shared connection instead of a
+    /// socket each, on nodes that report the `ws_multiplex` feature.
+    /// Subscriptions fall back to a socket each until
+    /// `negotiate_api_version` has seen the feature.
+    pub fn multiplex_subscriptions(mut self, enabled: bool) -> Self {
+        self.multiplex = enabled;
+        self
+    }
+
+    /// Talks JSON-RPC 2.0 to the node URL instead of REST.
+    pub fn json_rpc(mut self) -> Self {
+        self.json_rpc = true;
+        self
+    }
+
+    /// Asks for blocks, headers and logs in a binary encoding, falling back to
+    /// JSON on nodes that don't offer it. Only applies to the REST transport.
+    pub fn binary_encoding(mut self, encoding: BinaryEncoding) -> Self {
+        self.binary_encoding = Some(encoding);
+        self
+    }
+
+    /// Whether to ask for gzip, brotli, zstd or deflate compressed responses,
+    /// which mostly pays off on block ranges and logs. On by default; turn it
+    /// off where decompression costs more than the bandwidth it saves.
+    pub fn compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Authenticates to the node with a client certificate, for deployments
+    /// that require mutual TLS. `cert` is a PEM certificate chain and `key`
+    /// its PEM PKCS#8 private key. Used for both HTTP and WebSocket
+    /// connections.
+    pub fn with_client_identity(
+        mut self,
+        cert: impl Into<Vec<u8>>,
+        key: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.client_identity = Some((cert.into(), key.into()));
+        self
+    }
+
+    /// Trusts a PEM CA certificate in addition to the system roots, such as a
+    /// private CA that signed the node's certificate.
+    pub fn with_ca_certificate(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.ca_certificates.push(pem.into());
+        self
+    }
+
+    /// Signs every request with HMAC-SHA256, for deployments that require
+    /// signed requests. See `auth::HmacAuth` for the scheme.
+    pub fn hmac_auth(mut self, key_id: impl Into<String>, secret: impl Into<String>) -> Self {
+        self.hmac = Some(HmacAuth::new(key_id, secret));
+        self
+    }
+
+    /// Fails HTTP requests that take longer than `timeout` in total with
+    /// `HttpError`. Requests don't time out by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails HTTP requests whose connection takes longer than `timeout` to
+    /// open.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Most idle connections kept open per host. Raise it for indexers that
+    /// issue many concurrent requests, or set 0 to close connections after
+    /// every request.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool.max_idle_per_host = Some(max);
+        self
+    }
+
+    /// How long an idle pooled connection is kept before it is closed.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends HTTP/2 pings at `interval`, also while the connection is idle,
+    /// so proxies and load balancers don't drop it.
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.pool.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Whether to disable Nagle's algorithm on HTTP connections. On by
+    /// default.
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.pool.tcp_nodelay = Some(enabled);
+        self
+    }
+
+    /// Retries calls that fail transiently, see `RetryPolicy`. Calls are not
+    /// retried by default.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Logs every HTTP request and response at debug level, with credentials,
+    /// private keys and signatures redacted and long bodies truncated. Off by
+    /// default. Custom transports do their own logging.
+    pub fn log_requests(mut self, enabled: bool) -> Self {
+        self.log_requests = enabled;
+        self
+    }
+
+    /// Fails responses carrying fields the SDK's types don't model with
+    /// `SerializationError`, instead of keeping them in each type's `extra`.
+    /// Off by default, so newer nodes keep working with older SDKs.
+    pub fn strict_deserialization(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// Sends every call through a custom transport. Takes precedence over
+    /// `json_rpc`.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Builds the client, failing with `ConfigurationError` if a URL is
+    /// invalid, the API key cannot be sent as a header, or the HTTP client
+    /// cannot be created.
+    pub fn build(self) -> Result<VernachainClient> {
+        if self.subscription_buffer == 0 {
+            return Err(VernachainError::ConfigurationError(
+                "subscription buffer must hold at least one message".to_string(),
+            ));
+        }
+        if self.keepalive.ping_interval.is_zero()
+            || self.keepalive.idle_timeout <= self.keepalive.ping_interval
+        {
+            return Err(VernachainError::ConfigurationError(
+                "idle timeout must be longer than a non-zero ping interval".to_string(),
+            ));
+        }
+
+        let provider = match (self.auth.clone(), &self.api_key) {
+            (Some(provider), _) => Some(provider),
+            (None, Some(key)) => Some(api_key_provider(key)?),
+            (None, None) => None,
+        };
+        let auth = Arc::new(Credentials::new(provider));
+
+        let mut http_client = HttpClient::builder()
+            .gzip(self.compression)
+            .brotli(self.compression)
+            .zstd(self.compression)
+            .deflate(self.compression);
+        if let Some(timeout) = self.timeout {
+            http_client = http_client.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(timeout);
+        }
+        if let Some(max) = self.pool.max_idle_per_host {
+            http_client = http_client.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool.idle_timeout {
+            http_client = http_client.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.pool.http2_keep_alive_interval {
+            http_client = http_client
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+        if let Some(enabled) = self.pool.tcp_nodelay {
+            http_client = http_client.tcp_nodelay(enabled);
+        }
+        let mut tls = native_tls::TlsConnector::builder();
+        for pem in &self.ca_certificates {
+            let certificate = reqwest::Certificate::from_pem(pem).map_err(tls_error)?;
+            http_client = http_client.add_root_certificate(certificate);
+            tls.add_root_certificate(native_tls::Certificate::from_pem(pem).map_err(tls_error)?);
+        }
+        if let Some((cert, key)) = &self.client_identity {
+            let identity = reqwest::Identity::from_pkcs8_pem(cert, key).map_err(tls_error)?;
+            http_client = http_client.identity(identity);
+            tls.identity(native_tls::Identity::from_pkcs8(cert, key).map_err(tls_error)?);
+        }
+        let ws_connector = if self.client_identity.is_some() || !self.ca_certificates.is_empty() {
+            Some(Connector::NativeTls(tls.build().map_err(tls_error)?))
+        } else {
+            None
+        };
+
+        let http_client = http_client
+            .build()
+            .map_err(|e| {
+                VernachainError::ConfigurationError(format!("failed to create HTTP client: {}", e))
+            })?;
+
+        let ws_url = match &self.ws_url {
+            Some(url) => websocket_url(url)?,
+            None => websocket_url(&self.node_url)?,
+        };
+
+        let base_url = self.node_url.trim_end_matches('/').to_string();
+        let transport = match &self.transport {
+            Some(transport) => transport.clone(),
+            None => self.node_transport(&http_client, &base_url, auth.clone()),
+        };
+
+        // Admin calls travel like every other call, with the admin token in
+        // place of the caller's credentials.
+        #[cfg(feature = "admin")]
+        let admin = match &self.admin_token {
+            Some(_) if self.transport.is_some() => {
+                return Err(VernachainError::ConfigurationError(
+                    "an admin token can't be added to a custom transport".to_string(),
+                ));
+            }
+            Some(token) => {
+                let token: Arc<dyn AuthProvider> = api_key_provider(token)?;
+                Some(self.node_transport(&http_client, &base_url, token))
+            }
+            None => None,
+        };
+
+        let shutdown = CancellationToken::new();
+        let tasks = TaskTracker::new();
+        let connections = self.multiplex.then(|| {
+            Arc::new(ConnectionManager::new(
+                tasks.clone(),
+                shutdown.clone(),
+                self.keepalive,
+            ))
+        });
+        Ok(VernachainClient {
+            transport,
+            retry: self.retry,
+            strict: self.strict,
+            #[cfg(feature = "admin")]
+            admin,
+            ws_connector,
+            ws_url,
+            auth,
+            api_prefix: format!("/api/{}", DEFAULT_API_VERSION),
+            capabilities: None,
+            router: Arc::new(AddressRouter::new()),
+            nonces: Arc::new(NonceManager::new()),
+            bulk: Arc::new(AdaptiveConcurrency::new(self.bulk_concurrency)),
+            connection: Arc::new(ConnectionMonitor::new(self.subscription_buffer)),
+            subscription_buffer: self.subscription_buffer,
+            keepalive: self.keepalive,
+            connections,
+            tasks,
+            _shutdown_on_drop: Some(Arc::new(shutdown.clone().drop_guard())),
+            shutdown,
+        })
+    }
+}
+
+impl VernachainClientBuilder {
+    /// The REST or JSON-RPC transport the builder is configured for,
+    /// authenticating with `auth`.
+    fn node_transport(
+        &self,
+        http_client: &HttpClient,
+        base_url: &str,
+        auth: Arc<dyn AuthProvider>,
+    ) -> Arc<dyn Transport> {
+        if self.json_rpc {
+            let mut rpc = JsonRpcTransport::new(http_client.clone(), base_url).with_auth(auth);
+            if let Some(hmac) = &self.hmac {
+                rpc = rpc.with_hmac_auth(hmac.clone());
+            }
+            if self.log_requests {
+                rpc = rpc.with_logging();
+            }
+            Arc::new(rpc)
+        } else {
+            let mut rest = RestTransport::new(http_client.clone(), base_url).with_auth(auth);
+            if let Some(encoding) = self.binary_encoding {
+                rest = rest.with_binary_encoding(encoding);
+            }
+            if let Some(hmac) = &self.hmac {
+                rest = rest.with_hmac_auth(hmac.clone());
+            }
+            if self.log_requests {
+                rest = rest.with_logging();
+            }
+            Arc::new(rest)
+        }
+    }
 }
 
 impl VernachainClient {
-    pub fn new(node_url: &str, api_key: Option<String>) -> Self {
-        let mut headers = HeaderMap::new();
-        if let Some(key) = &api_key {
-            headers.insert(
-                "Authorization",
-                HeaderValue::from_str(&format!("Bearer {}", key)).unwrap(),
-            );
+    pub fn new(node_url: &str, api_key: Option<String>) -> Result<Self> {
+        let builder = Self::builder(node_url);
+        match api_key {
+            Some(key) => builder.api_key(key).build(),
+            None => builder.build(),
+        }
+    }
+
+    pub fn builder(node_url: &str) -> VernachainClientBuilder {
+        VernachainClientBuilder::new(node_url)
+    }
+
+    /// Connects to a known network and checks that the node serves the
+    /// expected chain before returning the client.
+    pub async fn connect(network: Network, api_key: Option<String>) -> Result<Self> {
+        let mut client = Self::new(network.url(), api_key)?;
+        client.negotiate_api_version().await?;
+        let info = client.get_chain_info().await?;
+        if info.chain_id != network.chain_id() {
+            return Err(VernachainError::ChainIdMismatch {
+                expected: network.chain_id(),
+                actual: info.chain_id,
+            });
+        }
+        Ok(client)
+    }
+
+    /// Asks the node which API versions it serves and switches to the newest
+    /// one this SDK supports. Nodes that predate version discovery are assumed
+    /// to speak `v1`. The request goes through the client's transport, so it
+    /// is signed and encoded like every other call.
+    pub async fn negotiate_api_version(&mut self) -> Result<&Capabilities> {
+        let response = self
+            .send_observed("GET", || self.transport.send_raw("GET", "/api/versions", None))
+            .await;
+
+        let capabilities = match response {
+            Err(e) if e.status_code() == Some(404) => Capabilities {
+                api_version: DEFAULT_API_VERSION.to_string(),
+                features: Default::default(),
+            },
+            Err(e) => return Err(e),
+            Ok(body) => {
+                let advertised: ApiVersions = serde_json::from_slice(&body)?;
+                let api_version = SUPPORTED_API_VERSIONS
+                    .iter()
+                    .find(|v| advertised.versions.iter().any(|a| a == *v))
+                    .ok_or_else(|| {
+                        VernachainError::UnexpectedResponseError(format!(
+                            "node serves none of the supported API versions: {:?}",
+                            advertised.versions
+                        ))
+                    })?;
+                Capabilities {
+                    api_version: api_version.to_string(),
+                    features: advertised.features,
+                }
+            }
+        };
+
+        self.api_prefix = format!("/api/{}", capabilities.api_version);
+        Ok(self.capabilities.insert(Arc::new(capabilities)))
+    }
+
+    /// Capabilities reported by the node, if version negotiation has run.
+    pub fn capabilities(&self) -> Option<&Capabilities> {
+        self.capabilities.as_deref()
+    }
+
+    pub fn router(&self) -> &AddressRouter {
+        &self.router
+    }
+
+    pub fn nonces(&self) -> &NonceManager {
+        &self.nonces
+    }
+
+    /// Replaces the client's credentials with `api_key`. Every clone of the
+    /// client sends the new key from the next request and WebSocket
+    /// connection on; sockets that are already open stay authenticated.
+    pub fn set_api_key(&self, api_key: impl Into<String>) -> Result<()> {
+        self.auth.set(Some(api_key_provider(&api_key.into())?));
+        Ok(())
+    }
+
+    /// Stops every subscription task, closing their sockets, and waits for
+    /// them to finish. Subscriptions opened afterwards fail with
+    /// `WebSocketClosed`. Dropping the last clone of the client also stops
+    /// the tasks, but without waiting.
+    pub async fn shutdown(&self) {
+        self.shutdown.cancel();
+        self.tasks.close();
+        self.tasks.wait().await;
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Changes in connectivity as the client's calls and sockets find it:
+    /// the node becoming reachable or unreachable, retries, rate limiting and
+    /// failover. Events before the call aren't replayed.
+    pub fn events(&self) -> Subscription<ConnectionEvent> {
+        self.connection.subscribe()
+    }
+
+    /// The node's admin endpoints, failing with `ConfigurationError` unless
+    /// the client was built with an `admin_token`.
+    #[cfg(feature = "admin")]
+    pub fn admin(&self) -> Result<crate::admin::NodeAdmin> {
+        let transport = self.admin.clone().ok_or_else(|| {
+            VernachainError::ConfigurationError("no admin token configured".to_string())
+        })?;
+        Ok(crate::admin::NodeAdmin::new(transport, &self.api_prefix, self.strict))
+    }
+
+    /// Polls at the SDK's usual interval, giving up when the client shuts
+    /// down. The submit-and-wait helpers also pass their whole `timeout`
+    /// here; their own outer timeout always fires first.
+    async fn poll<T, F, Fut>(&self, timeout: Duration, check: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Option<T>>>,
+    {
+        poll_until(POLL_INTERVAL, timeout, self.shutdown_token(), check).await
+    }
+
+    pub(crate) fn shutdown_token(&self) -> &CancellationToken {
+        &self.shutdown
+    }
+
+    /// Background tasks, waited for by `shutdown`.
+    pub(crate) fn task_tracker(&self) -> &TaskTracker {
+        &self.tasks
+    }
+
+    /// A clone for background tasks that doesn't count towards dropping the
+    /// client.
+    pub(crate) fn detached(&self) -> Self {
+        Self {
+            _shutdown_on_drop: None,
+            ..self.clone()
+        }
+    }
+
+    /// Fails when negotiation reported that the node lacks `feature`. Nodes
+    /// that were never negotiated with are given the benefit of the doubt.
+    fn require_feature(&self, feature: &str) -> Result<()> {
+        match self.capabilities() {
+            Some(capabilities) if !capabilities.supports(feature) => {
+                Err(VernachainError::UnsupportedFeature(feature.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.request("GET", endpoint, None).await
+    }
+
+    async fn request<T>(&self, method: &str, endpoint: &str, body: Option<serde_json::Value>) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let path = format!("{}{}", self.api_prefix, endpoint);
+        let data = self
+            .send_observed(method, || self.transport.send(method, &path, body.clone()))
+            .await?;
+        decode::from_value(data, self.strict)
+    }
+
+    /// `request` for endpoints returning blocks, headers or logs, which may
+    /// come back in the configured binary encoding.
+    async fn request_bulk<T>(
+        &self,
+        method: &str,
+        endpoint: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let path = format!("{}{}", self.api_prefix, endpoint);
+        let data = self
+            .send_observed(method, || self.transport.send_bulk(method, &path, body.clone()))
+            .await?;
+        decode::from_value(data, self.strict)
+    }
+
+    /// Runs a transport call under the retry policy, reporting what each
+    /// attempt says about the connection.
+    async fn send_observed<T, F, Fut>(&self, method: &str, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        self.retry
+            .run(method, || {
+                if attempt > 0 && self.connection.is_disconnected() {
+                    self.connection.reconnecting(attempt);
+                }
+                attempt += 1;
+                let call = call();
+                async move {
+                    let result = call.await;
+                    self.connection.observe(&result);
+                    if let Some(endpoint) = self.transport.endpoint() {
+                        self.connection.endpoint(endpoint);
+                    }
+                    result
+                }
+            })
+            .await
+    }
+
+    /// Calls an endpoint the SDK has no method for yet, with the client's
+    /// transport, credentials and retry policy. `path` is relative to the API
+    /// version prefix, e.g. `/chain` for `/api/v1/chain`.
+    pub async fn raw_request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        self.request(method, path, body).await
+    }
+
+    /// Like `raw_request`, returning the response body as sent, for endpoints
+    /// that don't answer in JSON.
+    pub async fn raw_bytes(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<Vec<u8>> {
+        let path = format!("{}{}", self.api_prefix, path);
+        self.send_observed(method, || self.transport.send_raw(method, &path, body.clone()))
+            .await
+    }
+
+    // Chain Methods
+    pub async fn get_chain_info(&self) -> Result<ChainInfo> {
+        self.request("GET", "/chain", None).await
+    }
+
+    pub async fn get_network_stats(&self) -> Result<NetworkStats> {
+        self.request("GET", "/stats", None).await
+    }
+
+    /// Time series of `metric` over `range`, bucketed by `resolution`.
+    pub async fn get_stats_history(
+        &self,
+        metric: StatsMetric,
+        range: Range<DateTime<Utc>>,
+        resolution: StatsResolution,
+    ) -> Result<StatsHistory> {
+        let query = StatsHistoryQuery {
+            metric,
+            from: range.start,
+            to: range.end,
+            resolution,
+        };
+        self.request("GET", &with_query("/stats/history", &query)?, None)
+            .await
+    }
+
+    /// The oracle's latest price of `symbol` in `quote`, e.g. `("VERNA", "USD")`.
+    pub async fn get_price(&self, symbol: &str, quote: &str) -> Result<PriceTick> {
+        let query = [("quote", quote)];
+        let endpoint = with_query(&format!("/oracle/prices/{}", symbol), &query)?;
+        self.request("GET", &endpoint, None).await
+    }
+
+    // Node Methods
+    pub async fn get_node_status(&self) -> Result<NodeStatus> {
+        self.request("GET", "/node/status", None).await
+    }
+
+    /// The peers this node is connected to.
+    pub async fn get_peers(&self) -> Result<Vec<PeerInfo>> {
+        self.request("GET", "/node/peers", None).await
+    }
+
+    /// Peer counts, node versions and latencies per shard, as far as this
+    /// node knows them.
+    pub async fn get_network_topology(&self) -> Result<NetworkTopology> {
+        self.request("GET", "/network/topology", None).await
+    }
+
+    // Shard Methods
+    pub async fn list_shards(&self) -> Result<Vec<ShardInfo>> {
+        self.request("GET", "/shards", None).await
+    }
+
+    pub async fn get_shard_stats(&self, shard_id: ShardId) -> Result<ShardStats> {
+        self.request("GET", &format!("/shards/{}/stats", shard_id), None)
+            .await
+    }
+
+    // Transaction Methods
+    /// Submits a transaction. When `shard_id` is unset it is resolved from
+    /// the sender's address, and a missing `nonce` is reserved from the
+    /// client's nonce manager.
+    pub async fn create_transaction(&self, mut request: TransactionRequest) -> Result<Transaction> {
+        if request.shard_id.is_none() {
+            request.shard_id = Some(self.resolve_shard(&request.sender).await?);
+        }
+        if request.nonce.is_none() {
+            request.nonce = Some(self.nonces.reserve(self, &request.sender).await?);
+        }
+        let sender = request.sender.clone();
+        let result = self
+            .request("POST", "/transactions", Some(serde_json::to_value(request)?))
+            .await;
+        if result.is_err() {
+            // The reserved nonce may or may not have been consumed.
+            self.nonces.invalidate(&sender);
+        }
+        result
+    }
+
+    /// Submits a transaction and waits until its block is `confirmations`
+    /// deep, counting the block itself; see `wait_for_confirmations`.
+    /// `timeout` covers both.
+    pub async fn create_transaction_and_wait(
+        &self,
+        request: TransactionRequest,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<Transaction> {
+        let submit_and_wait = async {
+            let tx = self.create_transaction(request).await?;
+            self.confirmations(&tx.hash, confirmations, timeout).await
+        };
+        tokio::time::timeout(timeout, submit_and_wait)
+            .await
+            .map_err(|_| VernachainError::TimeoutError)?
+    }
+
+    /// The gas price the node currently suggests for new transactions.
+    pub async fn get_gas_price(&self) -> Result<f64> {
+        let response: GasPriceResponse = self.request("GET", "/gas/price", None).await?;
+        Ok(response.gas_price)
+    }
+
+    /// Transactions waiting in `shard_id`'s mempool that match `filter`,
+    /// highest gas price first.
+    pub async fn get_pending_transactions(
+        &self,
+        shard_id: ShardId,
+        filter: &MempoolFilter,
+    ) -> Result<Vec<Transaction>> {
+        let endpoint = with_query(&format!("/mempool/{}/transactions", shard_id), filter)?;
+        self.request_bulk("GET", &endpoint, None).await
+    }
+
+    pub async fn get_mempool_stats(&self, shard_id: ShardId) -> Result<MempoolStats> {
+        self.request("GET", &format!("/mempool/{}/stats", shard_id), None)
+            .await
+    }
+
+    /// Gas price percentiles and block fullness over `range`, bucketed by
+    /// `resolution`, for tuning fee estimation against recent history.
+    pub async fn get_gas_stats(
+        &self,
+        range: Range<DateTime<Utc>>,
+        resolution: StatsResolution,
+    ) -> Result<GasStats> {
+        let query = GasStatsQuery {
+            from: range.start,
+            to: range.end,
+            resolution,
+        };
+        self.request("GET", &with_query("/gas/stats", &query)?, None)
+            .await
+    }
+
+    /// Submits a transaction whose gas is paid by the sponsor who signed it; see
+    /// `sponsor::SponsoredTransactionBuilder`.
+    pub async fn submit_sponsored_transaction(
+        &self,
+        request: TransactionRequest,
+    ) -> Result<Transaction> {
+        if !sponsor::verify_sponsorship(&request) {
+            return Err(VernachainError::ValidationError(
+                "transaction has no valid sponsor signature".to_string(),
+            ));
+        }
+        self.request(
+            "POST",
+            "/transactions/sponsored",
+            Some(serde_json::to_value(request)?),
+        )
+        .await
+    }
+
+    /// Submits several transactions in one request, filling shards and nonces
+    /// like `create_transaction`. With `atomic`, the node applies all of them or
+    /// none; that needs the `atomic_bundles` feature.
+    pub async fn submit_batch(
+        &self,
+        mut requests: Vec<TransactionRequest>,
+        atomic: bool,
+    ) -> Result<Vec<BatchItemResult>> {
+        if atomic {
+            self.require_feature("atomic_bundles")?;
+        }
+        for request in &mut requests {
+            if request.shard_id.is_none() {
+                request.shard_id = Some(self.resolve_shard(&request.sender).await?);
+            }
+            if request.nonce.is_none() {
+                request.nonce = Some(self.nonces.reserve(self, &request.sender).await?);
+            }
+        }
+        let senders: Vec<String> = requests.iter().map(|r| r.sender.clone()).collect();
+        let batch = BatchTransactionRequest {
+            transactions: requests,
+            atomic,
+        };
+        let response: Result<BatchResponse> = self
+            .request("POST", "/transactions/batch", Some(serde_json::to_value(batch)?))
+            .await;
+
+        let failed: Vec<&String> = match &response {
+            Ok(response) => response
+                .results
+                .iter()
+                .filter(|item| !item.is_success())
+                .filter_map(|item| senders.get(item.index))
+                .collect(),
+            Err(_) => senders.iter().collect(),
+        };
+        for sender in failed {
+            self.nonces.invalidate(sender);
+        }
+        Ok(response?.results)
+    }
+
+    /// Resubmits a pending transaction with the same nonce and a higher gas
+    /// price so it is mined sooner.
+    pub async fn replace_transaction(
+        &self,
+        tx_hash: &str,
+        new_gas_price: f64,
+    ) -> Result<Transaction> {
+        let pending = self.pending_transaction(tx_hash).await?;
+        if let Some(gas_price) = pending.gas_price {
+            if new_gas_price < gas_price * REPLACEMENT_GAS_BUMP {
+                return Err(VernachainError::ValidationError(format!(
+                    "replacement gas price must be at least {} (original {})",
+                    gas_price * REPLACEMENT_GAS_BUMP,
+                    gas_price
+                )));
+            }
+        }
+        let request = TransactionRequest {
+            sender: pending.sender,
+            recipient: pending.recipient,
+            amount: pending.amount,
+            shard_id: Some(pending.shard_id),
+            gas_price: Some(new_gas_price),
+            gas_limit: pending.gas_limit,
+            data: pending.data,
+            nonce: pending.nonce,
+            sponsor: None,
+            timestamp: None,
+            signature: None,
+        };
+        self.create_transaction(request).await
+    }
+
+    /// Replaces a pending transaction with a zero-value transfer to the sender
+    /// itself, at the minimum gas price the node accepts as a replacement.
+    pub async fn cancel_transaction(&self, tx_hash: &str) -> Result<Transaction> {
+        let pending = self.pending_transaction(tx_hash).await?;
+        let gas_price = pending.gas_price.ok_or_else(|| {
+            VernachainError::ValidationError(format!(
+                "gas price of {} is unknown; use replace_transaction",
+                tx_hash
+            ))
+        })?;
+        let request = TransactionRequest {
+            recipient: pending.sender.clone(),
+            sender: pending.sender,
+            amount: 0.0,
+            shard_id: Some(pending.shard_id),
+            gas_price: Some(gas_price * REPLACEMENT_GAS_BUMP),
+            gas_limit: pending.gas_limit,
+            data: None,
+            nonce: pending.nonce,
+            sponsor: None,
+            timestamp: None,
+            signature: None,
+        };
+        self.create_transaction(request).await
+    }
+
+    async fn pending_transaction(&self, tx_hash: &str) -> Result<Transaction> {
+        let tx = self.get_transaction(tx_hash).await?;
+        if !tx.is_pending() {
+            return Err(VernachainError::ValidationError(format!(
+                "transaction {} is no longer pending (status {})",
+                tx_hash, tx.status
+            )));
+        }
+        if tx.nonce.is_none() {
+            return Err(VernachainError::UnexpectedResponseError(format!(
+                "node did not report a nonce for {}",
+                tx_hash
+            )));
+        }
+        Ok(tx)
+    }
+
+    pub async fn get_transaction(&self, tx_hash: &str) -> Result<Transaction> {
+        self.request("GET", &format!("/transactions/{}", tx_hash), None).await
+    }
+
+    // Block Methods
+    pub async fn get_block(&self, block_number: u64, shard_id: ShardId) -> Result<Block> {
+        self.request_bulk(
+            "GET",
+            &format!("/blocks/{}?shard_id={}", block_number, shard_id),
+            None,
+        )
+        .await
+    }
+
+    pub async fn get_block_by_hash(&self, block_hash: &str) -> Result<Block> {
+        self.request("GET", &format!("/blocks/hash/{}", block_hash), None).await
+    }
+
+    pub async fn get_latest_block(&self, shard_id: ShardId) -> Result<Block> {
+        self.request(
+            "GET",
+            &format!("/blocks/latest?shard_id={}", shard_id),
+            None,
+        )
+        .await
+    }
+
+    pub async fn get_block_header(&self, block_number: u64, shard_id: ShardId) -> Result<BlockHeader> {
+        self.request(
+            "GET",
+            &format!("/blocks/{}/header?shard_id={}", block_number, shard_id),
+            None,
+        )
+        .await
+    }
+
+    /// Fetches headers for `from..=to`; pair with `bloom::may_contain` to skip
+    /// blocks that cannot hold the logs an indexer is after.
+    pub async fn get_block_headers(
+        &self,
+        shard_id: ShardId,
+        blocks: RangeInclusive<u64>,
+    ) -> Result<Vec<BlockHeader>> {
+        self.request_bulk(
+            "GET",
+            &format!(
+                "/blocks/headers?shard_id={}&from={}&to={}",
+                shard_id,
+                blocks.start(),
+                blocks.end()
+            ),
+            None,
+        )
+        .await
+    }
+
+    /// Streams blocks `from..=to` of `shard_id`, with their logs, into `writer`
+    /// as a snapshot. Returns the number of blocks written.
+    pub async fn export_range<W: AsyncWrite + Unpin>(
+        &self,
+        shard_id: ShardId,
+        from: u64,
+        to: u64,
+        writer: W,
+        format: SnapshotFormat,
+    ) -> Result<u64> {
+        let mut snapshot = SnapshotWriter::new(writer, shard_id, format).await?;
+        let mut written = 0;
+        for number in from..=to {
+            let block = self.get_block(number, shard_id).await?;
+            let logs = self.block_logs(&block).await?;
+            snapshot.write(&SnapshotBlock { block, logs }).await?;
+            written += 1;
+        }
+        snapshot.finish().await?;
+        Ok(written)
+    }
+
+    pub async fn get_finalized_block(&self, shard_id: ShardId) -> Result<Block> {
+        self.request(
+            "GET",
+            &format!("/blocks/finalized?shard_id={}", shard_id),
+            None,
+        )
+        .await
+    }
+
+    /// Polls until the transaction is included in a block at or below the
+    /// finalized head of its shard. A transaction the node marks as failed is
+    /// reported as `TransactionFailed`.
+    pub async fn wait_for_finality(&self, tx_hash: &str, timeout: Duration) -> Result<Transaction> {
+        self.poll(timeout, || async {
+            let tx = self.get_transaction(tx_hash).await?;
+            if tx.is_failed() {
+                return Err(VernachainError::TransactionFailed {
+                    tx_hash: tx.hash,
+                    status: tx.status,
+                });
+            }
+            if let Some(block_number) = tx.block_number {
+                let finalized = self.get_finalized_block(tx.shard_id).await?;
+                if finalized.number >= block_number {
+                    return Ok(Some(tx));
+                }
+            }
+            Ok(None)
+        })
+        .await
+    }
+
+    /// Polls until the transaction's block has `confirmations` blocks on top
+    /// of it, counting itself, so 1 returns as soon as it is included. A
+    /// transaction the node marks as failed is reported as
+    /// `TransactionFailed`.
+    pub async fn wait_for_confirmations(
+        &self,
+        tx_hash: &str,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<Transaction> {
+        self.confirmations(tx_hash, confirmations, timeout).await
+    }
+
+    async fn confirmations(
+        &self,
+        tx_hash: &str,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<Transaction> {
+        self.poll(timeout, || async {
+            let tx = self.get_transaction(tx_hash).await?;
+            if tx.is_failed() {
+                return Err(VernachainError::TransactionFailed {
+                    tx_hash: tx.hash,
+                    status: tx.status,
+                });
+            }
+            if let Some(block_number) = tx.block_number {
+                let latest = self.get_latest_block(tx.shard_id).await?;
+                if latest.number + 1 >= block_number + confirmations {
+                    return Ok(Some(tx));
+                }
+            }
+            Ok(None)
+        })
+        .await
+    }
+
+    // Account Methods
+    pub async fn resolve_shard(&self, address: &str) -> Result<ShardId> {
+        self.router.resolve(self, address).await
+    }
+
+    pub async fn get_balance(&self, address: &str) -> Result<f64> {
+        let shard_id = self.resolve_shard(address).await?;
+        let response: BalanceResponse = self
+            .request(
+                "GET",
+                &format!("/accounts/{}/balance?shard_id={}", address, shard_id),
+                None,
+            )
+            .await?;
+        Ok(response.balance)
+    }
+
+    pub async fn get_account(&self, address: &str) -> Result<Account> {
+        self.request("GET", &format!("/accounts/{}", address), None).await
+    }
+
+    pub async fn get_code(&self, address: &str) -> Result<String> {
+        let response: CodeResponse = self
+            .request("GET", &format!("/accounts/{}/code", address), None)
+            .await?;
+        Ok(response.code)
+    }
+
+    pub async fn get_storage_at(&self, address: &str, key: &str) -> Result<String> {
+        let response: StorageResponse = self
+            .request(
+                "GET",
+                &format!("/accounts/{}/storage/{}", address, key),
+                None,
+            )
+            .await?;
+        Ok(response.value)
+    }
+
+    pub async fn get_proof(
+        &self,
+        address: &str,
+        storage_keys: &[&str],
+        block: Option<u64>,
+    ) -> Result<StateProof> {
+        let mut endpoint = format!(
+            "/accounts/{}/proof?keys={}",
+            address,
+            storage_keys.join(",")
+        );
+        if let Some(block) = block {
+            endpoint.push_str(&format!("&block={}", block));
+        }
+        self.request("GET", &endpoint, None).await
+    }
+
+    /// Accounts with the largest balances, across all shards unless `shard_id`
+    /// is given.
+    pub async fn get_top_accounts(
+        &self,
+        limit: u32,
+        shard_id: Option<ShardId>,
+    ) -> Result<Vec<RankedAccount>> {
+        let mut endpoint = format!("/accounts/top?limit={}", limit);
+        if let Some(shard_id) = shard_id {
+            endpoint.push_str(&format!("&shard_id={}", shard_id));
+        }
+        self.request("GET", &endpoint, None).await
+    }
+
+    /// Balances of the token contracts `address` holds, on every shard.
+    pub async fn get_token_holdings(&self, address: &str) -> Result<Vec<TokenHolding>> {
+        self.request("GET", &format!("/accounts/{}/tokens", address), None)
+            .await
+    }
+
+    /// Balances, stake, pending rewards and token holdings of `addresses`,
+    /// fetched a few accounts at a time.
+    pub async fn get_portfolio(&self, addresses: &[&str]) -> Result<Portfolio> {
+        let accounts = futures_util::stream::iter(addresses)
+            .map(|address| self.bulk.run(self.account_holdings(address)))
+            .buffered(PORTFOLIO_CONCURRENCY)
+            .try_collect()
+            .await?;
+        Ok(Portfolio::new(accounts))
+    }
+
+    async fn account_holdings(&self, address: &str) -> Result<AccountHoldings> {
+        let (account, delegations, rewards, tokens) = tokio::try_join!(
+            self.get_account(address),
+            self.get_delegations(address),
+            self.get_pending_rewards(address),
+            self.get_token_holdings(address),
+        )?;
+        Ok(AccountHoldings {
+            address: account.address,
+            shard_id: account.shard_id,
+            balance: account.balance,
+            staked: delegations.iter().map(|delegation| delegation.amount).sum(),
+            delegations,
+            pending_rewards: rewards.total,
+            tokens,
+        })
+    }
+
+    /// One page of the transactions `address` sent or received in `range`.
+    /// Pass the previous page's `next_cursor` to continue.
+    pub async fn get_account_transactions(
+        &self,
+        address: &str,
+        range: Range<DateTime<Utc>>,
+        limit: u32,
+        cursor: Option<&str>,
+    ) -> Result<TransactionPage> {
+        let query = AccountTransactionsQuery {
+            shard_id: self.resolve_shard(address).await?,
+            from: range.start,
+            to: range.end,
+            limit,
+            cursor,
+        };
+        let endpoint = with_query(&format!("/accounts/{}/transactions", address), &query)?;
+        self.request("GET", &endpoint, None).await
+    }
+
+    /// Streams every transaction of `address` in `range` into `writer`, page
+    /// by page; see the `history` module. Returns the number of transactions
+    /// written.
+    pub async fn export_history<W: AsyncWrite + Unpin>(
+        &self,
+        address: &str,
+        range: Range<DateTime<Utc>>,
+        format: HistoryFormat,
+        writer: W,
+    ) -> Result<u64> {
+        let mut history = HistoryWriter::new(writer, format, address).await?;
+        let mut cursor = None;
+        let mut written = 0;
+        loop {
+            let page = self
+                .get_account_transactions(
+                    address,
+                    range.clone(),
+                    HISTORY_PAGE_SIZE,
+                    cursor.as_deref(),
+                )
+                .await?;
+            for tx in &page.transactions {
+                history.write(tx).await?;
+                written += 1;
+            }
+            match page.next_cursor {
+                Some(next) if !page.transactions.is_empty() => cursor = Some(next),
+                _ => break,
+            }
+        }
+        history.finish().await?;
+        Ok(written)
+    }
+
+    // Faucet Methods
+    /// Asks the network's faucet to send `amount` to `address`. Only test
+    /// networks run a faucet; a faucet that is still cooling down for the
+    /// address reports `RateLimitError`.
+    pub async fn request_faucet_funds(&self, address: &str, amount: f64) -> Result<FaucetGrant> {
+        self.faucet(FaucetRequest {
+            address: address.to_string(),
+            amount,
+            captcha_token: None,
+        })
+        .await
+    }
+
+    /// Like `request_faucet_funds`, for faucets that want a solved captcha or
+    /// an access token with each request.
+    pub async fn request_faucet_funds_with_captcha(
+        &self,
+        address: &str,
+        amount: f64,
+        captcha_token: &str,
+    ) -> Result<FaucetGrant> {
+        self.faucet(FaucetRequest {
+            address: address.to_string(),
+            amount,
+            captcha_token: Some(captcha_token.to_string()),
+        })
+        .await
+    }
+
+    async fn faucet(&self, request: FaucetRequest) -> Result<FaucetGrant> {
+        if request.address.is_empty() {
+            return Err(VernachainError::ValidationError(
+                "faucet address is empty".to_string(),
+            ));
+        }
+        if !(request.amount.is_finite() && request.amount > 0.0) {
+            return Err(VernachainError::ValidationError(
+                "faucet amount must be positive".to_string(),
+            ));
+        }
+        self.request("POST", "/faucet", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    // Smart Contract Methods
+    pub async fn deploy_contract(&self, request: ContractDeployRequest) -> Result<SmartContract> {
+        self.request(
+            "POST",
+            "/contracts",
+            Some(serde_json::to_value(request)?),
+        )
+        .await
+    }
+
+    /// Deploys `request` with `salt`, so that it lands at the same address on
+    /// every shard and network `deployer` deploys it to. If the contract is
+    /// already at that address on the request's shard it is returned instead
+    /// of deploying again. Fails with `UnsupportedFeature`, before deploying
+    /// anything, unless negotiation reported `deterministic_addresses`.
+    pub async fn deploy_contract_deterministic(
+        &self,
+        mut request: ContractDeployRequest,
+        deployer: &str,
+        salt: [u8; 32],
+    ) -> Result<SmartContract> {
+        // Nodes without the feature pick addresses of their own, so the
+        // existing-contract check below would look in the wrong place.
+        if !self
+            .capabilities()
+            .is_some_and(|capabilities| capabilities.supports("deterministic_addresses"))
+        {
+            return Err(VernachainError::UnsupportedFeature(
+                "deterministic_addresses".to_string(),
+            ));
+        }
+        let address = codec::predict_contract_address(deployer, salt)?;
+        if let Some(contract) = not_found_as_none(self.get_contract(&address).await)? {
+            if contract.shard_id == request.shard_id {
+                return Ok(contract);
+            }
+        }
+        request.salt = Some(hex::encode(salt));
+        let contract = self.deploy_contract(request).await?;
+        // The contract exists either way, so a different address is reported
+        // rather than turned into an error.
+        if !contract.address.eq_ignore_ascii_case(&address) {
+            warn!(
+                "Contract deployed to {} instead of the predicted {}",
+                contract.address, address
+            );
+        }
+        Ok(contract)
+    }
+
+    /// Validates a typed template and deploys it.
+    pub async fn deploy_template<T: ContractTemplate>(
+        &self,
+        template: &T,
+        shard_id: ShardId,
+    ) -> Result<SmartContract> {
+        self.deploy_contract(template.to_request(shard_id)?).await
+    }
+
+    /// Deploys a contract and waits until its deployment transaction is
+    /// `confirmations` deep; see `wait_for_confirmations`. `timeout` covers
+    /// both.
+    pub async fn deploy_contract_and_wait(
+        &self,
+        request: ContractDeployRequest,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<SmartContract> {
+        let deploy_and_wait = async {
+            let contract = self.deploy_contract(request).await?;
+            let tx_hash = contract.transaction_hash.as_deref().ok_or_else(|| {
+                VernachainError::UnexpectedResponseError(
+                    "node did not report the deployment transaction".to_string(),
+                )
+            })?;
+            self.confirmations(tx_hash, confirmations, timeout).await?;
+            Ok(contract)
+        };
+        tokio::time::timeout(timeout, deploy_and_wait)
+            .await
+            .map_err(|_| VernachainError::TimeoutError)?
+    }
+
+    pub async fn get_contract(&self, contract_address: &str) -> Result<SmartContract> {
+        self.request("GET", &format!("/contracts/{}", contract_address), None).await
+    }
+
+    /// The ABI published with a verified contract's source.
+    pub async fn get_contract_abi(&self, contract_address: &str) -> Result<ContractAbi> {
+        self.request("GET", &format!("/contracts/{}/abi", contract_address), None)
+            .await
+    }
+
+    /// The contract's `state` as of `at_block`, or the latest if `None`.
+    pub async fn get_contract_state(
+        &self,
+        contract_address: &str,
+        at_block: Option<u64>,
+    ) -> Result<ContractState> {
+        let mut endpoint = format!("/contracts/{}", contract_address);
+        if let Some(block) = at_block {
+            endpoint.push_str(&format!("?block={}", block));
+        }
+        let contract: SmartContract = self.request("GET", &endpoint, None).await?;
+        Ok(ContractState {
+            address: contract.address,
+            block_number: at_block,
+            values: contract.state.unwrap_or_default(),
+        })
+    }
+
+    /// The state keys of a contract that changed between two blocks.
+    pub async fn diff_contract_state(
+        &self,
+        contract_address: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<ContractStateDiff> {
+        if from_block > to_block {
+            return Err(VernachainError::ValidationError(format!(
+                "from_block {} is after to_block {}",
+                from_block, to_block
+            )));
+        }
+        let (before, after) = tokio::try_join!(
+            self.get_contract_state(contract_address, Some(from_block)),
+            self.get_contract_state(contract_address, Some(to_block)),
+        )?;
+        Ok(ContractStateDiff {
+            address: after.address.clone(),
+            from_block,
+            to_block,
+            changes: after.diff(&before),
+        })
+    }
+
+    pub async fn call_contract(
+        &self,
+        contract_address: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.contract_call(contract_address, method, params).await
+    }
+
+    /// Runs read-only `calls` and returns one result per call, in order. Nodes
+    /// with the `multicall` feature answer them in a single request; others
+    /// get a few calls at a time. A failed call only fails its own result.
+    pub async fn multicall(&self, calls: Vec<ContractCall>) -> Result<Vec<ContractCallResult>> {
+        if self
+            .capabilities()
+            .is_some_and(|capabilities| capabilities.supports("multicall"))
+        {
+            let body = serde_json::to_value(MulticallRequest { calls: &calls })?;
+            let response: MulticallResponse = self
+                .request("POST", "/contracts/multicall", Some(body))
+                .await?;
+            let mut results = response.results;
+            if results.len() != calls.len() {
+                return Err(VernachainError::UnexpectedResponseError(format!(
+                    "multicall returned {} results for {} calls",
+                    results.len(),
+                    calls.len()
+                )));
+            }
+            results.sort_by_key(|result| result.index);
+            return Ok(results);
+        }
+
+        let results = futures_util::stream::iter(calls.into_iter().enumerate())
+            .map(|(index, call)| async move {
+                let result = self
+                    .bulk
+                    .run(self.call_contract(&call.contract_address, &call.method, call.params))
+                    .await;
+                match result {
+                    Ok(value) => ContractCallResult {
+                        index,
+                        value: Some(value),
+                        error: None,
+                        extra: ExtraFields::new(),
+                    },
+                    Err(e) => ContractCallResult {
+                        index,
+                        value: None,
+                        error: Some(e.to_string()),
+                        extra: ExtraFields::new(),
+                    },
+                }
+            })
+            .buffered(MULTICALL_CONCURRENCY)
+            .collect()
+            .await;
+        Ok(results)
+    }
+
+    /// Calls a contract method and decodes its return value into `T`.
+    pub async fn call_contract_as<T: DeserializeOwned>(
+        &self,
+        contract_address: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        let value = self.call_contract(contract_address, method, params).await?;
+        serde_json::from_value(value).map_err(|e| {
+            VernachainError::UnexpectedResponseError(format!(
+                "{} returned an unexpected value: {}",
+                method, e
+            ))
+        })
+    }
+
+    /// Calls a contract method and returns the node's whole answer, with the
+    /// return value decoded into `T`.
+    pub async fn call_contract_result<T: DeserializeOwned>(
+        &self,
+        contract_address: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<CallResult<T>> {
+        self.contract_call(contract_address, method, params).await
+    }
+
+    /// The request behind `call_contract` and `call_contract_result`, which
+    /// only differ in how much of the answer they decode.
+    async fn contract_call<R: DeserializeOwned>(
+        &self,
+        contract_address: &str,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<R> {
+        self.request(
+            "POST",
+            &format!("/contracts/{}/call", contract_address),
+            Some(json!({
+                "method": method,
+                "params": params,
+            })),
+        )
+        .await
+    }
+
+    /// Publishes a contract's source for verification by the explorer.
+    pub async fn verify_contract(
+        &self,
+        contract_address: &str,
+        source: &str,
+        compiler_version: &str,
+        optimization: bool,
+    ) -> Result<ContractVerification> {
+        let request = ContractVerificationRequest {
+            source: source.to_string(),
+            compiler_version: compiler_version.to_string(),
+            optimization,
+        };
+        self.request(
+            "POST",
+            &format!("/contracts/{}/verification", contract_address),
+            Some(serde_json::to_value(request)?),
+        )
+        .await
+    }
+
+    pub async fn get_contract_verification_status(
+        &self,
+        contract_address: &str,
+    ) -> Result<ContractVerification> {
+        self.request(
+            "GET",
+            &format!("/contracts/{}/verification", contract_address),
+            None,
+        )
+        .await
+    }
+
+    // Event Log Methods
+    pub async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>> {
+        self.request_bulk("POST", "/logs", Some(serde_json::to_value(filter)?))
+            .await
+    }
+
+    /// Logs emitted by `block`, skipping the request for blocks without
+    /// transactions.
+    pub(crate) async fn block_logs(&self, block: &Block) -> Result<Vec<Log>> {
+        if block.transactions.is_empty() {
+            return Ok(Vec::new());
+        }
+        let filter = LogFilter::new()
+            .shard(block.shard_id)
+            .from_block(block.number)
+            .to_block(block.number);
+        self.get_logs(&filter).await
+    }
+
+    // Search Methods
+    /// Resolves a block number, block or transaction hash, or address the way
+    /// the explorer's search box does. 64 hex digits are tried as a
+    /// transaction hash, a block hash and then an account address, the
+    /// hex-encoded public key.
+    pub async fn search(&self, query: &str) -> Result<SearchResult> {
+        let query = query.trim();
+        if let Ok(number) = query.parse::<u64>() {
+            let mut blocks = Vec::new();
+            for shard in self.list_shards().await? {
+                let block = self.get_block(number, shard.shard_id).await;
+                blocks.extend(not_found_as_none(block)?);
+            }
+            return Ok(if blocks.is_empty() {
+                SearchResult::NotFound
+            } else {
+                SearchResult::Blocks(blocks)
+            });
+        }
+
+        let digits = query.strip_prefix("0x").unwrap_or(query);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(SearchResult::NotFound);
+        }
+        match digits.len() {
+            40 => match not_found_as_none(self.get_account(query).await)? {
+                Some(account) if account.code_hash.is_some() => {
+                    Ok(SearchResult::Contract(self.get_contract(query).await?))
+                }
+                Some(account) => Ok(SearchResult::Account(account)),
+                None => Ok(SearchResult::NotFound),
+            },
+            64 => {
+                if let Some(tx) = not_found_as_none(self.get_transaction(query).await)? {
+                    return Ok(SearchResult::Transaction(tx));
+                }
+                if let Some(block) = not_found_as_none(self.get_block_by_hash(query).await)? {
+                    return Ok(SearchResult::Block(block));
+                }
+                Ok(not_found_as_none(self.get_account(query).await)?
+                    .map_or(SearchResult::NotFound, SearchResult::Account))
+            }
+            _ => Ok(SearchResult::NotFound),
+        }
+    }
+
+    // Cross-Shard Operations
+    pub async fn initiate_cross_shard_transfer(
+        &self,
+        request: CrossShardTransferRequest,
+    ) -> Result<CrossShardTransfer> {
+        self.request(
+            "POST",
+            "/cross-shard/transfer",
+            Some(serde_json::to_value(request)?),
+        )
+        .await
+    }
+
+    pub async fn get_cross_shard_transfer(&self, transfer_id: &str) -> Result<CrossShardTransfer> {
+        self.request("GET", &format!("/cross-shard/transfers/{}", transfer_id), None)
+            .await
+    }
+
+    /// Polls until the transfer completes and returns it with its proof.
+    /// A transfer that ends in failure is reported as `TransferFailed`.
+    pub async fn wait_for_cross_shard_completion(
+        &self,
+        transfer_id: &str,
+        timeout: Duration,
+    ) -> Result<CrossShardTransfer> {
+        self.poll(timeout, || async {
+            let transfer = self.get_cross_shard_transfer(transfer_id).await?;
+            if transfer.is_failed() {
+                return Err(VernachainError::TransferFailed {
+                    transfer_id: transfer.transfer_id,
+                    status: transfer.status,
+                });
+            }
+            Ok(transfer.is_completed().then_some(transfer))
+        })
+        .await
+    }
+
+    /// Checks a completed transfer's proof against the state root of the
+    /// source shard block it references, as reported by this node rather than
+    /// by the cross-shard coordinator.
+    pub async fn verify_cross_shard_transfer(&self, transfer: &CrossShardTransfer) -> Result<bool> {
+        let proof = transfer.proof.as_ref().ok_or_else(|| {
+            VernachainError::ValidationError(format!(
+                "transfer {} has no proof yet",
+                transfer.transfer_id
+            ))
+        })?;
+        if proof.from_shard != transfer.from_shard || proof.to_shard != transfer.to_shard {
+            return Ok(false);
+        }
+
+        let source_block = self.get_block(proof.block_number, proof.from_shard).await?;
+        Ok(merkle::verify_cross_shard_proof(
+            proof,
+            &transfer.transaction.hash,
+            &source_block.state_root,
+        ))
+    }
+
+    // WebSocket Subscriptions
+    pub(crate) async fn open_socket(&self, path: &str) -> Result<WsStream> {
+        if self.is_shut_down() {
+            return Err(VernachainError::WebSocketClosed);
         }
+        let ws_url = format!("{}{}", self.ws_url, path);
+        let url = Url::parse(&ws_url).map_err(|e| VernachainError::InternalError(e.to_string()))?;
+        let connected =
+            connect_async_tls_with_config(url, None, false, self.ws_connector.clone())
+                .await
+                .map_err(VernachainError::from);
+        self.connection.observe(&connected);
+        let (mut socket, _) = connected?;
 
-        let http_client = HttpClient::builder()
-            .default_headers(headers)
-            .build()
-            .expect("Failed to create HTTP client");
+        // Handle API key authentication if needed
+        let token = self.auth.token().await?;
+        if !token.is_empty() {
+            socket
+                .send(Message::Text(json!({ "type": "auth", "token": token }).to_string()))
+                .await?;
+        }
 
-        let ws_url = node_url.replace("http", "ws");
+        Ok(socket)
+    }
 
-        Self {
-            http_client,
-            base_url: node_url.trim_end_matches('/').to_string(),
-            ws_url,
-            api_key,
+    /// Opens `topic` on the shared connection when multiplexing, or a socket
+    /// of its own on `path` otherwise.
+    async fn open_source(&self, path: &str, topic: String) -> Result<MessageSource> {
+        match self.multiplexer() {
+            Some(connections) => connections.subscribe(self, topic).await,
+            None => Ok(MessageSource::Socket(Box::new(self.open_socket(path).await?))),
         }
     }
 
-    async fn request<T>(&self, method: &str, endpoint: &str, body: Option<serde_json::Value>) -> Result<T>
+    /// The shared connection, if multiplexing is enabled and the node
+    /// supports it.
+    fn multiplexer(&self) -> Option<&ConnectionManager> {
+        let supported = self
+            .capabilities()
+            .is_some_and(|capabilities| capabilities.supports(ws::MULTIPLEX_FEATURE));
+        self.connections.as_deref().filter(|_| supported)
+    }
+
+    /// Forwards every JSON message on `path` (or `topic`) to the returned
+    /// subscription until `is_final` returns true for a message or the
+    /// source closes.
+    async fn subscribe_json<T, F>(
+        &self,
+        path: &str,
+        topic: String,
+        is_final: F,
+    ) -> Result<Subscription<T>>
     where
-        T: DeserializeOwned,
+        T: DeserializeOwned + Clone + Send + 'static,
+        F: Fn(&T) -> bool + Send + 'static,
     {
-        let url = format!("{}{}", self.base_url, endpoint);
-        let mut request = self.http_client.request(
-            method.parse().map_err(|_| VernachainError::InternalError("Invalid HTTP method".into()))?,
-            &url,
-        );
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let mut source = self.open_source(path, topic).await?;
+        let shutdown = self.shutdown.clone();
+        let keepalive = self.keepalive;
+        let strict = self.strict;
+
+        self.tasks.spawn(async move {
+            while let Some(text) = source.next_text(&shutdown, keepalive).await {
+                match decode::from_str::<T>(&text, strict) {
+                    Ok(item) => {
+                        let done = is_final(&item);
+                        if tx.send(SubscriptionEvent::Message(item)).is_err() || done {
+                            break;
+                        }
+                    }
+                    Err(e) => error!("Failed to parse subscription data: {}", e),
+                }
+            }
+        });
 
-        if let Some(data) = body {
-            request = request.json(&data);
+        Ok(Subscription::new(rx))
+    }
+
+    /// Oracle price updates for `symbols`, in each feed's own quote currency.
+    pub async fn subscribe_prices(&self, symbols: &[&str]) -> Result<Subscription<PriceTick>> {
+        if symbols.is_empty() {
+            return Err(VernachainError::ValidationError(
+                "no price symbols to subscribe to".to_string(),
+            ));
         }
+        let symbols = symbols.join(",");
+        self.subscribe_json(
+            &with_query("/ws/prices", &[("symbols", &symbols)])?,
+            format!("prices:{}", symbols),
+            |_: &PriceTick| false,
+        )
+        .await
+    }
+
+    pub async fn subscribe_blocks(&self, shard_id: ShardId) -> Result<Subscription<ChainEvent>> {
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let mut source = self
+            .open_source(
+                &format!("/ws/blocks?shard_id={}", shard_id),
+                format!("blocks:{}", shard_id),
+            )
+            .await?;
 
-        let response = request.send().await?;
-        
-        if !response.status().is_success() {
-            match response.status().as_u16() {
-                401 => return Err(VernachainError::AuthenticationError),
-                429 => return Err(VernachainError::RateLimitError),
-                _ => {
-                    let error_text = response.text().await?;
-                    return Err(VernachainError::NetworkError(error_text));
+        let client = self.detached();
+        self.tasks.spawn(async move {
+            let mut tracker = ReorgTracker::new(shard_id);
+            while let Some(text) = source.next_text(&client.shutdown, client.keepalive).await {
+                let block = match decode::from_str::<Block>(&text, client.strict) {
+                    Ok(block) => block,
+                    Err(e) => {
+                        error!("Failed to parse block data: {}", e);
+                        continue;
+                    }
+                };
+                let events = match tracker.process(block, &client).await {
+                    Ok(events) => events,
+                    Err(e) => {
+                        error!("Failed to resolve chain reorganization: {}", e);
+                        continue;
+                    }
+                };
+                for event in events {
+                    if let ChainEvent::Reorg { dropped, added, .. } = &event {
+                        info!(
+                            "Chain reorganization on shard {}: {} blocks dropped, {} added",
+                            shard_id,
+                            dropped.len(),
+                            added.len()
+                        );
+                    }
+                    if tx.send(SubscriptionEvent::Message(event)).is_err() {
+                        return;
+                    }
                 }
             }
-        }
+        });
 
-        let data = response.json().await?;
-        Ok(data)
+        Ok(Subscription::new(rx))
     }
 
-    // Transaction Methods
-    pub async fn create_transaction(&self, request: TransactionRequest) -> Result<Transaction> {
-        self.request(
-            "POST",
-            "/api/v1/transactions",
-            Some(serde_json::to_value(request)?),
+    pub async fn subscribe_cross_shard_transfer(
+        &self,
+        transfer_id: &str,
+    ) -> Result<Subscription<CrossShardTransfer>> {
+        self.subscribe_json(
+            &format!("/ws/cross-shard/{}", transfer_id),
+            format!("cross_shard:{}", transfer_id),
+            CrossShardTransfer::is_finished,
         )
         .await
     }
 
-    pub async fn get_transaction(&self, tx_hash: &str) -> Result<Transaction> {
-        self.request("GET", &format!("/api/v1/transactions/{}", tx_hash), None).await
+    pub async fn subscribe_bridge_transfer(
+        &self,
+        transfer_id: &str,
+    ) -> Result<Subscription<BridgeTransfer>> {
+        self.subscribe_json(
+            &format!("/ws/bridge/{}", transfer_id),
+            format!("bridge:{}", transfer_id),
+            BridgeTransfer::is_finished,
+        )
+        .await
     }
 
-    // Block Methods
-    pub async fn get_block(&self, block_number: u64, shard_id: u64) -> Result<Block> {
+    /// Logs matching `filter` from `from_block` on: first the historical ones,
+    /// then each new block's as it arrives, each delivered once. The filter
+    /// needs a shard, and its own block range is ignored. Logs of blocks
+    /// dropped by a reorg are re-sent with `removed`. The stream ends if the
+    /// node can't be reached after retrying.
+    pub async fn stream_events(
+        &self,
+        filter: LogFilter,
+        from_block: u64,
+    ) -> Result<Subscription<Log>> {
+        let shard_id = filter.shard_id.ok_or_else(|| {
+            VernachainError::ValidationError("event stream filter needs a shard".to_string())
+        })?;
+        // Subscribe before paging history so no block falls between the two.
+        let blocks = self.subscribe_blocks(shard_id).await?;
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let stream = LogStream::new(self.detached(), filter, shard_id, from_block, tx);
+        self.tasks.spawn(stream.run(blocks));
+        Ok(Subscription::new(rx))
+    }
+
+    /// `stream_events` starting right after the cursor saved in `cursors`
+    /// under `stream`, or at `from_block` if there is none. Save
+    /// `Cursor::from(&log)` for every log once it is handled, removed ones
+    /// included, and a restart neither repeats nor skips any.
+    pub async fn resume_events(
+        &self,
+        filter: LogFilter,
+        cursors: &dyn CursorStore,
+        stream: &str,
+        from_block: u64,
+    ) -> Result<Subscription<Log>> {
+        let shard_id = filter.shard_id.ok_or_else(|| {
+            VernachainError::ValidationError("event stream filter needs a shard".to_string())
+        })?;
+        let cursor = cursors.load(stream).await?;
+        let blocks = self.subscribe_blocks(shard_id).await?;
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let mut log_stream = LogStream::new(self.detached(), filter, shard_id, from_block, tx);
+        if let Some(cursor) = cursor {
+            log_stream = log_stream.resume_after(cursor);
+        }
+        self.tasks.spawn(log_stream.run(blocks));
+        Ok(Subscription::new(rx))
+    }
+
+    /// Blocks of `shard_id` from the cursor saved in `cursors` under
+    /// `stream`, or from `from_block` if there is none: first from history,
+    /// then as they arrive. Save `Cursor::after(&event)` once each event is
+    /// handled to resume after it on restart.
+    pub async fn resume_blocks(
+        &self,
+        shard_id: ShardId,
+        cursors: &dyn CursorStore,
+        stream: &str,
+        from_block: u64,
+    ) -> Result<Subscription<ChainEvent>> {
+        let from_block = match cursors.load(stream).await? {
+            Some(cursor) => cursor.block_number + 1,
+            None => from_block,
+        };
+        let blocks = self.subscribe_blocks(shard_id).await?;
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let block_stream = BlockStream::new(self.detached(), shard_id, from_block, tx);
+        self.tasks.spawn(block_stream.run(blocks));
+        Ok(Subscription::new(rx))
+    }
+
+    /// Reports transfers to and from `address` as they land in blocks on its
+    /// shard. Activity in blocks dropped by a reorg is re-sent with `removed`,
+    /// and blocks the watch fell behind on are reported as missed messages.
+    pub async fn watch_address(&self, address: &str) -> Result<Subscription<AddressActivity>> {
+        let shard_id = self.resolve_shard(address).await?;
+        let mut blocks = self.subscribe_blocks(shard_id).await?;
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let address = address.to_string();
+
+        self.tasks.spawn(async move {
+            loop {
+                let activity = match blocks.recv().await {
+                    Some(SubscriptionEvent::Message(ChainEvent::NewBlock(block))) => {
+                        address_activity(&address, &block, false)
+                    }
+                    Some(SubscriptionEvent::Message(ChainEvent::Reorg { dropped, added, .. })) => {
+                        let reverted = dropped.iter().map(|block| (block, true));
+                        let applied = added.iter().map(|block| (block, false));
+                        reverted
+                            .chain(applied)
+                            .flat_map(|(block, removed)| address_activity(&address, block, removed))
+                            .collect()
+                    }
+                    Some(SubscriptionEvent::MissedMessages(skipped)) => {
+                        error!("Address watch for {} missed {} blocks", address, skipped);
+                        if tx.send(SubscriptionEvent::MissedMessages(skipped)).is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                    None => break,
+                };
+                for item in activity {
+                    if tx.send(SubscriptionEvent::Message(item)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Subscription::new(rx))
+    }
+
+    /// Alerts as soon as any of `validator_addresses` is slashed or jailed,
+    /// with the node pushing each event in the block that applies it.
+    pub async fn watch_slashing(
+        &self,
+        validator_addresses: &[&str],
+    ) -> Result<Subscription<SlashingAlert>> {
+        if validator_addresses.is_empty() {
+            return Err(VernachainError::ValidationError(
+                "no validators to watch".to_string(),
+            ));
+        }
+        let validators = validator_addresses.join(",");
+        let mut events = self
+            .subscribe_json(
+                &with_query("/ws/slashing", &[("validators", &validators)])?,
+                format!("slashing:{}", validators),
+                |_: &SlashingEvent| false,
+            )
+            .await?;
+        let (tx, rx) = broadcast::channel(self.subscription_buffer);
+        let watched: HashSet<String> =
+            validator_addresses.iter().map(|a| a.to_string()).collect();
+
+        self.tasks.spawn(async move {
+            while let Some(event) = events.recv().await {
+                let alert = match event {
+                    SubscriptionEvent::Message(event) => {
+                        if !watched.contains(&event.validator_address) {
+                            continue;
+                        }
+                        SubscriptionEvent::Message(SlashingAlert::from(event))
+                    }
+                    SubscriptionEvent::MissedMessages(skipped) => {
+                        error!("Slashing watch missed {} events", skipped);
+                        SubscriptionEvent::MissedMessages(skipped)
+                    }
+                };
+                if tx.send(alert).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Subscription::new(rx))
+    }
+
+    // Webhook Methods
+    /// Registers `url` to receive events matching `filter`. Deliveries are signed
+    /// with `secret`; see `webhooks::verify_signature`.
+    pub async fn register_webhook(
+        &self,
+        filter: WebhookFilter,
+        url: &str,
+        secret: &str,
+    ) -> Result<Webhook> {
+        self.require_feature("webhooks")?;
+        let request = WebhookRegistrationRequest {
+            filter,
+            url: url.to_string(),
+            secret: secret.to_string(),
+        };
+        self.request("POST", "/webhooks", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>> {
+        self.require_feature("webhooks")?;
+        self.request("GET", "/webhooks", None).await
+    }
+
+    pub async fn delete_webhook(&self, webhook_id: &str) -> Result<Webhook> {
+        self.require_feature("webhooks")?;
+        self.request("DELETE", &format!("/webhooks/{}", webhook_id), None)
+            .await
+    }
+
+    // Debug Methods
+    pub async fn trace_transaction(&self, tx_hash: &str) -> Result<TransactionTrace> {
         self.request(
             "GET",
-            &format!("/api/v1/blocks/{}?shard_id={}", block_number, shard_id),
+            &format!("/debug/trace/transactions/{}", tx_hash),
             None,
         )
         .await
     }
 
-    pub async fn get_latest_block(&self, shard_id: u64) -> Result<Block> {
+    pub async fn trace_block(
+        &self,
+        block_number: u64,
+        shard_id: ShardId,
+    ) -> Result<Vec<TransactionTrace>> {
         self.request(
             "GET",
-            &format!("/api/v1/blocks/latest?shard_id={}", shard_id),
+            &format!(
+                "/debug/trace/blocks/{}?shard_id={}",
+                block_number, shard_id
+            ),
             None,
         )
         .await
     }
 
-    // Smart Contract Methods
-    pub async fn deploy_contract(&self, request: ContractDeployRequest) -> Result<SmartContract> {
+    // Validator Operations
+    pub async fn get_validator_set(&self, shard_id: ShardId) -> Result<Vec<Validator>> {
         self.request(
-            "POST",
-            "/api/v1/contracts",
-            Some(serde_json::to_value(request)?),
+            "GET",
+            &format!("/validators?shard_id={}", shard_id),
+            None,
         )
         .await
     }
 
-    pub async fn call_contract(
+    pub async fn get_validator(&self, address: &str) -> Result<Validator> {
+        self.request("GET", &format!("/validators/{}", address), None)
+            .await
+    }
+
+    /// One page of the delegations to the validator at `address`. Pass the
+    /// previous page's `next_cursor` to continue.
+    pub async fn get_validator_delegators(
         &self,
-        contract_address: &str,
-        method: &str,
-        params: serde_json::Value,
-    ) -> Result<serde_json::Value> {
+        address: &str,
+        cursor: Option<&str>,
+    ) -> Result<DelegationPage> {
+        let endpoint = with_query(
+            &format!("/validators/{}/delegators", address),
+            &[("cursor", cursor)],
+        )?;
+        self.request("GET", &endpoint, None).await
+    }
+
+    /// Stake, commission rate and reputation changes of the validator at
+    /// `address` within `range`.
+    pub async fn get_validator_history(
+        &self,
+        address: &str,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<ValidatorHistory> {
+        let query = TimeRangeQuery {
+            from: range.start,
+            to: range.end,
+        };
+        let endpoint = with_query(&format!("/validators/{}/history", address), &query)?;
+        self.request("GET", &endpoint, None).await
+    }
+
+    pub async fn get_validator_performance(
+        &self,
+        address: &str,
+        epochs: RangeInclusive<u64>,
+    ) -> Result<ValidatorPerformance> {
         self.request(
-            "POST",
-            &format!("/api/v1/contracts/{}/call", contract_address),
-            Some(json!({
-                "method": method,
-                "params": params,
-            })),
+            "GET",
+            &format!(
+                "/validators/{}/performance?from_epoch={}&to_epoch={}",
+                address,
+                epochs.start(),
+                epochs.end()
+            ),
+            None,
         )
         .await
     }
 
-    // Cross-Shard Operations
-    pub async fn initiate_cross_shard_transfer(
-        &self,
-        request: CrossShardTransferRequest,
-    ) -> Result<CrossShardTransfer> {
+    pub async fn get_slashing_events(&self, filter: &SlashingFilter) -> Result<Vec<SlashingEvent>> {
+        self.request("GET", &with_query("/slashing", filter)?, None)
+            .await
+    }
+
+    pub async fn get_current_epoch(&self, shard_id: ShardId) -> Result<Epoch> {
         self.request(
-            "POST",
-            "/api/v1/cross-shard/transfer",
-            Some(serde_json::to_value(request)?),
+            "GET",
+            &format!("/epochs/current?shard_id={}", shard_id),
+            None,
         )
         .await
     }
 
-    // WebSocket Subscriptions
-    pub async fn subscribe_blocks(
+    pub async fn get_epoch(&self, number: u64) -> Result<Epoch> {
+        self.request("GET", &format!("/epochs/{}", number), None).await
+    }
+
+    /// The block proposers of `epoch` on `shard_id`, known once the epoch's
+    /// validator set is fixed.
+    pub async fn get_proposal_schedule(
         &self,
-        shard_id: u64,
-    ) -> Result<broadcast::Receiver<Block>> {
-        let (tx, rx) = broadcast::channel(100);
-        let ws_url = format!("{}/ws/blocks?shard_id={}", self.ws_url, shard_id);
-        let tx = Arc::new(tx);
+        shard_id: ShardId,
+        epoch: u64,
+    ) -> Result<ProposalSchedule> {
+        self.request(
+            "GET",
+            &format!("/epochs/{}/schedule?shard_id={}", epoch, shard_id),
+            None,
+        )
+        .await
+    }
 
-        let url = Url::parse(&ws_url).map_err(|e| VernachainError::InternalError(e.to_string()))?;
-        let (ws_stream, _) = connect_async(url).await?;
-        let (mut write, mut read) = ws_stream.split();
+    pub async fn get_staking_params(&self) -> Result<StakingParams> {
+        self.request("GET", "/staking/params", None).await
+    }
 
-        // Handle API key authentication if needed
-        if let Some(key) = &self.api_key {
-            write
-                .send(Message::Text(json!({ "type": "auth", "token": key }).to_string()))
-                .await?;
+    pub async fn stake(&self, request: StakeRequest) -> Result<StakeResult> {
+        self.request("POST", "/stake", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    pub async fn unstake(&self, amount: f64, validator_address: &str) -> Result<StakeResult> {
+        let request = UnstakeRequest {
+            validator_address: validator_address.to_string(),
+            amount,
+        };
+        self.request("POST", "/unstake", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    pub async fn delegate(&self, validator_address: &str, amount: f64) -> Result<StakeResult> {
+        let request = DelegationRequest {
+            validator_address: validator_address.to_string(),
+            amount,
+        };
+        self.request("POST", "/delegate", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    pub async fn undelegate(&self, validator_address: &str, amount: f64) -> Result<StakeResult> {
+        let request = DelegationRequest {
+            validator_address: validator_address.to_string(),
+            amount,
+        };
+        self.request("POST", "/undelegate", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    pub async fn get_delegations(&self, address: &str) -> Result<Vec<Delegation>> {
+        self.request("GET", &format!("/accounts/{}/delegations", address), None)
+            .await
+    }
+
+    pub async fn get_pending_rewards(&self, address: &str) -> Result<PendingRewards> {
+        self.request("GET", &format!("/accounts/{}/rewards", address), None)
+            .await
+    }
+
+    pub async fn claim_rewards(&self, validator_address: Option<&str>) -> Result<RewardClaim> {
+        let request = ClaimRewardsRequest {
+            validator_address: validator_address.map(str::to_string),
+        };
+        self.request("POST", "/rewards/claim", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    pub async fn register_validator(&self, request: ValidatorRegistrationRequest) -> Result<Validator> {
+        check_commission_rate(request.commission_rate)?;
+        self.request("POST", "/validators", Some(serde_json::to_value(request)?))
+            .await
+    }
+
+    /// Updates the validator operated by the authenticated account.
+    pub async fn update_validator(
+        &self,
+        commission_rate: Option<f64>,
+        metadata: Option<ValidatorMetadata>,
+    ) -> Result<Validator> {
+        if let Some(rate) = commission_rate {
+            check_commission_rate(rate)?;
         }
+        let request = ValidatorUpdateRequest {
+            commission_rate,
+            metadata,
+        };
+        self.request("PATCH", "/validators/me", Some(serde_json::to_value(request)?))
+            .await
+    }
 
-        let tx_clone = tx.clone();
-        tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
-                match msg {
-                    Ok(Message::Text(text)) => {
-                        match serde_json::from_str::<Block>(&text) {
-                            Ok(block) => {
-                                if tx_clone.send(block).is_err() {
-                                    break;
-                                }
-                            }
-                            Err(e) => error!("Failed to parse block data: {}", e),
-                        }
-                    }
-                    Ok(Message::Close(_)) => break,
-                    Err(e) => {
-                        error!("WebSocket error: {}", e);
-                        break;
-                    }
-                    _ => {}
-                }
-            }
-        });
+    /// Has the node generate and register a fresh signing key for the
+    /// authenticated validator.
+    pub async fn rotate_validator_keys(&self) -> Result<KeyRotation> {
+        self.request("POST", "/validators/me/rotate-keys", None).await
+    }
+
+    // Bridge Operations
+    pub async fn get_bridge_chains(&self) -> Result<Vec<BridgeChain>> {
+        self.request("GET", "/bridge/chains", None).await
+    }
 
-        Ok(rx)
+    pub async fn get_bridge_tokens(&self, chain: &ChainId) -> Result<Vec<BridgeToken>> {
+        self.request("GET", &format!("/bridge/chains/{}/tokens", chain), None)
+            .await
     }
 
-    // Validator Operations
-    pub async fn get_validator_set(&self, shard_id: u64) -> Result<Vec<Validator>> {
+    /// Prices a transfer without submitting it.
+    pub async fn quote_bridge_transfer(&self, request: &BridgeTransferRequest) -> Result<BridgeQuote> {
         self.request(
-            "GET",
-            &format!("/api/v1/validators?shard_id={}", shard_id),
-            None,
+            "POST",
+            "/bridge/quote",
+            Some(serde_json::to_value(request)?),
         )
         .await
     }
 
-    pub async fn stake(&self, amount: f64, validator_address: &str) -> Result<serde_json::Value> {
+    pub async fn bridge_transfer(&self, request: BridgeTransferRequest) -> Result<BridgeTransfer> {
         self.request(
             "POST",
-            "/api/v1/stake",
-            Some(json!({
-                "amount": amount,
-                "validator_address": validator_address,
-            })),
+            "/bridge/transfer",
+            Some(serde_json::to_value(request)?),
         )
         .await
     }
 
-    // Bridge Operations
-    pub async fn bridge_transfer(&self, request: BridgeTransferRequest) -> Result<BridgeTransfer> {
+    pub async fn get_bridge_transfer(&self, transfer_id: &str) -> Result<BridgeTransfer> {
+        self.request("GET", &format!("/bridge/transfers/{}", transfer_id), None)
+            .await
+    }
+
+    /// Submits a bridge transfer and polls until it completes on the target
+    /// chain, which waits out the bridge's own confirmation depth. A transfer
+    /// that ends in failure is reported as `TransferFailed`. `timeout` covers
+    /// both.
+    pub async fn bridge_transfer_and_wait(
+        &self,
+        request: BridgeTransferRequest,
+        timeout: Duration,
+    ) -> Result<BridgeTransfer> {
+        let submit_and_wait = async {
+            let transfer_id = &self.bridge_transfer(request).await?.transfer_id;
+            self.poll(timeout, || async {
+                let transfer = self.get_bridge_transfer(transfer_id).await?;
+                if transfer.is_failed() {
+                    return Err(VernachainError::TransferFailed {
+                        transfer_id: transfer.transfer_id,
+                        status: transfer.status,
+                    });
+                }
+                Ok(transfer.is_completed().then_some(transfer))
+            })
+            .await
+        };
+        tokio::time::timeout(timeout, submit_and_wait)
+            .await
+            .map_err(|_| VernachainError::TimeoutError)?
+    }
+
+    /// Looks up a bridge transaction by its hash on the bridge.
+    pub async fn get_bridge_transaction(&self, tx_hash: &str) -> Result<BridgeTransactionDetail> {
+        self.request("GET", &format!("/bridge/transaction/{}", tx_hash), None)
+            .await
+    }
+
+    pub async fn get_bridge_proof(&self, transfer_id: &str) -> Result<BridgeProof> {
+        self.request("GET", &format!("/bridge/transfers/{}/proof", transfer_id), None)
+            .await
+    }
+
+    /// Claims a transfer on the destination chain using the proof obtained from
+    /// the source side.
+    pub async fn claim_bridge_transfer(
+        &self,
+        transfer_id: &str,
+        proof: &BridgeProof,
+    ) -> Result<BridgeTransfer> {
         self.request(
             "POST",
-            "/api/v1/bridge/transfer",
-            Some(serde_json::to_value(request)?),
+            &format!("/bridge/transfers/{}/claim", transfer_id),
+            Some(json!({ "proof": proof })),
         )
         .await
     }
-} 
\ No newline at end of file
+}
+
+fn address_activity(address: &str, block: &Block, removed: bool) -> Vec<AddressActivity> {
+    block
+        .transactions
+        .iter()
+        .filter_map(|tx| {
+            let kind = if tx.sender.eq_ignore_ascii_case(address) {
+                if tx.data.is_some() {
+                    ActivityKind::ContractInteraction
+                } else {
+                    ActivityKind::Outgoing
+                }
+            } else if tx.recipient.eq_ignore_ascii_case(address) {
+                ActivityKind::Incoming
+            } else {
+                return None;
+            };
+            Some(AddressActivity {
+                kind,
+                transaction: tx.clone(),
+                block_number: block.number,
+                block_hash: block.hash.clone(),
+                removed,
+            })
+        })
+        .collect()
+}
+
+/// Treats a rejected lookup as a miss so `search` can try the next candidate.
+fn not_found_as_none<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(VernachainError::Api {
+            status: 400 | 404, ..
+        }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn check_commission_rate(rate: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(VernachainError::ValidationError(format!(
+            "commission rate must be between 0 and 1, got {}",
+            rate
+        )));
+    }
+    Ok(())
+}
+
+/// Appends `params` to `endpoint` as a URL query string.
+fn with_query<T: Serialize>(endpoint: &str, params: &T) -> Result<String> {
+    let query = serde_urlencoded::to_string(params)
+        .map_err(|e| VernachainError::InternalError(e.to_string()))?;
+    if query.is_empty() {
+        Ok(endpoint.to_string())
+    } else {
+        Ok(format!("{}?{}", endpoint, query))
+    }
+}
+
+/// Maps a node URL onto the matching WebSocket scheme: `http` to `ws` and
+/// `https` to `wss`. WebSocket URLs are accepted unchanged.
+fn websocket_url(url: &str) -> Result<String> {
+    let mut parsed = Url::parse(url)
+        .map_err(|e| VernachainError::ConfigurationError(format!("invalid URL {}: {}", url, e)))?;
+    let scheme = match parsed.scheme() {
+        "http" | "ws" => "ws",
+        "https" | "wss" => "wss",
+        other => {
+            return Err(VernachainError::ConfigurationError(format!(
+                "unsupported URL scheme {} in {}",
+                other, url
+            )))
+        }
+    };
+    parsed.set_scheme(scheme).map_err(|_| {
+        VernachainError::ConfigurationError(format!("cannot use {} as a WebSocket URL", url))
+    })?;
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+fn api_key_provider(api_key: &str) -> Result<Arc<dyn AuthProvider>> {
+    HeaderValue::from_str(&format!("Bearer {}", api_key)).map_err(|_| {
+        VernachainError::ConfigurationError(
+            "API key contains characters not allowed in a header".to_string(),
+        )
+    })?;
+    Ok(Arc::new(StaticToken::new(api_key)))
+}
+
+fn tls_error(error: impl std::fmt::Display) -> VernachainError {
+    VernachainError::ConfigurationError(format!("invalid TLS configuration: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use wiremock::{
+        matchers::{header_exists, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn search_finds_accounts_by_public_key_address() {
+        let address = "ab".repeat(32);
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v1/accounts/{}", address)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "address": address,
+                "balance": 12.5,
+                "nonce": 3,
+                "shard_id": 1,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+
+        match client.search(&address).await.unwrap() {
+            SearchResult::Account(account) => assert_eq!(account.nonce, 3),
+            other => panic!("expected an account, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn negotiation_goes_through_the_signing_transport() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/versions"))
+            .and(header_exists(crate::auth::SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "versions": ["v1"],
+                "features": ["multicall"],
+            })))
+            .mount(&server)
+            .await;
+        let mut client = VernachainClient::builder(&server.uri())
+            .hmac_auth("key", "secret")
+            .build()
+            .unwrap();
+
+        let capabilities = client.negotiate_api_version().await.unwrap();
+        assert_eq!(capabilities.api_version, "v1");
+        assert!(capabilities.supports("multicall"));
+    }
+
+    #[tokio::test]
+    async fn rest_signs_exactly_the_bytes_it_sends() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        let hmac = HmacAuth::new("key-1", "s3cret");
+        let transport =
+            RestTransport::new(HttpClient::new(), &server.uri()).with_hmac_auth(hmac.clone());
+        let body = json!({ "memo": "caf\u{e9}", "amount": 1.5, "to": "bob" });
+        transport
+            .send("POST", "/api/v1/transactions?shard_id=0", Some(body))
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let request = &requests[0];
+        let header = |name: &str| {
+            let (_, values) = request
+                .headers
+                .iter()
+                .find(|(header, _)| header.as_str().eq_ignore_ascii_case(name))
+                .unwrap();
+            values.last().as_str().to_string()
+        };
+        let timestamp = header(crate::auth::TIMESTAMP_HEADER).parse().unwrap();
+        let [_, _, (_, expected)] = hmac.headers(
+            "POST",
+            "/api/v1/transactions?shard_id=0",
+            &request.body,
+            timestamp,
+        );
+        assert_eq!(header(crate::auth::SIGNATURE_HEADER), expected);
+        assert_eq!(header(crate::auth::KEY_HEADER), "key-1");
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn admin_calls_are_signed_like_the_rest() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/admin/peers"))
+            .and(header_exists(crate::auth::SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = VernachainClient::builder(&server.uri())
+            .hmac_auth("key", "secret")
+            .admin_token("admin")
+            .build()
+            .unwrap();
+
+        client.admin().unwrap().add_peer("10.0.0.2:30303").await.unwrap();
+    }
+
+    #[cfg(feature = "admin")]
+    #[test]
+    fn admin_token_rejects_a_custom_transport() {
+        let transport = RestTransport::new(HttpClient::new(), "http://localhost:8545");
+        let result = VernachainClient::builder("http://localhost:8545")
+            .transport(transport)
+            .admin_token("admin")
+            .build();
+        assert!(matches!(result, Err(VernachainError::ConfigurationError(_))));
+    }
+
+    #[tokio::test]
+    async fn finality_reports_failed_transactions() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/transactions/0xfeed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "hash": "0xfeed",
+                "sender": "alice",
+                "recipient": "bob",
+                "amount": 1.0,
+                "timestamp": "2024-01-01T00:00:00Z",
+                "shard_id": 0,
+                "status": "failed",
+                "block_number": 7,
+            })))
+            .mount(&server)
+            .await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+
+        let result = client
+            .wait_for_finality("0xfeed", Duration::from_secs(5))
+            .await;
+        assert!(matches!(
+            result,
+            Err(VernachainError::TransactionFailed { ref tx_hash, .. }) if tx_hash == "0xfeed"
+        ));
+    }
+
+    #[tokio::test]
+    async fn multiplexes_only_on_nodes_that_support_it() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/versions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "versions": ["v1"],
+                "features": ["ws_multiplex"],
+            })))
+            .mount(&server)
+            .await;
+        let mut client = VernachainClient::builder(&server.uri())
+            .multiplex_subscriptions(true)
+            .build()
+            .unwrap();
+        assert!(client.multiplexer().is_none());
+
+        client.negotiate_api_version().await.unwrap();
+        assert!(client.multiplexer().is_some());
+
+        let mut plain = VernachainClient::new(&server.uri(), None).unwrap();
+        plain.negotiate_api_version().await.unwrap();
+        assert!(plain.multiplexer().is_none());
+    }
+
+    #[tokio::test]
+    async fn nodes_without_version_discovery_speak_v1() {
+        let server = MockServer::start().await;
+        let mut client = VernachainClient::new(&server.uri(), None).unwrap();
+        let capabilities = client.negotiate_api_version().await.unwrap();
+        assert_eq!(capabilities.api_version, DEFAULT_API_VERSION);
+        assert!(capabilities.features.is_empty());
+    }
+
+    #[tokio::test]
+    async fn staking_params_keep_unknown_fields_unless_strict() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/staking/params"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "annual_inflation_rate": 0.05,
+                "total_supply": 1000.0,
+                "total_staked": 400.0,
+                "epochs_per_year": 365,
+                "min_stake": 10,
+            })))
+            .mount(&server)
+            .await;
+
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        let params = client.get_staking_params().await.unwrap();
+        assert_eq!(params.extra.get("min_stake"), Some(&json!(10)));
+
+        let strict = VernachainClient::builder(&server.uri())
+            .strict_deserialization(true)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            strict.get_staking_params().await,
+            Err(VernachainError::SerializationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn bridge_transaction_accepts_naive_timestamps() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v1/bridge/transaction/0xabc"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "bridge_tx_hash": "0xabc",
+                "from_chain_tx": "0x01",
+                "to_chain_tx": "0x02",
+                "status": "completed",
+                "timestamp": "2024-01-01T12:30:00.250000",
+            })))
+            .mount(&server)
+            .await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+
+        let detail = client.get_bridge_transaction("0xabc").await.unwrap();
+        assert_eq!(detail.to_chain_tx.as_deref(), Some("0x02"));
+        assert_eq!(detail.timestamp.to_string(), "2024-01-01 12:30:00.250");
+    }
+
+    #[tokio::test]
+    async fn deterministic_deploy_needs_node_support() {
+        let server = MockServer::start().await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        let request = ContractDeployRequest {
+            contract_type: ContractType::Token,
+            params: HashMap::new(),
+            shard_id: ShardId(0),
+            gas_limit: None,
+            salt: None,
+        };
+
+        let result = client
+            .deploy_contract_deterministic(request, &"ab".repeat(32), [7; 32])
+            .await;
+        assert!(matches!(result, Err(VernachainError::UnsupportedFeature(_))));
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+}
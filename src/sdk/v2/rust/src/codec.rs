@@ -0,0 +1,291 @@
+//! Canonical encodings shared with the node.
+//!
+//! The node hashes Python's `json.dumps(value, sort_keys=True)`, so everything
+//! here reproduces that output byte for byte: `", "` and `": "` separators,
+//! ASCII-only strings, and Python's float formatting.
+
+use crate::{
+    address::Address,
+    error::{Result, VernachainError},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, ser::Formatter};
+use sha2::{Digest, Sha256};
+use std::io;
+
+/// Serializes `value` the way the node's `json.dumps(value, sort_keys=True)` does.
+pub fn canonical_json<T: Serialize>(value: &T) -> serde_json::Result<Vec<u8>> {
+    // Round-trip through `Value` so object keys come out sorted.
+    let value = serde_json::to_value(value)?;
+    let mut out = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, PythonFormatter);
+    value.serialize(&mut serializer)?;
+    Ok(out)
+}
+
+/// The bytes the node hashes to identify a transfer: the transaction's
+/// `from`, `to`, `value`, `nonce` and ISO-formatted `timestamp`.
+pub fn encode_transaction(
+    sender: &str,
+    recipient: &str,
+    amount: f64,
+    nonce: u64,
+    timestamp: &DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    let fields = json!({
+        "from": sender,
+        "to": recipient,
+        "value": amount,
+        "nonce": nonce,
+        "timestamp": python_isoformat(timestamp),
+    });
+    Ok(canonical_json(&fields)?)
+}
+
+/// Hex SHA-256 of `encode_transaction`, as the node reports in `hash`.
+pub fn transaction_hash(
+    sender: &str,
+    recipient: &str,
+    amount: f64,
+    nonce: u64,
+    timestamp: &DateTime<Utc>,
+) -> Result<String> {
+    let encoded = encode_transaction(sender, recipient, amount, nonce, timestamp)?;
+    Ok(hex::encode(Sha256::digest(encoded)))
+}
+
+/// What a contract's address is derived from, besides its deployer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeploymentSeed {
+    /// The deployer's account nonce when the deployment is sent.
+    Nonce(u64),
+    /// A salt chosen by the deployer, giving the same address on every shard
+    /// and network.
+    Salt([u8; 32]),
+}
+
+impl From<u64> for DeploymentSeed {
+    fn from(nonce: u64) -> Self {
+        DeploymentSeed::Nonce(nonce)
+    }
+}
+
+impl From<[u8; 32]> for DeploymentSeed {
+    fn from(salt: [u8; 32]) -> Self {
+        DeploymentSeed::Salt(salt)
+    }
+}
+
+/// The address a contract deployed by `deployer` gets on nodes reporting the
+/// `deterministic_addresses` feature: `0x` and the first 20 bytes of the
+/// SHA-256 of the canonical `{"deployer", "nonce"}` or `{"deployer", "salt"}`
+/// object, with the deployer in lower case.
+///
+/// Other nodes, the reference node among them, derive addresses from the
+/// contract code and the time of deployment, which can't be predicted; the
+/// result doesn't apply to them.
+pub fn predict_contract_address(deployer: &str, seed: impl Into<DeploymentSeed>) -> Result<String> {
+    let deployer = Address::parse(deployer)?;
+    let fields = match seed.into() {
+        DeploymentSeed::Nonce(nonce) => json!({
+            "deployer": deployer.digits(),
+            "nonce": nonce,
+        }),
+        DeploymentSeed::Salt(salt) => json!({
+            "deployer": deployer.digits(),
+            "salt": hex::encode(salt),
+        }),
+    };
+    let digest = Sha256::digest(canonical_json(&fields)?);
+    Ok(format!("0x{}", hex::encode(&digest[..20])))
+}
+
+pub(crate) fn missing_field(field: &str) -> VernachainError {
+    VernachainError::ValidationError(format!("{} is required to compute the hash", field))
+}
+
+/// Python's `datetime.isoformat()` for a naive UTC timestamp: microseconds
+/// only when non-zero, and no offset.
+fn python_isoformat(timestamp: &DateTime<Utc>) -> String {
+    let naive = timestamp.naive_utc();
+    if timestamp.timestamp_subsec_micros() == 0 {
+        naive.format("%Y-%m-%dT%H:%M:%S").to_string()
+    } else {
+        naive.format("%Y-%m-%dT%H:%M:%S%.6f").to_string()
+    }
+}
+
+/// Python's `repr(float)`: the shortest round-tripping digits, in positional
+/// form for decimal exponents in `-4..16` and scientific form otherwise.
+fn python_float(value: f64) -> String {
+    // `{:e}` yields the shortest digits, e.g. "1.5e-5" or "-1e16".
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+
+    if !(-4..16).contains(&exponent) {
+        let fraction = &digits[1..];
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        return if fraction.is_empty() {
+            format!(
+                "{}{}e{}{:02}",
+                sign,
+                &digits[..1],
+                exponent_sign,
+                exponent.abs()
+            )
+        } else {
+            format!(
+                "{}{}.{}e{}{:02}",
+                sign,
+                &digits[..1],
+                fraction,
+                exponent_sign,
+                exponent.abs()
+            )
+        };
+    }
+
+    let point = exponent + 1;
+    if point <= 0 {
+        format!(
+            "{}0.{}{}",
+            sign,
+            "0".repeat(point.unsigned_abs() as usize),
+            digits
+        )
+    } else if point as usize >= digits.len() {
+        format!(
+            "{}{}{}.0",
+            sign,
+            digits,
+            "0".repeat(point as usize - digits.len())
+        )
+    } else {
+        let (whole, fraction) = digits.split_at(point as usize);
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
+struct PythonFormatter;
+
+impl Formatter for PythonFormatter {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+
+    fn write_f64<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        writer.write_all(python_float(value).as_bytes())
+    }
+
+    fn write_string_fragment<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        // Python escapes everything outside ASCII by default.
+        for c in fragment.chars() {
+            if c.is_ascii() {
+                writer.write_all(&[c as u8])?;
+            } else {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    write!(writer, "\\u{:04x}", unit)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Expected values are Python's output for the same inputs.
+
+    #[test]
+    fn formats_floats_like_python_repr() {
+        let cases = [
+            (0.0, "0.0"),
+            (1.0, "1.0"),
+            (1.5, "1.5"),
+            (0.1, "0.1"),
+            (100.0, "100.0"),
+            (-2.5, "-2.5"),
+            (0.0001, "0.0001"),
+            (123456789012345.6, "123456789012345.6"),
+            (1e16, "1e+16"),
+            (1e22, "1e+22"),
+            (1.5e-5, "1.5e-05"),
+            (1.25e-7, "1.25e-07"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(python_float(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn matches_python_json_dumps() {
+        let value = json!({
+            "b": [1, 2.5, "é"],
+            "a": {"y": null, "x": true},
+            "c": "😀",
+        });
+        assert_eq!(
+            String::from_utf8(canonical_json(&value).unwrap()).unwrap(),
+            r#"{"a": {"x": true, "y": null}, "b": [1, 2.5, "\u00e9"], "c": "\ud83d\ude00"}"#
+        );
+    }
+
+    #[test]
+    fn hashes_transactions_like_the_node() {
+        let timestamp = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            transaction_hash("alice", "bob", 1.5, 3, &timestamp).unwrap(),
+            "c7a13f8c24252565f5d32bd0c1041943c2c4a6b4984436cd84fc037055597d26"
+        );
+
+        let timestamp = timestamp + chrono::Duration::milliseconds(250);
+        assert_eq!(
+            String::from_utf8(encode_transaction("alice", "bob", 10.0, 0, &timestamp).unwrap())
+                .unwrap(),
+            r#"{"from": "alice", "nonce": 0, "timestamp": "2024-01-01T12:00:00.250000", "to": "bob", "value": 10.0}"#
+        );
+        assert_eq!(
+            transaction_hash("alice", "bob", 10.0, 0, &timestamp).unwrap(),
+            "97ff0946af1e1f995dda50461922805618cafd0c50d12f845c42c802ceeaee91"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Concurrency limits that adapt to how the node is coping.
+//!
+//! `AdaptiveConcurrency` starts at its maximum. A call that is rate limited,
+//! finds the node unavailable or times out halves the limit, and latency
+//! climbing well above the best seen cuts it by a tenth. Every call that
+//! completes normally raises it a little, back up to the maximum. At most one
+//! cut is made per round of calls, so a burst of failures from calls that
+//! were already in flight counts once.
+
+use crate::error::{Result, VernachainError};
+use std::{
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+use tracing::debug;
+
+/// Weight of each new latency sample in the running average.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// How far the best latency adjusts towards the current one per call, so a
+/// node that is slower for good stops counting as congested.
+const BASELINE_DRIFT: f64 = 0.01;
+
+/// Average latency, as a multiple of the best, above which the node counts
+/// as congested.
+const LATENCY_TOLERANCE: f64 = 2.0;
+
+pub struct AdaptiveConcurrency {
+    max: usize,
+    min: usize,
+    state: Mutex<State>,
+    released: Notify,
+}
+
+struct State {
+    limit: f64,
+    in_flight: usize,
+    /// Running average and best average latency of successful calls, in
+    /// seconds.
+    latency: Option<f64>,
+    baseline: Option<f64>,
+    calls_since_cut: usize,
+}
+
+impl AdaptiveConcurrency {
+    /// Allows up to `max` calls at once, fewer while the node is struggling.
+    pub fn new(max: usize) -> Self {
+        let max = max.max(1);
+        Self {
+            max,
+            min: 1,
+            state: Mutex::new(State {
+                limit: max as f64,
+                in_flight: 0,
+                latency: None,
+                baseline: None,
+                calls_since_cut: 0,
+            }),
+            released: Notify::new(),
+        }
+    }
+
+    /// Never goes below `min` calls at once.
+    pub fn min(mut self, min: usize) -> Self {
+        self.min = min.clamp(1, self.max);
+        self
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Calls currently allowed at once.
+    pub fn limit(&self) -> usize {
+        self.lock().limit as usize
+    }
+
+    /// Runs `call` once fewer than `limit` calls are in flight, and adjusts
+    /// the limit by how it went.
+    pub async fn run<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        let _permit = self.acquire().await;
+        let started = Instant::now();
+        let result = call.await;
+        self.record(&result, started.elapsed());
+        result
+    }
+
+    async fn acquire(&self) -> Permit<'_> {
+        loop {
+            let released = self.released.notified();
+            {
+                let mut state = self.lock();
+                if state.in_flight < state.limit as usize {
+                    state.in_flight += 1;
+                    return Permit(self);
+                }
+            }
+            released.await;
+        }
+    }
+
+    fn record<T>(&self, result: &Result<T>, latency: Duration) {
+        let mut state = self.lock();
+        state.calls_since_cut += 1;
+        let may_cut = state.calls_since_cut >= state.limit as usize;
+        match result {
+            Err(e) if is_overloaded(e) => {
+                if may_cut {
+                    self.cut(&mut state, 0.5);
+                }
+            }
+            Err(_) => {}
+            Ok(_) => {
+                let sample = latency.as_secs_f64();
+                let average = state.latency.map_or(sample, |average| {
+                    average + (sample - average) * LATENCY_SMOOTHING
+                });
+                let baseline = state.baseline.map_or(average, |baseline| {
+                    average.min(baseline + (average - baseline) * BASELINE_DRIFT)
+                });
+                state.latency = Some(average);
+                state.baseline = Some(baseline);
+                if average > baseline * LATENCY_TOLERANCE {
+                    if may_cut {
+                        self.cut(&mut state, 0.9);
+                    }
+                } else {
+                    state.limit = (state.limit + 1.0 / state.limit).min(self.max as f64);
+                }
+            }
+        }
+    }
+
+    fn cut(&self, state: &mut State, factor: f64) {
+        state.limit = (state.limit * factor).max(self.min as f64);
+        state.calls_since_cut = 0;
+        debug!("Concurrency limit lowered to {}", state.limit as usize);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A slot taken by a call in flight, given back when dropped.
+struct Permit<'a>(&'a AdaptiveConcurrency);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.0.lock().in_flight -= 1;
+        self.0.released.notify_waiters();
+    }
+}
+
+/// Whether `error` means the node wants fewer requests.
+fn is_overloaded(error: &VernachainError) -> bool {
+    match error {
+        VernachainError::TimeoutError => true,
+        VernachainError::HttpError(e) if e.is_timeout() => true,
+        _ => matches!(error.status_code(), Some(429 | 503)),
+    }
+}
@@ -0,0 +1,164 @@
+//! Client configuration from a TOML file or the environment.
+//!
+//! A file holds the same settings as the environment variables:
+//!
+//! ```toml
+//! network = "testnet"          # or url = "https://node.example.com"
+//! api_key = "..."
+//! timeout_secs = 30
+//! connect_timeout_secs = 5
+//!
+//! [retry]
+//! max_retries = 3
+//! initial_backoff_ms = 200
+//! max_backoff_ms = 5000
+//! ```
+//!
+//! `url` takes precedence over `network`. Settings left out keep the
+//! builder's defaults.
+
+use crate::{
+    client::VernachainClientBuilder,
+    error::{Result, VernachainError},
+    network::Network,
+    retry::RetryPolicy,
+};
+use serde::Deserialize;
+use std::{env, fmt, path::Path, str::FromStr, time::Duration};
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    pub url: Option<String>,
+    pub ws_url: Option<String>,
+    pub api_key: Option<String>,
+    /// `mainnet`, `testnet` or `devnet`.
+    pub network: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    pub retry: Option<RetryConfig>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_retries: Option<u32>,
+    pub initial_backoff_ms: Option<u64>,
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl ClientConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        toml::from_str(&contents)
+            .map_err(|e| VernachainError::ConfigurationError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Reads `VERNACHAIN_URL`, `VERNACHAIN_WS_URL`, `VERNACHAIN_API_KEY`,
+    /// `VERNACHAIN_NETWORK`, `VERNACHAIN_TIMEOUT_SECS`,
+    /// `VERNACHAIN_CONNECT_TIMEOUT_SECS`, `VERNACHAIN_MAX_RETRIES`,
+    /// `VERNACHAIN_RETRY_INITIAL_BACKOFF_MS` and
+    /// `VERNACHAIN_RETRY_MAX_BACKOFF_MS`. Unset variables are left out.
+    pub fn from_env() -> Result<Self> {
+        let retry = RetryConfig {
+            max_retries: parse_var("VERNACHAIN_MAX_RETRIES")?,
+            initial_backoff_ms: parse_var("VERNACHAIN_RETRY_INITIAL_BACKOFF_MS")?,
+            max_backoff_ms: parse_var("VERNACHAIN_RETRY_MAX_BACKOFF_MS")?,
+        };
+        let retry_set = retry.max_retries.is_some()
+            || retry.initial_backoff_ms.is_some()
+            || retry.max_backoff_ms.is_some();
+        Ok(Self {
+            url: var("VERNACHAIN_URL")?,
+            ws_url: var("VERNACHAIN_WS_URL")?,
+            api_key: var("VERNACHAIN_API_KEY")?,
+            network: var("VERNACHAIN_NETWORK")?,
+            timeout_secs: parse_var("VERNACHAIN_TIMEOUT_SECS")?,
+            connect_timeout_secs: parse_var("VERNACHAIN_CONNECT_TIMEOUT_SECS")?,
+            retry: retry_set.then_some(retry),
+        })
+    }
+
+    /// A builder with these settings applied, failing with
+    /// `ConfigurationError` if neither a URL nor a known network is set.
+    pub fn into_builder(self) -> Result<VernachainClientBuilder> {
+        let url = match (self.url, &self.network) {
+            (Some(url), _) => url,
+            (None, Some(network)) => Network::from_str(network)?.url().to_string(),
+            (None, None) => {
+                return Err(VernachainError::ConfigurationError(
+                    "no node URL or network configured".to_string(),
+                ))
+            }
+        };
+
+        let mut builder = VernachainClientBuilder::new(&url);
+        if let Some(ws_url) = self.ws_url {
+            builder = builder.with_ws_url(ws_url);
+        }
+        if let Some(api_key) = self.api_key {
+            builder = builder.api_key(api_key);
+        }
+        if let Some(secs) = self.timeout_secs {
+            builder = builder.timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            builder = builder.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(retry) = self.retry {
+            builder = builder.retry_policy(retry.policy());
+        }
+        Ok(builder)
+    }
+}
+
+impl RetryConfig {
+    fn policy(&self) -> RetryPolicy {
+        let mut policy = match self.max_retries {
+            Some(retries) => RetryPolicy::new(retries),
+            None => RetryPolicy::default(),
+        };
+        if let Some(ms) = self.initial_backoff_ms {
+            policy = policy.initial_backoff(Duration::from_millis(ms));
+        }
+        if let Some(ms) = self.max_backoff_ms {
+            policy = policy.max_backoff(Duration::from_millis(ms));
+        }
+        policy
+    }
+}
+
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("url", &self.url)
+            .field("ws_url", &self.ws_url)
+            .field("api_key", &self.api_key.as_ref().map(|_| ".."))
+            .field("network", &self.network)
+            .field("timeout_secs", &self.timeout_secs)
+            .field("connect_timeout_secs", &self.connect_timeout_secs)
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+fn var(name: &str) -> Result<Option<String>> {
+    match env::var(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(VernachainError::ConfigurationError(format!(
+            "{} is not valid UTF-8",
+            name
+        ))),
+    }
+}
+
+fn parse_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    match var(name)? {
+        Some(value) => value.parse().map(Some).map_err(|_| {
+            VernachainError::ConfigurationError(format!("{} is not a valid number", name))
+        }),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,117 @@
+//! Connectivity as seen by a client's own calls, for status displays and
+//! alerting.
+//!
+//! The client doesn't probe the node; it reports what its requests and
+//! sockets run into. The node counts as reachable whenever it answers, even
+//! with an error status, and as unreachable when a connection can't be made
+//! or a request times out.
+
+use crate::{
+    error::{Result, VernachainError},
+    subscription::{Subscription, SubscriptionEvent},
+};
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Mutex,
+};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The node answered, for the first time or after being unreachable.
+    Connected,
+    /// A call couldn't reach the node.
+    Disconnected { reason: String },
+    /// A call is being retried after the node was unreachable; `attempt`
+    /// counts the retries of that call.
+    Reconnecting { attempt: u32 },
+    /// The node turned a call away for exceeding its rate limit.
+    RateLimited,
+    /// Calls moved to another node, for transports that name the node they
+    /// use in `Transport::endpoint`.
+    Failover { endpoint: String },
+}
+
+const UNKNOWN: u8 = 0;
+const CONNECTED: u8 = 1;
+const DISCONNECTED: u8 = 2;
+
+/// Turns call outcomes into `ConnectionEvent`s, reporting `Connected` and
+/// `Disconnected` only when the state changes.
+pub(crate) struct ConnectionMonitor {
+    state: AtomicU8,
+    endpoint: Mutex<Option<String>>,
+    events: broadcast::Sender<SubscriptionEvent<ConnectionEvent>>,
+}
+
+impl ConnectionMonitor {
+    pub(crate) fn new(buffer: usize) -> Self {
+        Self {
+            state: AtomicU8::new(UNKNOWN),
+            endpoint: Mutex::new(None),
+            events: broadcast::channel(buffer.max(1)).0,
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> Subscription<ConnectionEvent> {
+        Subscription::new(self.events.subscribe())
+    }
+
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.state.load(Ordering::Relaxed) == DISCONNECTED
+    }
+
+    pub(crate) fn observe<T>(&self, result: &Result<T>) {
+        match result {
+            Ok(_) => self.reachable(),
+            Err(VernachainError::RateLimitError) => {
+                self.reachable();
+                self.emit(ConnectionEvent::RateLimited);
+            }
+            Err(VernachainError::Api { .. } | VernachainError::AuthenticationError) => {
+                self.reachable()
+            }
+            Err(VernachainError::HttpError(e))
+                if e.is_connect() || e.is_timeout() || e.is_request() =>
+            {
+                self.unreachable(e.to_string())
+            }
+            Err(e @ (VernachainError::WebSocketError(_) | VernachainError::TimeoutError)) => {
+                self.unreachable(e.to_string())
+            }
+            Err(_) => {}
+        }
+    }
+
+    pub(crate) fn reconnecting(&self, attempt: u32) {
+        self.emit(ConnectionEvent::Reconnecting { attempt });
+    }
+
+    /// Records the node the transport sent the last call to.
+    pub(crate) fn endpoint(&self, endpoint: String) {
+        let mut current = self.endpoint.lock().unwrap_or_else(|e| e.into_inner());
+        if current.as_ref().is_some_and(|current| *current != endpoint) {
+            self.emit(ConnectionEvent::Failover {
+                endpoint: endpoint.clone(),
+            });
+        }
+        *current = Some(endpoint);
+    }
+
+    fn reachable(&self) {
+        if self.state.swap(CONNECTED, Ordering::Relaxed) != CONNECTED {
+            self.emit(ConnectionEvent::Connected);
+        }
+    }
+
+    fn unreachable(&self, reason: String) {
+        if self.state.swap(DISCONNECTED, Ordering::Relaxed) != DISCONNECTED {
+            self.emit(ConnectionEvent::Disconnected { reason });
+        }
+    }
+
+    fn emit(&self, event: ConnectionEvent) {
+        // Nobody may be listening.
+        let _ = self.events.send(SubscriptionEvent::Message(event));
+    }
+}
@@ -0,0 +1,244 @@
+//! Persisted stream positions, so a restarted service resumes a block or
+//! event stream where it left off.
+//!
+//! A consumer saves a `Cursor` for each item once it has handled it, and
+//! passes the same store and stream name to `resume_blocks` or
+//! `resume_events` on start. The stream then begins right after the saved
+//! position: nothing handled is delivered again and nothing is skipped. With
+//! `SqliteCursorStore` the cursor can be saved in the transaction that
+//! records the item's effects, so a crash between the two can't happen.
+
+use crate::{
+    error::{Result, VernachainError},
+    types::{Block, ChainEvent, Log},
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use tokio::sync::Mutex;
+
+/// The last item a consumer has handled: a whole block, or a log within one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub block_number: u64,
+    /// `None` when the whole block has been handled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_index: Option<u64>,
+}
+
+impl Cursor {
+    /// Position after all of block `block_number`.
+    pub fn block(block_number: u64) -> Self {
+        Self {
+            block_number,
+            log_index: None,
+        }
+    }
+
+    /// Position once `event` from `resume_blocks` has been handled: its block,
+    /// or for a reorg the last added block. `None` for a reorg that only
+    /// dropped block 0.
+    pub fn after(event: &ChainEvent) -> Option<Self> {
+        match event {
+            ChainEvent::NewBlock(block) => Some(block.into()),
+            ChainEvent::Reorg { dropped, added, .. } => match added.last() {
+                Some(block) => Some(block.into()),
+                None => dropped.first()?.number.checked_sub(1).map(Cursor::block),
+            },
+        }
+    }
+
+    /// First block a resumed stream has to look at.
+    pub(crate) fn resume_block(&self) -> u64 {
+        match self.log_index {
+            Some(_) => self.block_number,
+            None => self.block_number + 1,
+        }
+    }
+
+    /// Whether `log` is at or before this position.
+    pub(crate) fn covers(&self, log: &Log) -> bool {
+        match self.log_index {
+            Some(index) => {
+                log.block_number < self.block_number
+                    || (log.block_number == self.block_number && log.log_index <= index)
+            }
+            None => log.block_number <= self.block_number,
+        }
+    }
+}
+
+impl From<&Block> for Cursor {
+    fn from(block: &Block) -> Self {
+        Cursor::block(block.number)
+    }
+}
+
+/// Position after `log`, or before it for a `removed` log, so the log is
+/// delivered again when its block's replacement is.
+impl From<&Log> for Cursor {
+    fn from(log: &Log) -> Self {
+        match (log.removed, log.log_index.checked_sub(1)) {
+            (false, _) => Self {
+                block_number: log.block_number,
+                log_index: Some(log.log_index),
+            },
+            (true, Some(previous)) => Self {
+                block_number: log.block_number,
+                log_index: Some(previous),
+            },
+            (true, None) => Cursor::block(log.block_number.saturating_sub(1)),
+        }
+    }
+}
+
+/// Where stream positions are kept, keyed by a stream name the consumer
+/// chooses.
+#[async_trait]
+pub trait CursorStore: Send + Sync {
+    async fn load(&self, stream: &str) -> Result<Option<Cursor>>;
+
+    async fn save(&self, stream: &str, cursor: Cursor) -> Result<()>;
+}
+
+/// Keeps every stream's cursor in one JSON file, rewritten atomically on each
+/// save.
+pub struct FileCursorStore {
+    path: PathBuf,
+    /// Serializes saves, which read the whole file before writing it back.
+    lock: Mutex<()>,
+}
+
+impl FileCursorStore {
+    /// Uses the file at `path`, which is created on the first save.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    async fn read(&self) -> Result<BTreeMap<String, Cursor>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+                VernachainError::ConfigurationError(format!(
+                    "invalid cursor file {}: {}",
+                    self.path.display(),
+                    e
+                ))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl CursorStore for FileCursorStore {
+    async fn load(&self, stream: &str) -> Result<Option<Cursor>> {
+        let _guard = self.lock.lock().await;
+        Ok(self.read().await?.get(stream).copied())
+    }
+
+    async fn save(&self, stream: &str, cursor: Cursor) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        let mut cursors = self.read().await?;
+        cursors.insert(stream.to_string(), cursor);
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut temporary = self.path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        tokio::fs::write(&temporary, serde_json::to_string_pretty(&cursors)?).await?;
+        tokio::fs::rename(&temporary, &self.path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "indexer")]
+pub use sqlite::SqliteCursorStore;
+
+#[cfg(feature = "indexer")]
+mod sqlite {
+    use super::{Cursor, CursorStore};
+    use crate::error::Result;
+    use async_trait::async_trait;
+    use sqlx::{
+        sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+        Sqlite, SqlitePool,
+    };
+    use std::str::FromStr;
+
+    const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS stream_cursors (
+        stream TEXT PRIMARY KEY,
+        block_number INTEGER NOT NULL,
+        log_index INTEGER
+    )";
+
+    /// Keeps cursors in a `stream_cursors` table, which can share a database
+    /// with the consumer's own data.
+    #[derive(Clone)]
+    pub struct SqliteCursorStore {
+        pool: SqlitePool,
+    }
+
+    impl SqliteCursorStore {
+        /// Opens (creating if needed) the database at `database_url`, e.g.
+        /// `sqlite://cursors.db`.
+        pub async fn open(database_url: &str) -> Result<Self> {
+            let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+            Self::new(SqlitePoolOptions::new().connect_with(options).await?).await
+        }
+
+        /// Uses an existing pool, creating the cursor table if needed.
+        pub async fn new(pool: SqlitePool) -> Result<Self> {
+            sqlx::query(SCHEMA).execute(&pool).await?;
+            Ok(Self { pool })
+        }
+
+        /// Saves `cursor` as part of `tx`, so it is committed together with
+        /// whatever else the transaction writes for the item.
+        pub async fn save_in(
+            tx: &mut sqlx::Transaction<'_, Sqlite>,
+            stream: &str,
+            cursor: Cursor,
+        ) -> Result<()> {
+            sqlx::query(
+                "INSERT OR REPLACE INTO stream_cursors (stream, block_number, log_index)
+                 VALUES (?, ?, ?)",
+            )
+            .bind(stream)
+            .bind(cursor.block_number as i64)
+            .bind(cursor.log_index.map(|i| i as i64))
+            .execute(&mut **tx)
+            .await?;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl CursorStore for SqliteCursorStore {
+        async fn load(&self, stream: &str) -> Result<Option<Cursor>> {
+            let row: Option<(i64, Option<i64>)> = sqlx::query_as(
+                "SELECT block_number, log_index FROM stream_cursors WHERE stream = ?",
+            )
+            .bind(stream)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(row.map(|(block_number, log_index)| Cursor {
+                block_number: block_number as u64,
+                log_index: log_index.map(|i| i as u64),
+            }))
+        }
+
+        async fn save(&self, stream: &str, cursor: Cursor) -> Result<()> {
+            let mut tx = self.pool.begin().await?;
+            Self::save_in(&mut tx, stream, cursor).await?;
+            tx.commit().await?;
+            Ok(())
+        }
+    }
+}
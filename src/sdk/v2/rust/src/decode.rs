@@ -0,0 +1,58 @@
+//! Decoding of node responses.
+//!
+//! Every struct decoded from a node response keeps the fields it doesn't
+//! model in `extra`, so older SDKs tolerate fields added by node upgrades and
+//! a renamed field can still be found. Contract ABIs and tagged enum variants
+//! have no `extra` and drop unknown fields. Strict decoding rejects any
+//! response that carries such fields, for deployments that would rather fail
+//! early than drift silently from the API.
+
+use crate::{error::Result, types::ExtraFields};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use std::cell::Cell;
+
+thread_local! {
+    static STRICT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Restores the previous mode when dropped, even if decoding panics.
+struct ModeGuard(bool);
+
+impl ModeGuard {
+    fn set(strict: bool) -> Self {
+        Self(STRICT.with(|mode| mode.replace(strict)))
+    }
+}
+
+impl Drop for ModeGuard {
+    fn drop(&mut self) {
+        STRICT.with(|mode| mode.set(self.0));
+    }
+}
+
+pub(crate) fn from_value<T: DeserializeOwned>(value: serde_json::Value, strict: bool) -> Result<T> {
+    let _mode = ModeGuard::set(strict);
+    Ok(serde_json::from_value(value)?)
+}
+
+pub(crate) fn from_str<T: DeserializeOwned>(text: &str, strict: bool) -> Result<T> {
+    let _mode = ModeGuard::set(strict);
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Collects a response type's unmodeled fields, failing in strict mode if
+/// there are any.
+pub(crate) fn capture_extra<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<ExtraFields, D::Error> {
+    let extra = ExtraFields::deserialize(deserializer)?;
+    if !extra.is_empty() && STRICT.with(Cell::get) {
+        let mut names: Vec<&str> = extra.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        return Err(serde::de::Error::custom(format!(
+            "unknown fields: {}",
+            names.join(", ")
+        )));
+    }
+    Ok(extra)
+}
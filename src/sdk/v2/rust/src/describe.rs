@@ -0,0 +1,185 @@
+//! Human-readable summaries of transactions, for wallet confirmation screens
+//! and activity lists.
+//!
+//! A plain transfer reads as "Transfer 5 VERNA to 0x3f2A9c11…". A contract call
+//! is decoded with the contract's ABI when one is known, reading as
+//! "Call approve(spender: 0x81bD04aa…, amount: 5) on Token 0x3f2A9c11…", and
+//! otherwise shows the function name and raw arguments.
+
+pub use crate::amount::NATIVE_SYMBOL;
+
+use crate::{
+    abi::{ContractAbi, ContractCallData, DecodedArg},
+    address,
+    types::Transaction,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::HashMap, fmt};
+
+/// Addresses longer than this are shortened in summary text.
+const SHORT_ADDRESS_LEN: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TransactionAction {
+    Transfer {
+        recipient: String,
+        amount: f64,
+    },
+    /// A call whose arguments were named from the contract's ABI.
+    Call {
+        contract: String,
+        contract_name: Option<String>,
+        function: String,
+        args: Vec<DecodedArg>,
+        /// Native currency sent along with the call.
+        amount: f64,
+    },
+    /// A call to a contract with no known ABI, or one that didn't match it.
+    UnknownCall {
+        contract: String,
+        function: String,
+        args: Vec<Value>,
+        amount: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TransactionSummary {
+    pub hash: String,
+    pub sender: String,
+    pub status: String,
+    pub action: TransactionAction,
+}
+
+/// Summarizes `tx`, decoding contract calls with `known_abis`, which maps
+/// contract addresses to their interfaces. Addresses match ignoring case.
+pub fn describe_transaction(
+    tx: &Transaction,
+    known_abis: &HashMap<String, ContractAbi>,
+) -> TransactionSummary {
+    let call = tx.data.as_ref().and_then(ContractCallData::from_data);
+    let action = match call {
+        None => TransactionAction::Transfer {
+            recipient: tx.recipient.clone(),
+            amount: tx.amount,
+        },
+        Some(call) => {
+            let abi = known_abis
+                .iter()
+                .find(|(address, _)| address.eq_ignore_ascii_case(&tx.recipient))
+                .map(|(_, abi)| abi);
+            match abi.and_then(|abi| Some((abi, abi.decode_call(&call).ok()?))) {
+                Some((abi, decoded)) => TransactionAction::Call {
+                    contract: tx.recipient.clone(),
+                    contract_name: abi.contract_name.clone(),
+                    function: decoded.function,
+                    args: decoded.args,
+                    amount: tx.amount,
+                },
+                None => TransactionAction::UnknownCall {
+                    contract: tx.recipient.clone(),
+                    function: call.function,
+                    args: call.args,
+                    amount: tx.amount,
+                },
+            }
+        }
+    };
+    TransactionSummary {
+        hash: tx.hash.clone(),
+        sender: tx.sender.clone(),
+        status: tx.status.clone(),
+        action,
+    }
+}
+
+impl fmt::Display for TransactionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.action.fmt(f)
+    }
+}
+
+impl fmt::Display for TransactionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionAction::Transfer { recipient, amount } => write!(
+                f,
+                "Transfer {} {} to {}",
+                amount,
+                NATIVE_SYMBOL,
+                short_address(recipient)
+            ),
+            TransactionAction::Call {
+                contract,
+                contract_name,
+                function,
+                args,
+                amount,
+            } => {
+                let args: Vec<String> = args
+                    .iter()
+                    .map(|arg| {
+                        format!(
+                            "{}: {}",
+                            arg.name,
+                            format_value(&arg.value, &arg.param_type)
+                        )
+                    })
+                    .collect();
+                write!(f, "Call {}({}) on ", function, args.join(", "))?;
+                if let Some(name) = contract_name {
+                    write!(f, "{} ", name)?;
+                }
+                f.write_str(&short_address(contract))?;
+                write_attached(f, *amount)
+            }
+            TransactionAction::UnknownCall {
+                contract,
+                function,
+                args,
+                amount,
+            } => {
+                let args: Vec<String> = args.iter().map(|arg| format_value(arg, "")).collect();
+                write!(
+                    f,
+                    "Call {}({}) on {}",
+                    function,
+                    args.join(", "),
+                    short_address(contract)
+                )?;
+                write_attached(f, *amount)
+            }
+        }
+    }
+}
+
+fn write_attached(f: &mut fmt::Formatter<'_>, amount: f64) -> fmt::Result {
+    if amount > 0.0 {
+        write!(f, " with {} {}", amount, NATIVE_SYMBOL)?;
+    }
+    Ok(())
+}
+
+fn format_value(value: &Value, param_type: &str) -> String {
+    match value {
+        Value::String(text) if param_type == "address" => short_address(text),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The address's first characters, checksummed, followed by an ellipsis.
+fn short_address(address: &str) -> String {
+    let address = &address::display(address);
+    let digits = address.strip_prefix("0x").unwrap_or(address);
+    if address.chars().count() <= SHORT_ADDRESS_LEN + 2 || !digits.is_ascii() {
+        return address.to_string();
+    }
+    let prefix_len = address.len() - digits.len();
+    format!(
+        "{}…",
+        &address[..prefix_len + SHORT_ADDRESS_LEN.min(digits.len())]
+    )
+}
@@ -0,0 +1,315 @@
+//! Throwaway local networks for end-to-end tests.
+//!
+//! `Devnet` starts a single-node Vernachain network in Docker, or from a node
+//! binary, on a free local port, waits until it answers, and funds a few
+//! fresh accounts from its faucet. The node is torn down when the `Devnet` is
+//! dropped, so each test can have its own:
+//!
+//! ```no_run
+//! # async fn example() -> vernachain_sdk::Result<()> {
+//! use vernachain_sdk::devnet::Devnet;
+//!
+//! let devnet = Devnet::builder().accounts(2).start().await?;
+//! let alice = &devnet.accounts()[0];
+//! let balance = devnet.client().get_balance(&alice.address()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    keys::{generate_keypair, Keypair},
+    poll::{poll_until, CancellationToken},
+};
+use std::{
+    net::TcpListener,
+    path::PathBuf,
+    process::{Command as StdCommand, Stdio},
+    time::Duration,
+};
+use tokio::process::{Child, Command};
+
+const DEFAULT_IMAGE: &str = "vernachain/node:latest";
+/// The port the node image serves its API on.
+const CONTAINER_API_PORT: u16 = 8000;
+const DEFAULT_ACCOUNTS: usize = 3;
+const DEFAULT_FUNDING: f64 = 1_000.0;
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+enum Launcher {
+    Docker { image: String },
+    Binary { path: PathBuf, args: Vec<String> },
+}
+
+pub struct DevnetBuilder {
+    launcher: Launcher,
+    accounts: usize,
+    funding: f64,
+    startup_timeout: Duration,
+}
+
+impl DevnetBuilder {
+    /// Runs the node from this Docker image instead of the default one.
+    pub fn docker_image(mut self, image: impl Into<String>) -> Self {
+        self.launcher = Launcher::Docker {
+            image: image.into(),
+        };
+        self
+    }
+
+    /// Runs a local node binary instead of Docker. It is started with `args`
+    /// followed by `--api-port <port>`.
+    pub fn binary(mut self, path: impl Into<PathBuf>, args: &[&str]) -> Self {
+        self.launcher = Launcher::Binary {
+            path: path.into(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        };
+        self
+    }
+
+    /// How many funded accounts to create.
+    pub fn accounts(mut self, accounts: usize) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// How much each account is funded with.
+    pub fn funding(mut self, amount: f64) -> Self {
+        self.funding = amount;
+        self
+    }
+
+    /// How long to wait for the node to answer, including pulling its image.
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Starts the node, waits for it and funds the accounts. A node that
+    /// started but never became ready is torn down before returning.
+    pub async fn start(self) -> Result<Devnet> {
+        let port = free_port()?;
+        let node = match &self.launcher {
+            Launcher::Docker { image } => start_container(image, port).await?,
+            Launcher::Binary { path, args } => {
+                let child = Command::new(path)
+                    .args(args)
+                    .arg("--api-port")
+                    .arg(port.to_string())
+                    .stdout(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn()?;
+                Node::Process(child)
+            }
+        };
+
+        let url = format!("http://127.0.0.1:{}", port);
+        let mut devnet = Devnet {
+            node,
+            client: VernachainClient::builder(&url).build()?,
+            url,
+            accounts: Vec::new(),
+        };
+        devnet.wait_until_ready(self.startup_timeout).await?;
+        for _ in 0..self.accounts {
+            let keypair = generate_keypair();
+            devnet
+                .client
+                .request_faucet_funds(&keypair.address(), self.funding)
+                .await?;
+            devnet.accounts.push(keypair);
+        }
+        Ok(devnet)
+    }
+}
+
+enum Node {
+    Container(String),
+    Process(Child),
+}
+
+/// A running local network, stopped on drop.
+pub struct Devnet {
+    node: Node,
+    client: VernachainClient,
+    url: String,
+    accounts: Vec<Keypair>,
+}
+
+impl Devnet {
+    pub fn builder() -> DevnetBuilder {
+        DevnetBuilder {
+            launcher: Launcher::Docker {
+                image: DEFAULT_IMAGE.to_string(),
+            },
+            accounts: DEFAULT_ACCOUNTS,
+            funding: DEFAULT_FUNDING,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+        }
+    }
+
+    /// Starts a devnet with the default settings.
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn client(&self) -> &VernachainClient {
+        &self.client
+    }
+
+    /// The funded accounts, with their keys.
+    pub fn accounts(&self) -> &[Keypair] {
+        &self.accounts
+    }
+
+    async fn wait_until_ready(&self, timeout: Duration) -> Result<()> {
+        let ready = poll_until(
+            READY_POLL_INTERVAL,
+            timeout,
+            &CancellationToken::new(),
+            || async { Ok(self.client.get_node_status().await.ok().map(|_| ())) },
+        )
+        .await;
+        ready.map_err(|e| match e {
+            VernachainError::TimeoutError => VernachainError::NetworkError(format!(
+                "devnet node did not become ready within {:?}",
+                timeout
+            )),
+            e => e,
+        })
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        match &mut self.node {
+            Node::Container(id) => {
+                let _ = StdCommand::new("docker")
+                    .args(["rm", "--force", id])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+            }
+            Node::Process(child) => {
+                let _ = child.start_kill();
+            }
+        }
+    }
+}
+
+async fn start_container(image: &str, port: u16) -> Result<Node> {
+    let output = Command::new("docker")
+        .args([
+            "run",
+            "--detach",
+            "--rm",
+            "--env",
+            "NETWORK=devnet",
+            "--publish",
+        ])
+        .arg(format!("127.0.0.1:{}:{}", port, CONTAINER_API_PORT))
+        .arg(image)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(VernachainError::ConfigurationError(format!(
+            "docker run {} failed: {}",
+            image,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Node::Container(id))
+}
+
+/// A port that was free a moment ago. Another process could take it before
+/// the node binds it, which is unlikely enough for tests.
+fn free_port() -> Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    /// A shell script standing in for a node that records its pid and never
+    /// answers.
+    fn silent_node(pid_file: &std::path::Path) -> String {
+        format!("echo $$ > {}; exec sleep 60", pid_file.display())
+    }
+
+    async fn wait_for_pid(pid_file: &std::path::Path) -> u32 {
+        for _ in 0..50 {
+            if let Ok(pid) = std::fs::read_to_string(pid_file) {
+                if let Ok(pid) = pid.trim().parse() {
+                    return pid;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("node never wrote {}", pid_file.display());
+    }
+
+    /// Whether `pid` is still running; a killed child that hasn't been
+    /// reaped yet counts as stopped.
+    fn running(pid: u32) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .is_ok_and(|stat| !stat.contains(") Z "))
+    }
+
+    async fn assert_stopped(pid: u32) {
+        for _ in 0..100 {
+            if !running(pid) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("node {} is still running", pid);
+    }
+
+    #[tokio::test]
+    async fn stops_a_node_that_never_becomes_ready() {
+        let pid_file = std::env::temp_dir().join(format!("devnet-{}-unready", std::process::id()));
+        let _ = std::fs::remove_file(&pid_file);
+
+        let result = Devnet::builder()
+            .binary("sh", &["-c", &silent_node(&pid_file)])
+            .accounts(0)
+            .startup_timeout(Duration::from_millis(500))
+            .start()
+            .await;
+        assert!(matches!(result, Err(VernachainError::NetworkError(_))));
+        assert_stopped(wait_for_pid(&pid_file).await).await;
+        let _ = std::fs::remove_file(&pid_file);
+    }
+
+    #[tokio::test]
+    async fn stops_the_node_on_drop() {
+        let pid_file = std::env::temp_dir().join(format!("devnet-{}-drop", std::process::id()));
+        let _ = std::fs::remove_file(&pid_file);
+        let child = Command::new("sh")
+            .args(["-c", &silent_node(&pid_file)])
+            .kill_on_drop(true)
+            .spawn()
+            .unwrap();
+        let url = "http://127.0.0.1:1".to_string();
+        let devnet = Devnet {
+            node: Node::Process(child),
+            client: VernachainClient::builder(&url).build().unwrap(),
+            url,
+            accounts: Vec::new(),
+        };
+        let pid = wait_for_pid(&pid_file).await;
+        assert!(running(pid));
+
+        drop(devnet);
+        assert_stopped(pid).await;
+        let _ = std::fs::remove_file(&pid_file);
+    }
+}
@@ -0,0 +1,53 @@
+//! Binary response encodings for bulk reads.
+//!
+//! Nodes that support it answer block, header and log fetches in CBOR or
+//! MessagePack when asked to via `Accept`, which is smaller and quicker to
+//! parse than JSON. Responses are decoded by their `Content-Type`, so nodes
+//! that only speak JSON keep working.
+
+use crate::error::{Result, VernachainError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryEncoding {
+    Cbor,
+    MessagePack,
+}
+
+impl BinaryEncoding {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            BinaryEncoding::Cbor => "application/cbor",
+            BinaryEncoding::MessagePack => "application/msgpack",
+        }
+    }
+
+    /// `Accept` header preferring this encoding over JSON.
+    pub(crate) fn accept(self) -> String {
+        format!("{}, application/json;q=0.5", self.content_type())
+    }
+
+    /// The encoding a response was sent in, or `None` for JSON and anything
+    /// else this SDK does not decode as binary.
+    pub(crate) fn from_content_type(content_type: &str) -> Option<Self> {
+        let essence = content_type.split(';').next().unwrap_or("").trim();
+        match essence.to_ascii_lowercase().as_str() {
+            "application/cbor" => Some(BinaryEncoding::Cbor),
+            "application/msgpack" | "application/x-msgpack" => Some(BinaryEncoding::MessagePack),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn decode(self, bytes: &[u8]) -> Result<serde_json::Value> {
+        let decoded = match self {
+            BinaryEncoding::Cbor => ciborium::from_reader(bytes).map_err(|e| e.to_string()),
+            BinaryEncoding::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| e.to_string()),
+        };
+        decoded.map_err(|e| {
+            VernachainError::UnexpectedResponseError(format!(
+                "invalid {} body: {}",
+                self.content_type(),
+                e
+            ))
+        })
+    }
+}
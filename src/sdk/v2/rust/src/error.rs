@@ -6,11 +6,17 @@ pub enum VernachainError {
     HttpError(#[from] reqwest::Error),
 
     #[error("WebSocket error: {0}")]
-    WebSocketError(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocketError(Box<tokio_tungstenite::tungstenite::Error>),
 
     #[error("JSON serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigurationError(String),
+
     #[error("Invalid API key")]
     AuthenticationError,
 
@@ -20,15 +26,42 @@ pub enum VernachainError {
     #[error("Network error: {0}")]
     NetworkError(String),
 
+    #[error("Node returned {status}: {message} (request {request_id})")]
+    Api {
+        status: u16,
+        message: String,
+        /// The `X-Request-Id` of the failed call, for matching node-side logs.
+        request_id: String,
+    },
+
     #[error("Unexpected response format: {0}")]
     UnexpectedResponseError(String),
 
+    #[error("Node does not support {0}")]
+    UnsupportedFeature(String),
+
+    #[error("Chain id mismatch: expected {expected}, node reported {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+
+    #[error("Transfer {transfer_id} failed with status {status}")]
+    TransferFailed { transfer_id: String, status: String },
+
+    #[error("Transaction {tx_hash} failed with status {status}")]
+    TransactionFailed { tx_hash: String, status: String },
+
+    #[cfg(feature = "indexer")]
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+
     #[error("WebSocket connection closed")]
     WebSocketClosed,
 
     #[error("Operation timeout")]
     TimeoutError,
 
+    #[error("Operation cancelled")]
+    Cancelled,
+
     #[error("Rate limit exceeded")]
     RateLimitError,
 
@@ -36,4 +69,72 @@ pub enum VernachainError {
     InternalError(String),
 }
 
-pub type Result<T> = std::result::Result<T, VernachainError>; 
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, VernachainError>;
+
+/// Whether a failed call is worth trying again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The node was unreachable, overloaded or too slow; the same call may
+    /// succeed later.
+    Transient,
+    /// The call would fail the same way again, e.g. it was invalid, not
+    /// authorized or the transaction itself failed.
+    Permanent,
+}
+
+impl VernachainError {
+    pub fn category(&self) -> ErrorCategory {
+        let transient = match self {
+            VernachainError::RateLimitError
+            | VernachainError::TimeoutError
+            | VernachainError::NetworkError(_)
+            | VernachainError::WebSocketError(_)
+            | VernachainError::WebSocketClosed => true,
+            VernachainError::Api { status, .. } => matches!(status, 500 | 502 | 503 | 504),
+            VernachainError::HttpError(e) => {
+                e.is_connect() || e.is_timeout() || e.is_request() || e.is_body()
+            }
+            _ => false,
+        };
+        if transient {
+            ErrorCategory::Transient
+        } else {
+            ErrorCategory::Permanent
+        }
+    }
+
+    /// Whether the error is `ErrorCategory::Transient`. Retrying a
+    /// non-idempotent call can still apply it twice; `RetryPolicy` only
+    /// retries those when the node can't have acted on them.
+    pub fn is_retryable(&self) -> bool {
+        self.category() == ErrorCategory::Transient
+    }
+
+    /// Whether the node rejected the call itself, with a 4xx other than a
+    /// rate limit, or the SDK did before sending it.
+    pub fn is_client_error(&self) -> bool {
+        match self {
+            VernachainError::ValidationError(_) => true,
+            _ => self
+                .status_code()
+                .is_some_and(|status| (400..500).contains(&status) && status != 429),
+        }
+    }
+
+    /// The HTTP status the node answered with, if the error came from one.
+    pub fn status_code(&self) -> Option<u16> {
+        match self {
+            VernachainError::Api { status, .. } => Some(*status),
+            VernachainError::AuthenticationError => Some(401),
+            VernachainError::RateLimitError => Some(429),
+            VernachainError::HttpError(e) => e.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for VernachainError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        VernachainError::WebSocketError(Box::new(error))
+    }
+}
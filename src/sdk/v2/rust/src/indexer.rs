@@ -0,0 +1,483 @@
+//! Local SQLite index of a single shard.
+//!
+//! `ChainIndexer` follows `subscribe_blocks`, backfills any gap between the
+//! database tip and the live head over REST, and rewrites the affected range on
+//! reorganizations. Rows keep the node's JSON alongside a few indexed columns,
+//! so queries return the same types as the client.
+
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    snapshot::SnapshotReader,
+    subscription::SubscriptionEvent,
+    types::*,
+};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    Sqlite, SqlitePool,
+};
+use std::{ops::RangeInclusive, str::FromStr};
+use tokio::io::AsyncRead;
+use tracing::{info, warn};
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS blocks (
+        shard_id INTEGER NOT NULL,
+        number INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (shard_id, number)
+    )",
+    "CREATE INDEX IF NOT EXISTS blocks_hash ON blocks (hash)",
+    "CREATE TABLE IF NOT EXISTS transactions (
+        shard_id INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        block_number INTEGER NOT NULL,
+        sender TEXT NOT NULL,
+        recipient TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (shard_id, hash)
+    )",
+    "CREATE INDEX IF NOT EXISTS transactions_sender ON transactions (sender)",
+    "CREATE INDEX IF NOT EXISTS transactions_recipient ON transactions (recipient)",
+    "CREATE TABLE IF NOT EXISTS logs (
+        shard_id INTEGER NOT NULL,
+        block_number INTEGER NOT NULL,
+        log_index INTEGER NOT NULL,
+        address TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (shard_id, block_number, log_index)
+    )",
+    "CREATE INDEX IF NOT EXISTS logs_address ON logs (address)",
+];
+
+pub struct ChainIndexer {
+    client: VernachainClient,
+    pool: SqlitePool,
+    shard_id: ShardId,
+    start_block: Option<u64>,
+}
+
+impl ChainIndexer {
+    /// Opens (creating if needed) the database at `database_url`, e.g.
+    /// `sqlite://index.db`, and prepares its schema.
+    pub async fn open(
+        client: VernachainClient,
+        database_url: &str,
+        shard_id: ShardId,
+    ) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(Self {
+            client,
+            pool,
+            shard_id,
+            start_block: None,
+        })
+    }
+
+    /// Block to start from when the database is empty. Without it, indexing
+    /// starts at the first block received from the subscription.
+    pub fn with_start_block(mut self, block_number: u64) -> Self {
+        self.start_block = Some(block_number);
+        self
+    }
+
+    /// Indexes the shard until the block subscription closes.
+    pub async fn run(&self) -> Result<()> {
+        self.reconcile().await?;
+        let mut events = self.client.subscribe_blocks(self.shard_id).await?;
+        loop {
+            match events.recv().await {
+                Some(SubscriptionEvent::Message(ChainEvent::NewBlock(block))) => {
+                    if let Some(previous) = block.number.checked_sub(1) {
+                        self.backfill(previous).await?;
+                    }
+                    self.index_block(&block).await?;
+                }
+                Some(SubscriptionEvent::Message(ChainEvent::Reorg {
+                    common_ancestor,
+                    added,
+                    ..
+                })) => {
+                    let keep = match (&common_ancestor, added.first()) {
+                        (Some(ancestor), _) => Some(ancestor.number),
+                        (None, Some(first)) => first.number.checked_sub(1),
+                        (None, None) => continue,
+                    };
+                    self.rollback(keep).await?;
+                    for block in &added {
+                        self.index_block(block).await?;
+                    }
+                }
+                Some(SubscriptionEvent::MissedMessages(skipped)) => {
+                    // The next block's gap backfill picks the missed ones up.
+                    warn!("Indexer lagged behind by {} block events", skipped);
+                }
+                None => return Err(VernachainError::WebSocketClosed),
+            }
+        }
+    }
+
+    /// Fetches and indexes every block in `blocks`, replacing existing rows.
+    pub async fn sync_range(&self, blocks: RangeInclusive<u64>) -> Result<()> {
+        for number in blocks {
+            let block = self.client.get_block(number, self.shard_id).await?;
+            self.index_block(&block).await?;
+        }
+        Ok(())
+    }
+
+    /// Rolls back stored blocks that are no longer canonical, which happens when
+    /// a reorg landed while the indexer was not running.
+    async fn reconcile(&self) -> Result<()> {
+        while let Some(tip) = self.latest_block_number().await? {
+            let stored = self.block_by_number(tip).await?;
+            let canonical = self.client.get_block(tip, self.shard_id).await?;
+            if stored.is_some_and(|b| b.hash == canonical.hash) {
+                break;
+            }
+            info!(
+                "Dropping non-canonical block {} on shard {}",
+                tip, self.shard_id
+            );
+            self.rollback(tip.checked_sub(1)).await?;
+        }
+        Ok(())
+    }
+
+    async fn backfill(&self, to: u64) -> Result<()> {
+        let from = match self.latest_block_number().await? {
+            Some(tip) => tip + 1,
+            None => match self.start_block {
+                Some(start) => start,
+                None => return Ok(()),
+            },
+        };
+        if from <= to {
+            info!(
+                "Backfilling blocks {}..={} on shard {}",
+                from, to, self.shard_id
+            );
+            self.sync_range(from..=to).await?;
+        }
+        Ok(())
+    }
+
+    /// Loads a snapshot written by `VernachainClient::export_range` without
+    /// contacting the node. Blocks it contains replace their indexed rows;
+    /// other blocks stay as they are. Returns the number of blocks imported.
+    pub async fn import_range<R: AsyncRead + Unpin>(&self, reader: R) -> Result<u64> {
+        let mut snapshot = SnapshotReader::new(reader).await?;
+        if snapshot.shard_id() != self.shard_id {
+            return Err(VernachainError::ValidationError(format!(
+                "snapshot is for shard {}, indexer follows shard {}",
+                snapshot.shard_id(),
+                self.shard_id
+            )));
+        }
+        let mut imported = 0;
+        while let Some(entry) = snapshot.next().await? {
+            self.store_block(&entry.block, &entry.logs).await?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
+    async fn index_block(&self, block: &Block) -> Result<()> {
+        let logs = self.client.block_logs(block).await?;
+        self.store_block(block, &logs).await
+    }
+
+    async fn store_block(&self, block: &Block, logs: &[Log]) -> Result<()> {
+        let shard_id = self.shard_id.get() as i64;
+        let number = block.number as i64;
+        let mut tx = self.pool.begin().await?;
+        delete_block(&mut tx, self.shard_id, block.number).await?;
+        sqlx::query("INSERT INTO blocks (shard_id, number, hash, data) VALUES (?, ?, ?, ?)")
+            .bind(shard_id)
+            .bind(number)
+            .bind(&block.hash)
+            .bind(serde_json::to_string(block)?)
+            .execute(&mut *tx)
+            .await?;
+        for transaction in &block.transactions {
+            sqlx::query(
+                "INSERT OR REPLACE INTO transactions
+                 (shard_id, hash, block_number, sender, recipient, data)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(shard_id)
+            .bind(&transaction.hash)
+            .bind(number)
+            .bind(&transaction.sender)
+            .bind(&transaction.recipient)
+            .bind(serde_json::to_string(transaction)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+        for log in logs {
+            sqlx::query(
+                "INSERT OR REPLACE INTO logs (shard_id, block_number, log_index, address, data)
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(shard_id)
+            .bind(number)
+            .bind(log.log_index as i64)
+            .bind(&log.address)
+            .bind(serde_json::to_string(log)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes everything above `keep`, or the whole shard when `keep` is None.
+    async fn rollback(&self, keep: Option<u64>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        delete_from(&mut tx, self.shard_id, keep.map_or(0, |n| n + 1)).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Queries
+    pub async fn latest_block_number(&self) -> Result<Option<u64>> {
+        let number: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(number) FROM blocks WHERE shard_id = ?")
+                .bind(self.shard_id.get() as i64)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(number.map(|n| n as u64))
+    }
+
+    pub async fn block_by_number(&self, number: u64) -> Result<Option<Block>> {
+        let data: Option<String> =
+            sqlx::query_scalar("SELECT data FROM blocks WHERE shard_id = ? AND number = ?")
+                .bind(self.shard_id.get() as i64)
+                .bind(number as i64)
+                .fetch_optional(&self.pool)
+                .await?;
+        decode_optional(data)
+    }
+
+    pub async fn block_by_hash(&self, hash: &str) -> Result<Option<Block>> {
+        let data: Option<String> =
+            sqlx::query_scalar("SELECT data FROM blocks WHERE shard_id = ? AND hash = ?")
+                .bind(self.shard_id.get() as i64)
+                .bind(hash)
+                .fetch_optional(&self.pool)
+                .await?;
+        decode_optional(data)
+    }
+
+    pub async fn transaction(&self, hash: &str) -> Result<Option<Transaction>> {
+        let data: Option<String> =
+            sqlx::query_scalar("SELECT data FROM transactions WHERE shard_id = ? AND hash = ?")
+                .bind(self.shard_id.get() as i64)
+                .bind(hash)
+                .fetch_optional(&self.pool)
+                .await?;
+        decode_optional(data)
+    }
+
+    /// Transactions sent or received by `address`, newest first.
+    pub async fn transactions_for_address(
+        &self,
+        address: &str,
+        limit: u32,
+    ) -> Result<Vec<Transaction>> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT data FROM transactions
+             WHERE shard_id = ? AND (sender = ? OR recipient = ?)
+             ORDER BY block_number DESC LIMIT ?",
+        )
+        .bind(self.shard_id.get() as i64)
+        .bind(address)
+        .bind(address)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.iter()
+            .map(|row| Ok(serde_json::from_str(row)?))
+            .collect()
+    }
+
+    /// Indexed logs matching `filter`, in chain order. The filter's shard is
+    /// ignored; an indexer only holds its own shard.
+    pub async fn logs(&self, filter: &LogFilter) -> Result<Vec<Log>> {
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT data FROM logs
+             WHERE shard_id = ? AND block_number >= ? AND block_number <= ?
+             ORDER BY block_number, log_index",
+        )
+        .bind(self.shard_id.get() as i64)
+        .bind(filter.from_block.map_or(0, |n| n as i64))
+        .bind(filter.to_block.map_or(i64::MAX, |n| n as i64))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut logs = Vec::new();
+        for row in rows {
+            let log: Log = serde_json::from_str(&row)?;
+            if log_matches(filter, &log) {
+                logs.push(log);
+            }
+        }
+        Ok(logs)
+    }
+}
+
+/// Tables holding per-block rows, with their block number column.
+const BLOCK_TABLES: [(&str, &str); 3] = [
+    ("logs", "block_number"),
+    ("transactions", "block_number"),
+    ("blocks", "number"),
+];
+
+/// Removes the shard's rows from block `first` upwards.
+async fn delete_from(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    shard_id: ShardId,
+    first: u64,
+) -> Result<()> {
+    delete_where(tx, shard_id, ">=", first).await
+}
+
+/// Removes the shard's rows of block `number` only, leaving later blocks.
+async fn delete_block(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    shard_id: ShardId,
+    number: u64,
+) -> Result<()> {
+    delete_where(tx, shard_id, "=", number).await
+}
+
+async fn delete_where(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    shard_id: ShardId,
+    comparison: &str,
+    number: u64,
+) -> Result<()> {
+    for (table, column) in BLOCK_TABLES {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE shard_id = ? AND {} {} ?",
+            table, column, comparison
+        ))
+        .bind(shard_id.get() as i64)
+        .bind(number as i64)
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+fn log_matches(filter: &LogFilter, log: &Log) -> bool {
+    let address_match = filter.addresses.is_empty()
+        || filter
+            .addresses
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(&log.address));
+    let topics_match = filter
+        .topics
+        .iter()
+        .enumerate()
+        .all(|(i, topic)| match topic {
+            Some(topic) => log
+                .topics
+                .get(i)
+                .is_some_and(|t| t.eq_ignore_ascii_case(topic)),
+            None => true,
+        });
+    let event_match = filter.event.is_none() || filter.event == log.event;
+    address_match && topics_match && event_match
+}
+
+fn decode_optional<T: serde::de::DeserializeOwned>(data: Option<String>) -> Result<Option<T>> {
+    data.map(|d| serde_json::from_str(&d))
+        .transpose()
+        .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::{SnapshotBlock, SnapshotFormat, SnapshotWriter};
+    use serde_json::json;
+
+    fn block(number: u64) -> Block {
+        serde_json::from_value(json!({
+            "number": number,
+            "hash": format!("hash{}", number),
+            "previous_hash": format!("hash{}", number.saturating_sub(1)),
+            "timestamp": "2024-01-01T00:00:00Z",
+            "transactions": [],
+            "validator": "validator",
+            "shard_id": 0,
+            "merkle_root": "",
+            "state_root": "",
+        }))
+        .unwrap()
+    }
+
+    async fn indexer() -> ChainIndexer {
+        let path = std::env::temp_dir().join(format!(
+            "vernachain-indexer-{}-{}.db",
+            std::process::id(),
+            rand_core::RngCore::next_u64(&mut rand_core::OsRng)
+        ));
+        let client = VernachainClient::new("http://127.0.0.1:1", None).unwrap();
+        ChainIndexer::open(client, &format!("sqlite://{}", path.display()), ShardId(0))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn restoring_a_block_keeps_later_blocks() {
+        let indexer = indexer().await;
+        for number in 1..=5 {
+            indexer.store_block(&block(number), &[]).await.unwrap();
+        }
+        indexer.store_block(&block(2), &[]).await.unwrap();
+
+        assert_eq!(indexer.latest_block_number().await.unwrap(), Some(5));
+        assert!(indexer.block_by_number(3).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn rollback_drops_every_later_block() {
+        let indexer = indexer().await;
+        for number in 1..=5 {
+            indexer.store_block(&block(number), &[]).await.unwrap();
+        }
+        indexer.rollback(Some(2)).await.unwrap();
+
+        assert_eq!(indexer.latest_block_number().await.unwrap(), Some(2));
+        assert!(indexer.block_by_number(3).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn importing_an_older_range_keeps_later_blocks() {
+        let indexer = indexer().await;
+        for number in 1..=5 {
+            indexer.store_block(&block(number), &[]).await.unwrap();
+        }
+        let mut writer = SnapshotWriter::new(Vec::new(), ShardId(0), SnapshotFormat::Json)
+            .await
+            .unwrap();
+        for number in 2..=3 {
+            let entry = SnapshotBlock {
+                block: block(number),
+                logs: Vec::new(),
+            };
+            writer.write(&entry).await.unwrap();
+        }
+        let snapshot = writer.finish().await.unwrap();
+
+        assert_eq!(indexer.import_range(snapshot.as_slice()).await.unwrap(), 2);
+        assert_eq!(indexer.latest_block_number().await.unwrap(), Some(5));
+    }
+}
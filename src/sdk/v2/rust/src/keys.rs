@@ -0,0 +1,238 @@
+//! Account keys.
+//!
+//! Accounts use ed25519 keys, as created by the Vernachain wallet. A secret key
+//! is its 32-byte seed, and an account's address is the hex-encoded public key.
+
+use crate::{
+    codec,
+    error::{Result, VernachainError},
+    types::TransactionRequest,
+};
+use ed25519_dalek::{
+    pkcs8::{
+        spki::der::pem::LineEnding, DecodePrivateKey, DecodePublicKey, EncodePrivateKey,
+        EncodePublicKey,
+    },
+    Signature, Signer, SigningKey, VerifyingKey,
+};
+use rand_core::OsRng;
+use std::fmt;
+
+/// Creates a new random keypair from the operating system's RNG.
+pub fn generate_keypair() -> Keypair {
+    Keypair {
+        signing_key: SigningKey::generate(&mut OsRng),
+    }
+}
+
+#[derive(Clone)]
+pub struct Keypair {
+    signing_key: SigningKey,
+}
+
+impl Keypair {
+    /// Loads a 32-byte secret seed, the format the wallet stores as
+    /// `private_key`.
+    pub fn from_secret_hex(secret: &str) -> Result<Self> {
+        let bytes = decode_hex::<32>(secret, "secret key")?;
+        Ok(Self {
+            signing_key: SigningKey::from_bytes(&bytes),
+        })
+    }
+
+    pub fn to_secret_hex(&self) -> String {
+        hex::encode(self.signing_key.to_bytes())
+    }
+
+    /// Loads a PKCS#8 `PRIVATE KEY` PEM document.
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let signing_key = SigningKey::from_pkcs8_pem(pem).map_err(|e| invalid("private key", e))?;
+        Ok(Self { signing_key })
+    }
+
+    pub fn to_pem(&self) -> Result<String> {
+        let pem = self
+            .signing_key
+            .to_pkcs8_pem(LineEnding::LF)
+            .map_err(|e| VernachainError::InternalError(e.to_string()))?;
+        Ok(pem.to_string())
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.signing_key.verifying_key())
+    }
+
+    pub fn address(&self) -> String {
+        self.public_key().to_address()
+    }
+
+    /// Signs `message` and returns the hex-encoded 64-byte signature.
+    pub fn sign(&self, message: &[u8]) -> String {
+        hex::encode(self.signing_key.sign(message).to_bytes())
+    }
+
+    /// Signs a transfer from this key's account over the fields the node
+    /// hashes it by, so the nonce and timestamp must be pinned first.
+    pub fn sign_transaction(&self, request: &mut TransactionRequest) -> Result<()> {
+        if !request.sender.eq_ignore_ascii_case(&self.address()) {
+            return Err(VernachainError::ValidationError(format!(
+                "transaction is from {}, not this key's account {}",
+                request.sender,
+                self.address()
+            )));
+        }
+        let (Some(nonce), Some(timestamp)) = (request.nonce, &request.timestamp) else {
+            return Err(VernachainError::ValidationError(
+                "nonce and timestamp must be set before signing".to_string(),
+            ));
+        };
+        let encoded = codec::encode_transaction(
+            &request.sender,
+            &request.recipient,
+            request.amount,
+            nonce,
+            timestamp,
+        )?;
+        request.signature = Some(self.sign(&encoded));
+        Ok(())
+    }
+
+    pub fn signing_key(&self) -> &SigningKey {
+        &self.signing_key
+    }
+}
+
+impl From<SigningKey> for Keypair {
+    fn from(signing_key: SigningKey) -> Self {
+        Self { signing_key }
+    }
+}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret half.
+        f.debug_struct("Keypair")
+            .field("public_key", &self.public_key().to_hex())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(VerifyingKey);
+
+impl PublicKey {
+    pub fn from_hex(public_key: &str) -> Result<Self> {
+        let bytes = decode_hex::<32>(public_key, "public key")?;
+        let key = VerifyingKey::from_bytes(&bytes).map_err(|e| invalid("public key", e))?;
+        Ok(Self(key))
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0.as_bytes())
+    }
+
+    /// Loads an SPKI `PUBLIC KEY` PEM document.
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let key = VerifyingKey::from_public_key_pem(pem).map_err(|e| invalid("public key", e))?;
+        Ok(Self(key))
+    }
+
+    pub fn to_pem(&self) -> Result<String> {
+        self.0
+            .to_public_key_pem(LineEnding::LF)
+            .map_err(|e| VernachainError::InternalError(e.to_string()))
+    }
+
+    /// The account address for this key, as the wallet derives it.
+    pub fn to_address(&self) -> String {
+        self.to_hex()
+    }
+
+    /// Checks a hex-encoded signature over `message`. Signatures must be
+    /// exactly 64 bytes, and small-order keys and non-canonical `R` values
+    /// are rejected rather than accepted by the cofactored check.
+    pub fn verify(&self, message: &[u8], signature: &str) -> bool {
+        let Ok(bytes) = decode_hex::<64>(signature, "signature") else {
+            return false;
+        };
+        self.0
+            .verify_strict(message, &Signature::from_bytes(&bytes))
+            .is_ok()
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.0
+    }
+}
+
+fn decode_hex<const N: usize>(data: &str, what: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(data.trim_start_matches("0x")).map_err(|e| invalid(what, e))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        VernachainError::ValidationError(format!(
+            "{} must be {} bytes, got {}",
+            what,
+            N,
+            bytes.len()
+        ))
+    })
+}
+
+fn invalid(what: &str, error: impl fmt::Display) -> VernachainError {
+    VernachainError::ValidationError(format!("invalid {}: {}", what, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Verifier;
+
+    #[test]
+    fn verifies_its_own_signatures() {
+        let keypair = generate_keypair();
+        let signature = keypair.sign(b"hello");
+        assert!(keypair.public_key().verify(b"hello", &signature));
+        assert!(!keypair.public_key().verify(b"hullo", &signature));
+        assert!(!keypair.public_key().verify(b"hello", &(signature + "00")));
+    }
+
+    #[test]
+    fn signs_transactions_over_their_encoding() {
+        let keypair = generate_keypair();
+        let timestamp = chrono::Utc::now();
+        let mut request = TransactionRequest {
+            sender: keypair.address(),
+            recipient: "22".repeat(32),
+            amount: 1.5,
+            ..Default::default()
+        };
+        assert!(keypair.sign_transaction(&mut request).is_err());
+
+        request.nonce = Some(3);
+        request.timestamp = Some(timestamp);
+        keypair.sign_transaction(&mut request).unwrap();
+        let encoded =
+            codec::encode_transaction(&request.sender, &request.recipient, 1.5, 3, &timestamp)
+                .unwrap();
+        let signature = request.signature.as_deref().unwrap();
+        assert!(keypair.public_key().verify(&encoded, signature));
+
+        assert!(generate_keypair().sign_transaction(&mut request).is_err());
+    }
+
+    #[test]
+    fn rejects_small_order_keys() {
+        // The identity point as the key, with R = identity and S = 0, passes
+        // the plain verification equation for any message.
+        let identity = format!("01{}", "00".repeat(31));
+        let key = PublicKey::from_hex(&identity).unwrap();
+        let signature = format!("{}{}", identity, "00".repeat(32));
+        assert!(key
+            .verifying_key()
+            .verify(
+                b"anything",
+                &Signature::from_bytes(&decode_hex::<64>(&signature, "").unwrap())
+            )
+            .is_ok());
+        assert!(!key.verify(b"anything", &signature));
+    }
+}
@@ -1,16 +1,92 @@
+pub mod abi;
+pub mod abi_registry;
+pub mod address;
+pub mod address_book;
+pub mod amount;
+#[cfg(feature = "admin")]
+pub mod admin;
+pub mod auth;
+pub mod backfill;
+pub mod bloom;
+mod block_stream;
+pub mod bridge;
 pub mod client;
+pub mod codec;
+pub mod concurrency;
+pub mod connection;
+pub mod config;
+pub mod cursor;
+mod decode;
+pub mod describe;
+#[cfg(feature = "devnet")]
+pub mod devnet;
+pub mod encoding;
 pub mod error;
+pub mod history;
+#[cfg(feature = "indexer")]
+pub mod indexer;
+pub mod keys;
+mod log_stream;
+mod logging;
+pub mod merkle;
+pub mod network;
+pub mod multisig;
+pub mod nft;
+pub mod nonce;
+pub mod payment;
+pub mod poll;
+pub mod portfolio;
+pub mod proxy;
+pub mod retry;
+mod reorg;
+pub mod routing;
+pub mod scheduler;
+pub mod snapshot;
+pub mod sponsor;
+pub mod subscription;
+pub mod staking;
+pub mod templates;
+pub mod tokens;
 pub mod types;
+pub mod webhooks;
+mod ws;
 
-pub use client::VernachainClient;
-pub use error::{Result, VernachainError};
+pub use abi_registry::AbiRegistry;
+pub use address::Address;
+pub use address_book::AddressBook;
+pub use amount::{format_units, parse_units, Amount};
+pub use auth::AuthProvider;
+pub use backfill::{BackfillProgress, Backfiller};
+pub use client::{Transport, VernachainClient, VernachainClientBuilder};
+pub use codec::{predict_contract_address, DeploymentSeed};
+pub use concurrency::AdaptiveConcurrency;
+pub use connection::ConnectionEvent;
+pub use config::ClientConfig;
+pub use cursor::{Cursor, CursorStore, FileCursorStore};
+pub use describe::{describe_transaction, TransactionSummary};
+pub use encoding::BinaryEncoding;
+pub use error::{ErrorCategory, Result, VernachainError};
+pub use keys::{generate_keypair, Keypair, PublicKey};
+pub use multisig::Multisig;
+pub use network::Network;
+pub use nft::Nft;
+pub use nonce::NonceManager;
+pub use payment::PaymentRequest;
+pub use poll::{poll_until, CancellationToken};
+pub use portfolio::Portfolio;
+pub use proxy::Proxy;
+pub use retry::RetryPolicy;
+pub use routing::AddressRouter;
+pub use subscription::{Subscription, SubscriptionEvent};
+pub use tokens::Token;
 pub use types::*;
 
 /// Re-export commonly used types
 pub mod prelude {
     pub use super::{
-        Block, BridgeTransfer, BridgeTransferRequest, ContractDeployRequest, CrossShardTransfer,
-        CrossShardTransferRequest, Result, SmartContract, Transaction, TransactionRequest,
-        Validator, VernachainClient, VernachainError,
+        Block, BridgeTransfer, BridgeTransferRequest, ChainEvent, ChainId, ContractDeployRequest,
+        ContractType, CrossShardTransfer, CrossShardTransferRequest, Network, Result, ShardId,
+        SmartContract, Transaction, TransactionRequest, Validator, VernachainClient,
+        VernachainError,
     };
 } 
\ No newline at end of file
@@ -0,0 +1,189 @@
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    cursor::Cursor,
+    reorg::REORG_WINDOW,
+    subscription::{Subscription, SubscriptionEvent},
+    types::*,
+};
+use std::collections::VecDeque;
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+/// Blocks covered by each historical `get_logs` page.
+const PAGE_BLOCKS: u64 = 1_000;
+
+/// Pages historical logs from `next_block` up to the head, then follows a
+/// block subscription, so every matching log is delivered once in block
+/// order. Blocks the subscription skipped are fetched from history, and the
+/// logs of blocks dropped by a reorg are re-sent with `removed` set.
+pub(crate) struct LogStream {
+    client: VernachainClient,
+    filter: LogFilter,
+    shard_id: ShardId,
+    next_block: u64,
+    /// Delivered logs of recent blocks by block hash, oldest first, for
+    /// reverting them on a reorg.
+    recent: VecDeque<(String, Vec<Log>)>,
+    /// Position a resumed stream starts after; logs up to it were handled
+    /// before the restart and are not sent again.
+    resume_after: Option<Cursor>,
+    tx: broadcast::Sender<SubscriptionEvent<Log>>,
+}
+
+impl LogStream {
+    pub(crate) fn new(
+        client: VernachainClient,
+        filter: LogFilter,
+        shard_id: ShardId,
+        from_block: u64,
+        tx: broadcast::Sender<SubscriptionEvent<Log>>,
+    ) -> Self {
+        Self {
+            client,
+            filter,
+            shard_id,
+            next_block: from_block,
+            recent: VecDeque::with_capacity(REORG_WINDOW),
+            resume_after: None,
+            tx,
+        }
+    }
+
+    /// Starts right after `cursor` rather than at `from_block`.
+    pub(crate) fn resume_after(mut self, cursor: Cursor) -> Self {
+        self.next_block = cursor.resume_block();
+        self.resume_after = Some(cursor);
+        self
+    }
+
+    pub(crate) async fn run(mut self, blocks: Subscription<ChainEvent>) {
+        let shutdown = self.client.shutdown_token().clone();
+        let result = tokio::select! {
+            _ = shutdown.cancelled() => Ok(()),
+            result = self.follow(blocks) => result,
+        };
+        match result {
+            Ok(()) | Err(VernachainError::Cancelled) => {}
+            Err(e) => error!("Event stream on shard {} stopped: {}", self.shard_id, e),
+        }
+    }
+
+    async fn follow(&mut self, mut blocks: Subscription<ChainEvent>) -> Result<()> {
+        let head = self.client.get_latest_block(self.shard_id).await?;
+        self.backfill(head.number).await?;
+        while let Some(event) = blocks.recv().await {
+            match event {
+                SubscriptionEvent::Message(ChainEvent::NewBlock(block)) => {
+                    self.apply(&block).await?
+                }
+                SubscriptionEvent::Message(ChainEvent::Reorg { dropped, added, .. }) => {
+                    self.revert(&dropped)?;
+                    for block in &added {
+                        self.apply(block).await?;
+                    }
+                }
+                SubscriptionEvent::MissedMessages(skipped) => {
+                    // The next block fills the gap from history.
+                    warn!(
+                        "Event stream on shard {} missed {} blocks",
+                        self.shard_id, skipped
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Delivers the logs of blocks `next_block..=to` from history.
+    async fn backfill(&mut self, to: u64) -> Result<()> {
+        while self.next_block <= to {
+            let end = to.min(self.next_block.saturating_add(PAGE_BLOCKS - 1));
+            let filter = self
+                .filter
+                .clone()
+                .shard(self.shard_id)
+                .from_block(self.next_block)
+                .to_block(end);
+            let logs = self.client.get_logs(&filter).await?;
+            self.deliver(logs)?;
+            self.advance(end + 1);
+        }
+        Ok(())
+    }
+
+    async fn apply(&mut self, block: &Block) -> Result<()> {
+        if block.number < self.next_block {
+            return Ok(());
+        }
+        if block.number > self.next_block {
+            self.backfill(block.number - 1).await?;
+        }
+        if !block.transactions.is_empty() {
+            let filter = self
+                .filter
+                .clone()
+                .shard(self.shard_id)
+                .from_block(block.number)
+                .to_block(block.number);
+            let logs = self.client.get_logs(&filter).await?;
+            self.deliver(logs)?;
+        }
+        self.advance(block.number + 1);
+        Ok(())
+    }
+
+    fn advance(&mut self, next_block: u64) {
+        self.next_block = next_block;
+        if self
+            .resume_after
+            .is_some_and(|cursor| cursor.block_number < next_block)
+        {
+            self.resume_after = None;
+        }
+    }
+
+    /// Re-sends the delivered logs of `dropped`, newest first, as removed.
+    fn revert(&mut self, dropped: &[Block]) -> Result<()> {
+        for block in dropped.iter().rev() {
+            if let Some(position) = self.recent.iter().position(|(hash, _)| *hash == block.hash) {
+                if let Some((_, logs)) = self.recent.remove(position) {
+                    for mut log in logs.into_iter().rev() {
+                        log.removed = true;
+                        self.send(log)?;
+                    }
+                }
+            }
+            self.next_block = self.next_block.min(block.number);
+        }
+        Ok(())
+    }
+
+    fn deliver(&mut self, logs: Vec<Log>) -> Result<()> {
+        for log in logs {
+            match self.recent.back_mut() {
+                Some((hash, block_logs)) if *hash == log.block_hash => block_logs.push(log.clone()),
+                _ => {
+                    if self.recent.len() == REORG_WINDOW {
+                        self.recent.pop_front();
+                    }
+                    self.recent
+                        .push_back((log.block_hash.clone(), vec![log.clone()]));
+                }
+            }
+            // Logs handled before a restart are still kept above, so a reorg
+            // of their block reverts them.
+            if !self.resume_after.is_some_and(|cursor| cursor.covers(&log)) {
+                self.send(log)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&self, log: Log) -> Result<()> {
+        self.tx
+            .send(SubscriptionEvent::Message(log))
+            .map(|_| ())
+            .map_err(|_| VernachainError::Cancelled)
+    }
+}
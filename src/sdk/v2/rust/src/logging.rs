@@ -0,0 +1,133 @@
+//! Opt-in request and response logging.
+//!
+//! When enabled on the builder, every HTTP exchange is logged at debug level
+//! inside its `vernachain_request` span: the method, URL and headers of the
+//! request, then the status, duration and body of the response. Transaction
+//! payloads carry private keys and signatures, so credentials, secrets and
+//! signatures are replaced with `[redacted]` wherever they appear, in headers,
+//! query parameters and JSON bodies alike, and long bodies are truncated.
+
+use crate::auth;
+use reqwest::{header::HeaderMap, StatusCode, Url};
+use std::time::Duration;
+use tracing::debug;
+
+/// Bodies are cut off after this many bytes.
+const MAX_LOGGED_BODY: usize = 1024;
+const REDACTED: &str = "[redacted]";
+
+/// Fields redacted wherever their name contains one of these.
+const SENSITIVE_PARTS: &[&str] = &["private_key", "secret", "signature", "password", "mnemonic"];
+/// Fields redacted when named exactly this.
+const SENSITIVE_NAMES: &[&str] = &["api_key", "access_token", "refresh_token", "authorization"];
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "cookie", auth::SIGNATURE_HEADER];
+
+pub(crate) fn log_request(request: &reqwest::Request) {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(describe_body)
+        .unwrap_or_default();
+    debug!(
+        method = %request.method(),
+        url = %redact_url(request.url()),
+        headers = ?redact_headers(request.headers()),
+        body = %body,
+        "Sending request"
+    );
+}
+
+pub(crate) fn log_response(status: StatusCode, elapsed: Duration, body: &[u8]) {
+    debug!(
+        status = status.as_u16(),
+        elapsed_ms = elapsed.as_millis() as u64,
+        body = %describe_body(body),
+        "Received response"
+    );
+}
+
+fn is_sensitive(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_PARTS.iter().any(|part| name.contains(part))
+        || SENSITIVE_NAMES.contains(&name.as_str())
+}
+
+fn redact_url(url: &Url) -> Url {
+    let mut redacted = url.clone();
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if is_sensitive(&name) {
+                    REDACTED.into()
+                } else {
+                    value
+                };
+                (name.into_owned(), value.into_owned())
+            })
+            .collect();
+        redacted.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    redacted
+}
+
+fn redact_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let sensitive = SENSITIVE_HEADERS
+                .iter()
+                .any(|header| name.as_str().eq_ignore_ascii_case(header));
+            let value = if sensitive {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if is_sensitive(name) {
+                    *field = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Redacted JSON, or the raw text, truncated. Binary bodies are only sized.
+fn describe_body(body: &[u8]) -> String {
+    let text = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(mut json) => {
+            redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => match std::str::from_utf8(body) {
+            Ok(text) => text.to_string(),
+            Err(_) => return format!("<{} bytes>", body.len()),
+        },
+    };
+    truncate(text)
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() <= MAX_LOGGED_BODY {
+        return text;
+    }
+    let total = text.len();
+    let mut end = MAX_LOGGED_BODY;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    format!("{}... ({} bytes)", text, total)
+}
@@ -0,0 +1,210 @@
+//! Verification of Merkle proofs returned by the node.
+//!
+//! Hashing mirrors the node's state trie: keys are SHA-256 hashed before
+//! insertion, values are stored as sorted-key JSON, and each trie level
+//! consumes one key byte. Cross-shard proofs use the node's simpler hash path.
+
+use crate::{
+    codec::canonical_json,
+    types::{CrossShardProof, ProofNode, StateProof},
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Verifies that `proof` links `key` to `value` under the trie rooted at `root`.
+pub fn verify_proof(root: &str, key: &str, value: &Value, proof: &[ProofNode]) -> bool {
+    let Some(first) = proof.first() else {
+        return false;
+    };
+    if !first.hash().eq_ignore_ascii_case(root) {
+        return false;
+    }
+    let Ok(encoded_value) = canonical_json(value) else {
+        return false;
+    };
+
+    let mut remaining: &[u8] = &Sha256::digest(key.as_bytes());
+    for (i, node) in proof.iter().enumerate() {
+        let next = proof.get(i + 1).map(ProofNode::hash);
+        match node {
+            ProofNode::Leaf { hash, key, value } => {
+                let Ok(leaf_key) = hex::decode(key) else {
+                    return false;
+                };
+                let Ok(leaf_value) = canonical_json(value) else {
+                    return false;
+                };
+                return next.is_none()
+                    && leaf_key == remaining
+                    && leaf_value == encoded_value
+                    && leaf_hash(&leaf_key, &leaf_value) == *hash;
+            }
+            ProofNode::Extension { hash, prefix } => {
+                let (Ok(prefix), Some(next)) = (hex::decode(prefix), next) else {
+                    return false;
+                };
+                if !remaining.starts_with(&prefix) || extension_hash(&prefix, next) != *hash {
+                    return false;
+                }
+                remaining = &remaining[prefix.len()..];
+            }
+            ProofNode::Branch {
+                hash,
+                value: branch_value,
+                children,
+            } => {
+                let Some(computed) = branch_hash(children, branch_value.as_ref()) else {
+                    return false;
+                };
+                if computed != *hash {
+                    return false;
+                }
+                match remaining.split_first() {
+                    None => return next.is_none() && branch_value.as_ref() == Some(value),
+                    Some((byte, rest)) => {
+                        let child = children.get((byte & 0xF) as usize).and_then(Option::as_ref);
+                        match (child, next) {
+                            (Some(child), Some(next)) if child.eq_ignore_ascii_case(next) => {}
+                            _ => return false,
+                        }
+                        remaining = rest;
+                    }
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Verifies the account proof against `state_root` and every storage proof
+/// against the account's `storage_root`.
+pub fn verify_state_proof(proof: &StateProof, state_root: &str) -> bool {
+    if !verify_proof(
+        state_root,
+        &proof.address,
+        &proof.account,
+        &proof.account_proof,
+    ) {
+        return false;
+    }
+    if proof.storage_proof.is_empty() {
+        return true;
+    }
+
+    let Some(storage_root) = proof.account.get("storage_root").and_then(Value::as_str) else {
+        return false;
+    };
+    proof
+        .storage_proof
+        .iter()
+        .all(|entry| verify_proof(storage_root, &entry.key, &entry.value, &entry.proof))
+}
+
+/// Folds a hash path the way the node does for cross-shard messages: each step
+/// hashes the hex digest so far concatenated with the next sibling.
+pub fn fold_path(leaf_hash: &str, path: &[String]) -> String {
+    path.iter().fold(leaf_hash.to_string(), |current, sibling| {
+        sha256_hex(format!("{}{}", current, sibling).as_bytes())
+    })
+}
+
+/// Verifies that the proven transaction is `tx_hash` and that its path leads to
+/// `source_state_root`, the state root of the source shard's block.
+pub fn verify_cross_shard_proof(
+    proof: &CrossShardProof,
+    tx_hash: &str,
+    source_state_root: &str,
+) -> bool {
+    proof.transaction_hash.eq_ignore_ascii_case(tx_hash)
+        && proof.state_root.eq_ignore_ascii_case(source_state_root)
+        && fold_path(&proof.transaction_hash, &proof.merkle_proof)
+            .eq_ignore_ascii_case(source_state_root)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+fn leaf_hash(key: &[u8], value: &[u8]) -> String {
+    sha256_hex(&[key, b":", value].concat())
+}
+
+fn extension_hash(prefix: &[u8], next_hash: &str) -> String {
+    sha256_hex(&[prefix, b":", next_hash.as_bytes()].concat())
+}
+
+fn branch_hash(children: &[Option<String>], value: Option<&Value>) -> Option<String> {
+    if children.len() != 16 {
+        return None;
+    }
+    let empty = sha256_hex(b"");
+    let value_hash = match value {
+        Some(value) => sha256_hex(&canonical_json(value).ok()?),
+        None => empty.clone(),
+    };
+    let parts: Vec<&str> = children
+        .iter()
+        .map(|child| child.as_deref().unwrap_or(&empty))
+        .chain(std::iter::once(value_hash.as_str()))
+        .collect();
+    Some(sha256_hex(parts.join(":").as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Generated by the node's `MerklePatriciaTrie` holding `alice` and `bob`.
+    const ROOT: &str = "d3763f8f8607d11482052069f161bacca91cfa31d40d4329f61c962a1fdb1d18";
+
+    fn alice_proof() -> Vec<ProofNode> {
+        let mut children = vec![Value::Null; 16];
+        children[1] = json!("87e61e4a6aa1f364b2e275fd72c882e1e2cdc6e6d352d97b7dd6c21597f0c834");
+        children[11] = json!("4e60cdd8157691cc557c081d0b6c695a75cd225e86aaf9a0442a83f8011bc281");
+        serde_json::from_value(json!([
+            {"type": "branch", "hash": ROOT, "value": null, "children": children},
+            {
+                "type": "leaf",
+                "hash": "4e60cdd8157691cc557c081d0b6c695a75cd225e86aaf9a0442a83f8011bc281",
+                "key": "d806c97f0e00af1a1fc3328fa763a9269723c8db8fac4f93af71db186d6e90",
+                "value": {"balance": 100, "nonce": 1},
+            },
+        ]))
+        .unwrap()
+    }
+
+    fn alice() -> Value {
+        json!({"balance": 100, "nonce": 1})
+    }
+
+    #[test]
+    fn accepts_node_proofs() {
+        assert!(verify_proof(ROOT, "alice", &alice(), &alice_proof()));
+    }
+
+    #[test]
+    fn rejects_other_values_keys_and_roots() {
+        let proof = alice_proof();
+        let other = json!({"balance": 101, "nonce": 1});
+        assert!(!verify_proof(ROOT, "alice", &other, &proof));
+        assert!(!verify_proof(ROOT, "bob", &alice(), &proof));
+        assert!(!verify_proof(&"0".repeat(64), "alice", &alice(), &proof));
+        assert!(!verify_proof(ROOT, "alice", &alice(), &[]));
+    }
+
+    #[test]
+    fn rejects_tampered_nodes() {
+        let mut proof = alice_proof();
+        if let ProofNode::Leaf { value, .. } = &mut proof[1] {
+            *value = json!({"balance": 1000, "nonce": 1});
+        }
+        let forged = json!({"balance": 1000, "nonce": 1});
+        assert!(!verify_proof(ROOT, "alice", &forged, &proof));
+
+        let mut proof = alice_proof();
+        proof.truncate(1);
+        assert!(!verify_proof(ROOT, "alice", &alice(), &proof));
+    }
+}
@@ -0,0 +1,278 @@
+//! Multisig wallet workflow: propose a transaction, collect owner signatures
+//! offline, and execute it once the threshold is met.
+//!
+//! A `SignedProposal` serializes to plain JSON, so it can be passed between
+//! owners by any channel. Owners sign the SHA-256 digest of the proposal's
+//! sorted-key JSON with their ed25519 wallet key.
+
+use crate::{
+    client::VernachainClient,
+    codec,
+    error::{Result, VernachainError},
+    keys::PublicKey,
+    templates::MultisigDeploy,
+    types::ShardId,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// The transaction the owners are asked to approve.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultisigProposal {
+    pub multisig: String,
+    /// The wallet's execution nonce; a proposal can only be executed once.
+    pub nonce: u64,
+    pub to: String,
+    pub amount: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<HashMap<String, serde_json::Value>>,
+}
+
+impl MultisigProposal {
+    /// Hex SHA-256 of the proposal in the node's canonical JSON form.
+    pub fn digest(&self) -> Result<String> {
+        Ok(hex::encode(Sha256::digest(codec::canonical_json(self)?)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialSignature {
+    /// Hex-encoded ed25519 public key, which is also the owner's address.
+    pub signer: String,
+    pub signature: String,
+}
+
+impl PartialSignature {
+    /// Whether this is the signer's signature over `digest`, under the
+    /// strict check of `PublicKey::verify`.
+    fn verify(&self, digest: &str) -> bool {
+        PublicKey::from_hex(&self.signer)
+            .is_ok_and(|key| key.verify(digest.as_bytes(), &self.signature))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedProposal {
+    pub proposal: MultisigProposal,
+    #[serde(default)]
+    pub signatures: Vec<PartialSignature>,
+}
+
+impl SignedProposal {
+    pub fn new(proposal: MultisigProposal) -> Self {
+        Self {
+            proposal,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Adds this owner's signature, replacing any earlier one from the same key.
+    pub fn sign(&mut self, key: &SigningKey) -> Result<()> {
+        let digest = self.proposal.digest()?;
+        let signature = PartialSignature {
+            signer: hex::encode(key.verifying_key().as_bytes()),
+            signature: hex::encode(key.sign(digest.as_bytes()).to_bytes()),
+        };
+        self.add_signature(signature)
+    }
+
+    /// Adds a signature collected from another owner after checking it.
+    pub fn add_signature(&mut self, signature: PartialSignature) -> Result<()> {
+        let digest = self.proposal.digest()?;
+        if !signature.verify(&digest) {
+            return Err(VernachainError::ValidationError(format!(
+                "signature from {} does not match the proposal",
+                signature.signer
+            )));
+        }
+        self.signatures
+            .retain(|s| !s.signer.eq_ignore_ascii_case(&signature.signer));
+        self.signatures.push(signature);
+        Ok(())
+    }
+
+    /// Merges the signatures from another copy of the same proposal.
+    pub fn merge(&mut self, other: SignedProposal) -> Result<()> {
+        if other.proposal != self.proposal {
+            return Err(VernachainError::ValidationError(
+                "cannot merge signatures for a different proposal".to_string(),
+            ));
+        }
+        for signature in other.signatures {
+            self.add_signature(signature)?;
+        }
+        Ok(())
+    }
+
+    /// Number of distinct `owners` with a valid signature on the proposal.
+    pub fn approvals(&self, owners: &[String]) -> usize {
+        let Ok(digest) = self.proposal.digest() else {
+            return 0;
+        };
+        let owners: HashSet<String> = owners.iter().map(|o| o.to_ascii_lowercase()).collect();
+        self.signatures
+            .iter()
+            .filter(|s| owners.contains(&s.signer.to_ascii_lowercase()))
+            .filter(|s| s.verify(&digest))
+            .map(|s| s.signer.to_ascii_lowercase())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+}
+
+/// Handle to a deployed multisig wallet.
+#[derive(Clone)]
+pub struct Multisig {
+    client: VernachainClient,
+    address: String,
+}
+
+impl Multisig {
+    /// Deploys a new wallet from the multisig template.
+    pub async fn create(
+        client: &VernachainClient,
+        owners: Vec<String>,
+        threshold: usize,
+        shard_id: ShardId,
+    ) -> Result<Self> {
+        let contract = client
+            .deploy_template(&MultisigDeploy::new(owners, threshold), shard_id)
+            .await?;
+        Ok(Self::at(client, &contract.address))
+    }
+
+    pub fn at(client: &VernachainClient, address: &str) -> Self {
+        Self {
+            client: client.clone(),
+            address: address.to_string(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub async fn owners(&self) -> Result<Vec<String>> {
+        self.client
+            .call_contract_as(&self.address, "owners", json!([]))
+            .await
+    }
+
+    pub async fn threshold(&self) -> Result<usize> {
+        self.client
+            .call_contract_as(&self.address, "threshold", json!([]))
+            .await
+    }
+
+    pub async fn nonce(&self) -> Result<u64> {
+        self.client
+            .call_contract_as(&self.address, "nonce", json!([]))
+            .await
+    }
+
+    /// Builds an unsigned proposal against the wallet's current nonce.
+    pub async fn propose(
+        &self,
+        to: &str,
+        amount: f64,
+        data: Option<HashMap<String, serde_json::Value>>,
+    ) -> Result<SignedProposal> {
+        Ok(SignedProposal::new(MultisigProposal {
+            multisig: self.address.clone(),
+            nonce: self.nonce().await?,
+            to: to.to_string(),
+            amount,
+            data,
+        }))
+    }
+
+    /// Executes the proposal once enough owners have signed it.
+    pub async fn submit(&self, signed: &SignedProposal) -> Result<serde_json::Value> {
+        if !signed.proposal.multisig.eq_ignore_ascii_case(&self.address) {
+            return Err(VernachainError::ValidationError(format!(
+                "proposal targets {}, not {}",
+                signed.proposal.multisig, self.address
+            )));
+        }
+        let (owners, threshold) = tokio::try_join!(self.owners(), self.threshold())?;
+        let approvals = signed.approvals(&owners);
+        if approvals < threshold {
+            return Err(VernachainError::ValidationError(format!(
+                "proposal has {} of {} required signatures",
+                approvals, threshold
+            )));
+        }
+        self.client
+            .call_contract(
+                &self.address,
+                "execute",
+                json!([signed.proposal, signed.signatures]),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    fn proposal() -> SignedProposal {
+        SignedProposal::new(MultisigProposal {
+            multisig: "0x".to_string() + &"11".repeat(20),
+            nonce: 0,
+            to: "22".repeat(32),
+            amount: 1.5,
+            data: None,
+        })
+    }
+
+    #[test]
+    fn counts_valid_owner_signatures() {
+        let (alice, bob) = (
+            SigningKey::generate(&mut OsRng),
+            SigningKey::generate(&mut OsRng),
+        );
+        let owners = [&alice, &bob].map(|key| hex::encode(key.verifying_key().as_bytes()));
+        let mut signed = proposal();
+        signed.sign(&alice).unwrap();
+        signed.sign(&alice).unwrap();
+        assert_eq!(signed.approvals(&owners), 1);
+        signed.sign(&bob).unwrap();
+        assert_eq!(signed.approvals(&owners), 2);
+        assert_eq!(signed.approvals(&owners[..1]), 1);
+    }
+
+    #[test]
+    fn rejects_signatures_with_trailing_bytes() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut signed = proposal();
+        signed.sign(&key).unwrap();
+        let mut signature = signed.signatures[0].clone();
+        signature.signature.push_str("00");
+        assert!(proposal().add_signature(signature.clone()).is_err());
+
+        signed.signatures = vec![signature];
+        assert_eq!(
+            signed.approvals(&[hex::encode(key.verifying_key().as_bytes())]),
+            0
+        );
+    }
+
+    #[test]
+    fn rejects_small_order_signers() {
+        let identity = format!("01{}", "00".repeat(31));
+        let signature = PartialSignature {
+            signer: identity.clone(),
+            signature: format!("{}{}", identity, "00".repeat(32)),
+        };
+        assert!(proposal().add_signature(signature.clone()).is_err());
+
+        let mut signed = proposal();
+        signed.signatures = vec![signature];
+        assert_eq!(signed.approvals(&[identity]), 0);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::error::{Result, VernachainError};
+use std::str::FromStr;
+
+/// Well-known Vernachain deployments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    Custom { url: String, chain_id: u64 },
+}
+
+impl Network {
+    pub fn url(&self) -> &str {
+        match self {
+            Network::Mainnet => "https://node.vernachain.com",
+            Network::Testnet => "https://testnet.node.vernachain.com",
+            Network::Devnet => "http://localhost:8000",
+            Network::Custom { url, .. } => url,
+        }
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1,
+            Network::Testnet => 2,
+            Network::Devnet => 1337,
+            Network::Custom { chain_id, .. } => *chain_id,
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = VernachainError;
+
+    /// Parses `mainnet`, `testnet` or `devnet`, ignoring case.
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            _ => Err(VernachainError::ConfigurationError(format!(
+                "unknown network {:?}",
+                name
+            ))),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Typed access to contracts deployed from the NFT template, plus off-chain
+//! metadata resolution.
+
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    types::ExtraFields,
+};
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+/// How long a metadata fetch may take in total, since gateways can stall.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(30);
+const METADATA_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftAttribute {
+    pub trait_type: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Image location with `ipfs://` already mapped to the configured gateway.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<NftAttribute>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Handle to a non-fungible token contract.
+#[derive(Clone)]
+pub struct Nft {
+    client: VernachainClient,
+    address: String,
+    gateway: String,
+    // Metadata lives off-chain, so it is fetched without the node's credentials.
+    metadata_client: HttpClient,
+}
+
+impl Nft {
+    pub fn at(client: &VernachainClient, address: &str) -> Self {
+        Self {
+            client: client.clone(),
+            address: address.to_string(),
+            gateway: DEFAULT_IPFS_GATEWAY.to_string(),
+            metadata_client: metadata_client(METADATA_TIMEOUT),
+        }
+    }
+
+    /// Limits how long a metadata fetch may take in total, 30 seconds by
+    /// default.
+    pub fn with_metadata_timeout(mut self, timeout: Duration) -> Self {
+        self.metadata_client = metadata_client(timeout);
+        self
+    }
+
+    /// Uses `gateway` (e.g. `https://cloudflare-ipfs.com/ipfs/`) for `ipfs://` URIs.
+    pub fn with_gateway(mut self, gateway: &str) -> Self {
+        self.gateway = format!("{}/", gateway.trim_end_matches('/'));
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub async fn name(&self) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "name", json!([]))
+            .await
+    }
+
+    pub async fn symbol(&self) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "symbol", json!([]))
+            .await
+    }
+
+    pub async fn total_supply(&self) -> Result<u64> {
+        self.client
+            .call_contract_as(&self.address, "total_supply", json!([]))
+            .await
+    }
+
+    pub async fn balance_of(&self, owner: &str) -> Result<u64> {
+        self.client
+            .call_contract_as(&self.address, "balance_of", json!([owner]))
+            .await
+    }
+
+    pub async fn owner_of(&self, token_id: u64) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "owner_of", json!([token_id]))
+            .await
+    }
+
+    pub async fn token_uri(&self, token_id: u64) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "token_uri", json!([token_id]))
+            .await
+    }
+
+    /// Enumeration requires the contract to implement the enumerable extension.
+    pub async fn token_by_index(&self, index: u64) -> Result<u64> {
+        self.client
+            .call_contract_as(&self.address, "token_by_index", json!([index]))
+            .await
+    }
+
+    pub async fn token_of_owner_by_index(&self, owner: &str, index: u64) -> Result<u64> {
+        self.client
+            .call_contract_as(
+                &self.address,
+                "token_of_owner_by_index",
+                json!([owner, index]),
+            )
+            .await
+    }
+
+    /// Lists every token held by `owner` via the enumerable extension.
+    pub async fn tokens_of_owner(&self, owner: &str) -> Result<Vec<u64>> {
+        let count = self.balance_of(owner).await?;
+        let mut tokens = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            tokens.push(self.token_of_owner_by_index(owner, index).await?);
+        }
+        Ok(tokens)
+    }
+
+    pub async fn transfer(&self, from: &str, to: &str, token_id: u64) -> Result<bool> {
+        self.client
+            .call_contract_as(&self.address, "transfer_from", json!([from, to, token_id]))
+            .await
+    }
+
+    pub async fn approve(&self, to: &str, token_id: u64) -> Result<bool> {
+        self.client
+            .call_contract_as(&self.address, "approve", json!([to, token_id]))
+            .await
+    }
+
+    /// Resolves the token's URI and fetches its JSON metadata document.
+    pub async fn metadata(&self, token_id: u64) -> Result<NftMetadata> {
+        let uri = self.token_uri(token_id).await?;
+        let url = self.resolve_uri(&uri);
+        let response = self.metadata_client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(VernachainError::NetworkError(format!(
+                "metadata request to {} failed with status {}",
+                url,
+                response.status()
+            )));
+        }
+
+        let mut metadata: NftMetadata = response.json().await?;
+        metadata.image = metadata.image.map(|image| self.resolve_uri(&image));
+        Ok(metadata)
+    }
+
+    /// Maps `ipfs://` URIs onto the configured HTTP gateway; other URIs are
+    /// returned unchanged.
+    pub fn resolve_uri(&self, uri: &str) -> String {
+        match uri.strip_prefix("ipfs://") {
+            Some(path) => format!(
+                "{}{}",
+                self.gateway,
+                path.trim_start_matches("ipfs/").trim_start_matches('/')
+            ),
+            None => uri.to_string(),
+        }
+    }
+}
+
+fn metadata_client(timeout: Duration) -> HttpClient {
+    HttpClient::builder()
+        .timeout(timeout)
+        .connect_timeout(METADATA_CONNECT_TIMEOUT.min(timeout))
+        .build()
+        .expect("an HTTP client with only timeouts set builds like HttpClient::new")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    async fn nft_with_metadata(server: &MockServer, metadata: ResponseTemplate) -> Nft {
+        Mock::given(method("POST"))
+            .and(path("/api/v1/contracts/0xnft/call"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!(format!("{}/meta/1", server.uri()))),
+            )
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/meta/1"))
+            .respond_with(metadata)
+            .mount(server)
+            .await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        Nft::at(&client, "0xnft")
+    }
+
+    #[tokio::test]
+    async fn fetches_metadata_and_keeps_unknown_fields() {
+        let server = MockServer::start().await;
+        let metadata = ResponseTemplate::new(200).set_body_json(json!({
+            "name": "Token 1",
+            "image": "ipfs://QmImage",
+            "animation_url": "https://example.com/1.mp4",
+        }));
+        let nft = nft_with_metadata(&server, metadata).await;
+
+        let metadata = nft.metadata(1).await.unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("Token 1"));
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://ipfs.io/ipfs/QmImage")
+        );
+        assert_eq!(
+            metadata.extra["animation_url"],
+            json!("https://example.com/1.mp4")
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_stalled_metadata_hosts() {
+        let server = MockServer::start().await;
+        let metadata = ResponseTemplate::new(200)
+            .set_body_json(json!({}))
+            .set_delay(Duration::from_secs(5));
+        let nft = nft_with_metadata(&server, metadata)
+            .await
+            .with_metadata_timeout(Duration::from_millis(200));
+
+        match nft.metadata(1).await {
+            Err(VernachainError::HttpError(e)) => assert!(e.is_timeout()),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+    }
+}
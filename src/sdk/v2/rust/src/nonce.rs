@@ -0,0 +1,153 @@
+use crate::{client::VernachainClient, error::Result};
+use std::{collections::HashMap, sync::Mutex};
+
+/// Hands out sequential nonces per sender so several transactions can be in
+/// flight at once.
+///
+/// The first reservation for an address starts from the account's confirmed
+/// nonce on the node. Invalidate an address after a failed submission or an
+/// out-of-band transaction so the next reservation resynchronizes.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    next: Mutex<HashMap<String, u64>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next unused nonce for `address` and marks it as taken.
+    pub async fn reserve(&self, client: &VernachainClient, address: &str) -> Result<u64> {
+        let key = address.to_ascii_lowercase();
+        let mut fetched = None;
+        loop {
+            {
+                let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+                // Another task may have started tracking the address while we
+                // fetched; its count is ahead of the node's.
+                if let Some(nonce) = fetched.take() {
+                    next.entry(key.clone()).or_insert(nonce);
+                }
+                if let Some(entry) = next.get_mut(&key) {
+                    let nonce = *entry;
+                    *entry += 1;
+                    return Ok(nonce);
+                }
+            }
+            // Untracked, or invalidated since the last look: ask the node.
+            fetched = Some(client.get_account(address).await?.nonce);
+        }
+    }
+
+    /// The nonce the next reservation for `address` would return, if tracked.
+    pub fn peek(&self, address: &str) -> Option<u64> {
+        self.next
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&address.to_ascii_lowercase())
+            .copied()
+    }
+
+    pub fn set(&self, address: &str, next_nonce: u64) {
+        self.next
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(address.to_ascii_lowercase(), next_nonce);
+    }
+
+    pub fn invalidate(&self, address: &str) {
+        self.next
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&address.to_ascii_lowercase());
+    }
+
+    pub fn clear(&self) {
+        self.next.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::{sync::Arc, time::Duration};
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    const ADDRESS: &str = "alice";
+
+    async fn node(nonce: u64, delay: Duration) -> (MockServer, VernachainClient) {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v1/accounts/{}", ADDRESS)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({
+                        "address": ADDRESS,
+                        "balance": 1.0,
+                        "nonce": nonce,
+                        "shard_id": 0,
+                    }))
+                    .set_delay(delay),
+            )
+            .mount(&server)
+            .await;
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn resynchronizes_after_invalidate() {
+        let (_server, client) = node(7, Duration::ZERO).await;
+        let nonces = NonceManager::new();
+        assert_eq!(nonces.reserve(&client, ADDRESS).await.unwrap(), 7);
+        assert_eq!(nonces.reserve(&client, "ALICE").await.unwrap(), 8);
+        assert_eq!(nonces.peek(ADDRESS), Some(9));
+
+        nonces.invalidate(ADDRESS);
+        assert_eq!(nonces.peek(ADDRESS), None);
+        assert_eq!(nonces.reserve(&client, ADDRESS).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn tracked_count_wins_over_a_slower_fetch() {
+        let (_server, client) = node(7, Duration::from_millis(200)).await;
+        let nonces = Arc::new(NonceManager::new());
+        let reserving = tokio::spawn({
+            let (nonces, client) = (nonces.clone(), client.clone());
+            async move { nonces.reserve(&client, ADDRESS).await.unwrap() }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        nonces.set(ADDRESS, 20);
+        assert_eq!(reserving.await.unwrap(), 20);
+        assert_eq!(nonces.peek(ADDRESS), Some(21));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn never_hands_out_zero_while_invalidated_concurrently() {
+        let (_server, client) = node(7, Duration::ZERO).await;
+        let nonces = Arc::new(NonceManager::new());
+        nonces.set(ADDRESS, 30);
+        let mut tasks = Vec::new();
+        for i in 0..200 {
+            let (nonces, client) = (nonces.clone(), client.clone());
+            tasks.push(tokio::spawn(async move {
+                if i % 3 == 0 {
+                    nonces.invalidate(ADDRESS);
+                    None
+                } else {
+                    Some(nonces.reserve(&client, ADDRESS).await.unwrap())
+                }
+            }));
+        }
+        for task in tasks {
+            if let Some(nonce) = task.await.unwrap() {
+                assert!(nonce >= 7, "reserved nonce {}", nonce);
+            }
+        }
+    }
+}
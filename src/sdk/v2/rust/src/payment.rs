@@ -0,0 +1,229 @@
+//! `vernachain:` payment request URIs, for QR codes and payment links.
+//!
+//! A request names the recipient and optionally an amount, the recipient's
+//! shard and a memo:
+//!
+//! ```text
+//! vernachain:0x3f2a...?amount=12.5&shard=1&memo=Invoice%2042
+//! ```
+//!
+//! As with BIP 21, unknown parameters are ignored unless they start with
+//! `req-`, which marks a parameter the payer must understand; such requests
+//! are rejected rather than paid without it.
+
+use crate::{
+    address,
+    error::{Result, VernachainError},
+    types::{ShardId, TransactionRequest, TransactionRequestBuilder},
+};
+use std::{collections::HashMap, fmt};
+use url::{form_urlencoded, Url};
+
+pub const SCHEME: &str = "vernachain";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentRequest {
+    pub address: String,
+    pub amount: Option<f64>,
+    pub shard_id: Option<ShardId>,
+    pub memo: Option<String>,
+}
+
+impl PaymentRequest {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            amount: None,
+            shard_id: None,
+            memo: None,
+        }
+    }
+
+    pub fn amount(mut self, amount: f64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn shard_id(mut self, shard_id: ShardId) -> Self {
+        self.shard_id = Some(shard_id);
+        self
+    }
+
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    pub fn to_uri(&self) -> String {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if let Some(amount) = self.amount {
+            query.append_pair("amount", &amount.to_string());
+        }
+        if let Some(shard_id) = self.shard_id {
+            query.append_pair("shard", &shard_id.to_string());
+        }
+        if let Some(memo) = &self.memo {
+            query.append_pair("memo", memo);
+        }
+        let query = query.finish();
+        let address = address::display(&self.address);
+        if query.is_empty() {
+            format!("{}:{}", SCHEME, address)
+        } else {
+            format!("{}:{}?{}", SCHEME, address, query)
+        }
+    }
+
+    /// Parses a payment URI, failing with `ValidationError` if it is not a
+    /// well-formed `vernachain:` request.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        let url = Url::parse(uri).map_err(|e| invalid(format!("invalid URI: {}", e)))?;
+        if url.scheme() != SCHEME {
+            return Err(invalid(format!("expected a {}: URI", SCHEME)));
+        }
+        let address = url.path();
+        if address.is_empty() || address.contains('/') {
+            return Err(invalid(format!("invalid recipient address {:?}", address)));
+        }
+        address::check(address)?;
+
+        let mut params = HashMap::new();
+        for (name, value) in url.query_pairs() {
+            if params
+                .insert(name.to_string(), value.into_owned())
+                .is_some()
+            {
+                return Err(invalid(format!("parameter {} is given twice", name)));
+            }
+        }
+        let mut request = Self::new(address);
+        for (name, value) in params {
+            match name.as_str() {
+                "amount" => {
+                    let amount: f64 = value
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid amount {:?}", value)))?;
+                    if !(amount.is_finite() && amount > 0.0) {
+                        return Err(invalid(format!("amount must be positive, got {}", value)));
+                    }
+                    request.amount = Some(amount);
+                }
+                "shard" => {
+                    let shard: u64 = value
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid shard {:?}", value)))?;
+                    request.shard_id = Some(ShardId(shard));
+                }
+                "memo" => request.memo = Some(value),
+                name if name.starts_with("req-") => {
+                    return Err(invalid(format!("unsupported required parameter {}", name)));
+                }
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+
+    /// A transaction builder paying this request from `sender`, with the memo
+    /// carried in the transaction's `data`. The amount and shard still need
+    /// setting if the request left them open.
+    pub fn to_transaction(&self, sender: impl Into<String>) -> TransactionRequestBuilder {
+        let mut builder = TransactionRequest::builder()
+            .sender(sender)
+            .recipient(self.address.clone());
+        if let Some(amount) = self.amount {
+            builder = builder.amount(amount);
+        }
+        if let Some(shard_id) = self.shard_id {
+            builder = builder.shard_id(shard_id);
+        }
+        if let Some(memo) = &self.memo {
+            builder = builder.data(HashMap::from([("memo".to_string(), memo.clone().into())]));
+        }
+        builder
+    }
+}
+
+impl fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+fn invalid(message: impl Into<String>) -> VernachainError {
+    VernachainError::ValidationError(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x5AAEb6053f3e94c9B9A09F33669435e7ef1BEaEd";
+
+    fn error(uri: &str) -> String {
+        match PaymentRequest::from_uri(uri) {
+            Err(VernachainError::ValidationError(message)) => message,
+            other => panic!("expected a validation error for {}, got {:?}", uri, other),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_a_uri() {
+        let request = PaymentRequest::new(ADDRESS.to_ascii_lowercase())
+            .amount(12.5)
+            .shard_id(ShardId(1))
+            .memo("Invoice 42 & café");
+        let uri = request.to_uri();
+        assert_eq!(
+            uri,
+            format!(
+                "vernachain:{}?amount=12.5&shard=1&memo=Invoice+42+%26+caf%C3%A9",
+                ADDRESS
+            )
+        );
+        let parsed = PaymentRequest::from_uri(&uri).unwrap();
+        assert_eq!(
+            parsed,
+            PaymentRequest {
+                address: ADDRESS.to_string(),
+                ..request
+            }
+        );
+        assert_eq!(
+            PaymentRequest::from_uri(&format!("vernachain:{}", ADDRESS)).unwrap(),
+            PaymentRequest::new(ADDRESS)
+        );
+    }
+
+    #[test]
+    fn decodes_percent_encoded_memos() {
+        let uri = format!(
+            "vernachain:{}?memo=Invoice%2042%2Fa%26b&label=shop",
+            ADDRESS
+        );
+        let request = PaymentRequest::from_uri(&uri).unwrap();
+        assert_eq!(request.memo.as_deref(), Some("Invoice 42/a&b"));
+    }
+
+    #[test]
+    fn rejects_duplicate_and_required_parameters() {
+        let uri = format!("vernachain:{}?amount=1&amount=2", ADDRESS);
+        assert!(error(&uri).contains("amount is given twice"));
+        let uri = format!("vernachain:{}?req-expires=1700000000", ADDRESS);
+        assert!(error(&uri).contains("req-expires"));
+    }
+
+    #[test]
+    fn rejects_bad_checksums() {
+        let flipped = ADDRESS.replacen('A', "a", 1);
+        error(&format!("vernachain:{}?amount=1", flipped));
+        error(&format!("bitcoin:{}", ADDRESS));
+    }
+
+    #[test]
+    fn rejects_non_positive_amounts() {
+        for amount in ["0", "-1", "NaN", "inf", "ten"] {
+            error(&format!("vernachain:{}?amount={}", ADDRESS, amount));
+        }
+    }
+}
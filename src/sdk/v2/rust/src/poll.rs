@@ -0,0 +1,58 @@
+//! Polling the node until some condition holds.
+//!
+//! The SDK's own waiters (finality, confirmations, cross-shard and bridge
+//! transfers) are built on `poll_until`, and it is public for waiting on
+//! anything else the node reports through polling, such as bridge claims.
+//! The first check runs immediately. After that the wait starts at
+//! `interval` and grows by half on every check up to four times `interval`,
+//! with jitter so many waiters started together don't poll in lockstep.
+
+use crate::error::{Result, VernachainError};
+use rand_core::{OsRng, RngCore};
+use std::{future::Future, time::Duration};
+use tokio::time::sleep;
+
+pub use tokio_util::sync::CancellationToken;
+
+const MAX_BACKOFF_FACTOR: u32 = 4;
+
+/// Calls `check` until it returns `Some`, an error, or the wait ends.
+///
+/// Errors from `check` end the wait and are returned as is. Running out of
+/// time is `TimeoutError`, and cancelling `cancel` is `Cancelled`.
+pub async fn poll_until<T, F, Fut>(
+    interval: Duration,
+    timeout: Duration,
+    cancel: &CancellationToken,
+    mut check: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>>>,
+{
+    let max_backoff = interval.saturating_mul(MAX_BACKOFF_FACTOR);
+    let poll = async {
+        let mut backoff = interval;
+        loop {
+            if let Some(value) = check().await? {
+                return Ok(value);
+            }
+            sleep(jitter(backoff)).await;
+            backoff = backoff.saturating_add(backoff / 2).min(max_backoff);
+        }
+    };
+
+    tokio::select! {
+        result = tokio::time::timeout(timeout, poll) => {
+            result.map_err(|_| VernachainError::TimeoutError)?
+        }
+        _ = cancel.cancelled() => Err(VernachainError::Cancelled),
+    }
+}
+
+/// A random wait between three quarters and all of `backoff`.
+fn jitter(backoff: Duration) -> Duration {
+    let quarter = backoff / 4;
+    let fraction = OsRng.next_u32() as f64 / u32::MAX as f64;
+    backoff - quarter.mul_f64(fraction)
+}
@@ -0,0 +1,75 @@
+//! Holdings of a set of accounts, gathered into one snapshot.
+//!
+//! Token balances are summed per contract, so the same token held by several
+//! accounts appears once in `Portfolio::tokens`.
+
+use crate::types::{Delegation, ShardId, TokenHolding};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHoldings {
+    pub address: String,
+    pub shard_id: ShardId,
+    pub balance: f64,
+    /// Sum of `delegations`.
+    pub staked: f64,
+    pub delegations: Vec<Delegation>,
+    pub pending_rewards: f64,
+    pub tokens: Vec<TokenHolding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenTotal {
+    pub contract_address: String,
+    pub symbol: String,
+    pub balance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Portfolio {
+    /// In the order the addresses were given.
+    pub accounts: Vec<AccountHoldings>,
+    pub total_balance: f64,
+    pub total_staked: f64,
+    pub total_pending_rewards: f64,
+    /// Ordered by contract address.
+    pub tokens: Vec<TokenTotal>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl Portfolio {
+    pub(crate) fn new(accounts: Vec<AccountHoldings>) -> Self {
+        let mut tokens: BTreeMap<String, TokenTotal> = BTreeMap::new();
+        for holding in accounts.iter().flat_map(|account| &account.tokens) {
+            tokens
+                .entry(holding.contract_address.to_ascii_lowercase())
+                .or_insert_with(|| TokenTotal {
+                    contract_address: holding.contract_address.clone(),
+                    symbol: holding.symbol.clone(),
+                    balance: 0.0,
+                })
+                .balance += holding.balance;
+        }
+        Self {
+            total_balance: accounts.iter().map(|account| account.balance).sum(),
+            total_staked: accounts.iter().map(|account| account.staked).sum(),
+            total_pending_rewards: accounts.iter().map(|account| account.pending_rewards).sum(),
+            tokens: tokens.into_values().collect(),
+            accounts,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    /// Native balance, stake and pending rewards together.
+    pub fn total_native(&self) -> f64 {
+        self.total_balance + self.total_staked + self.total_pending_rewards
+    }
+
+    pub fn account(&self, address: &str) -> Option<&AccountHoldings> {
+        self.accounts
+            .iter()
+            .find(|account| account.address.eq_ignore_ascii_case(address))
+    }
+}
@@ -0,0 +1,99 @@
+//! Upgradeable contracts: a proxy that keeps its address and state while the
+//! implementation contract it forwards calls to is replaced.
+//!
+//! Each implementation is a separately deployed contract, and its
+//! `SmartContract::version` says which release the proxy is running.
+
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    templates::ProxyDeploy,
+    types::{ShardId, SmartContract},
+};
+use serde_json::json;
+
+/// Handle to a deployed upgradeable proxy.
+#[derive(Clone)]
+pub struct Proxy {
+    client: VernachainClient,
+    address: String,
+}
+
+impl Proxy {
+    /// Deploys a proxy in front of `implementation`, upgradeable by `admin`.
+    pub async fn deploy(
+        client: &VernachainClient,
+        implementation: &str,
+        admin: &str,
+        shard_id: ShardId,
+    ) -> Result<Self> {
+        let contract = client
+            .deploy_template(&ProxyDeploy::new(implementation, admin), shard_id)
+            .await?;
+        Ok(Self::at(client, &contract.address))
+    }
+
+    pub fn at(client: &VernachainClient, address: &str) -> Self {
+        Self {
+            client: client.clone(),
+            address: address.to_string(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Address of the contract calls are currently forwarded to.
+    pub async fn implementation(&self) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "implementation", json!([]))
+            .await
+    }
+
+    /// The current implementation contract, including its `version`.
+    pub async fn implementation_contract(&self) -> Result<SmartContract> {
+        self.client
+            .get_contract(&self.implementation().await?)
+            .await
+    }
+
+    pub async fn admin(&self) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "admin", json!([]))
+            .await
+    }
+
+    /// Points the proxy at `new_implementation`. The node only accepts this
+    /// from the proxy's admin. Fails with `ValidationError` if the proxy
+    /// already uses that implementation or it is on another shard.
+    pub async fn upgrade_to(&self, new_implementation: &str) -> Result<serde_json::Value> {
+        let (proxy, current, target) = tokio::try_join!(
+            self.client.get_contract(&self.address),
+            self.implementation(),
+            self.client.get_contract(new_implementation),
+        )?;
+        if current.eq_ignore_ascii_case(new_implementation) {
+            return Err(VernachainError::ValidationError(format!(
+                "proxy {} already uses {}",
+                self.address, new_implementation
+            )));
+        }
+        if target.shard_id != proxy.shard_id {
+            return Err(VernachainError::ValidationError(format!(
+                "implementation {} is on shard {}, but the proxy is on shard {}",
+                new_implementation, target.shard_id, proxy.shard_id
+            )));
+        }
+        self.client
+            .call_contract(&self.address, "upgrade_to", json!([new_implementation]))
+            .await
+    }
+
+    /// Hands the right to upgrade to `new_admin`.
+    pub async fn change_admin(&self, new_admin: &str) -> Result<serde_json::Value> {
+        self.client
+            .call_contract(&self.address, "change_admin", json!([new_admin]))
+            .await
+    }
+}
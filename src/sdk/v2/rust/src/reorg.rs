@@ -0,0 +1,257 @@
+use crate::{
+    client::VernachainClient,
+    error::{Result, VernachainError},
+    types::*,
+};
+use std::collections::VecDeque;
+use tracing::warn;
+
+/// Number of recent blocks kept per subscription for fork detection.
+pub(crate) const REORG_WINDOW: usize = 128;
+
+/// Follows the parent-hash chain of a block subscription and turns branch
+/// switches into `ChainEvent::Reorg`.
+pub(crate) struct ReorgTracker {
+    shard_id: ShardId,
+    blocks: VecDeque<Block>,
+}
+
+impl ReorgTracker {
+    pub(crate) fn new(shard_id: ShardId) -> Self {
+        Self {
+            shard_id,
+            blocks: VecDeque::with_capacity(REORG_WINDOW),
+        }
+    }
+
+    /// Events for `block`: nothing if it was seen already, `NewBlock` for it
+    /// and any parents the subscription skipped, or a `Reorg` if it is on
+    /// another branch.
+    pub(crate) async fn process(
+        &mut self,
+        block: Block,
+        client: &VernachainClient,
+    ) -> Result<Vec<ChainEvent>> {
+        let tip = match self.blocks.back() {
+            Some(tip) => tip,
+            None => {
+                self.push(block.clone());
+                return Ok(vec![ChainEvent::NewBlock(block)]);
+            }
+        };
+
+        if self.blocks.iter().any(|b| b.hash == block.hash) {
+            return Ok(Vec::new());
+        }
+
+        if block.previous_hash == tip.hash {
+            self.push(block.clone());
+            return Ok(vec![ChainEvent::NewBlock(block)]);
+        }
+
+        let skipped = block.number.saturating_sub(tip.number + 1);
+        if skipped > REORG_WINDOW as u64 {
+            warn!(
+                "Block subscription on shard {} skipped {} blocks, too many to check for a reorg",
+                self.shard_id, skipped
+            );
+            self.blocks.clear();
+            self.push(block.clone());
+            return Ok(vec![ChainEvent::NewBlock(block)]);
+        }
+
+        // Walk the new block's ancestry back until it meets a block we know:
+        // the tip if blocks were only skipped, an older block on a fork.
+        let mut added = vec![block];
+        let linked = loop {
+            let head = &added[added.len() - 1];
+            if let Some(pos) = self
+                .blocks
+                .iter()
+                .position(|b| b.hash == head.previous_hash)
+            {
+                break Some(pos);
+            }
+            if head.number == 0 || head.number <= self.blocks[0].number {
+                break None;
+            }
+            let parent = client.get_block_by_hash(&head.previous_hash).await?;
+            if parent.number >= head.number {
+                return Err(VernachainError::UnexpectedResponseError(format!(
+                    "parent {} of block {} has number {}",
+                    parent.hash, head.number, parent.number
+                )));
+            }
+            added.push(parent);
+        };
+        added.reverse();
+
+        if linked == Some(self.blocks.len() - 1) {
+            for b in &added {
+                self.push(b.clone());
+            }
+            return Ok(added.into_iter().map(ChainEvent::NewBlock).collect());
+        }
+
+        let (common_ancestor, dropped) = match linked {
+            Some(pos) => (
+                Some(self.blocks[pos].clone()),
+                self.blocks.drain(pos + 1..).collect(),
+            ),
+            None => (None, self.blocks.drain(..).collect()),
+        };
+        for b in &added {
+            self.push(b.clone());
+        }
+
+        Ok(vec![ChainEvent::Reorg {
+            common_ancestor,
+            dropped,
+            added,
+        }])
+    }
+
+    fn push(&mut self, block: Block) {
+        if self.blocks.len() == REORG_WINDOW {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    /// Block `number` on `branch`, whose parent is on `parent_branch`.
+    fn block(branch: &str, number: u64, parent_branch: &str) -> Block {
+        serde_json::from_value(json!({
+            "number": number,
+            "hash": format!("{}{}", branch, number),
+            "previous_hash": format!("{}{}", parent_branch, number.saturating_sub(1)),
+            "timestamp": "2024-01-01T00:00:00Z",
+            "transactions": [],
+            "validator": "validator",
+            "shard_id": 0,
+            "merkle_root": "",
+            "state_root": "",
+        }))
+        .unwrap()
+    }
+
+    fn main_chain(to: u64) -> Vec<Block> {
+        (0..=to).map(|number| block("a", number, "a")).collect()
+    }
+
+    /// A node serving `blocks` by hash.
+    async fn node(blocks: &[Block]) -> (MockServer, VernachainClient) {
+        let server = MockServer::start().await;
+        for block in blocks {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v1/blocks/hash/{}", block.hash)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(block))
+                .mount(&server)
+                .await;
+        }
+        let client = VernachainClient::new(&server.uri(), None).unwrap();
+        (server, client)
+    }
+
+    async fn tracker(blocks: Vec<Block>, client: &VernachainClient) -> ReorgTracker {
+        let mut tracker = ReorgTracker::new(ShardId(0));
+        for block in blocks {
+            tracker.process(block, client).await.unwrap();
+        }
+        tracker
+    }
+
+    fn hashes(blocks: &[Block]) -> Vec<&str> {
+        blocks.iter().map(|b| b.hash.as_str()).collect()
+    }
+
+    fn new_blocks(events: &[ChainEvent]) -> Vec<&str> {
+        events
+            .iter()
+            .map(|event| match event {
+                ChainEvent::NewBlock(block) => block.hash.as_str(),
+                other => panic!("expected a new block, got {:?}", other),
+            })
+            .collect()
+    }
+
+    fn reorg(events: &[ChainEvent]) -> (Option<&str>, Vec<&str>, Vec<&str>) {
+        match events {
+            [ChainEvent::Reorg {
+                common_ancestor,
+                dropped,
+                added,
+            }] => (
+                common_ancestor.as_ref().map(|b| b.hash.as_str()),
+                hashes(dropped),
+                hashes(added),
+            ),
+            other => panic!("expected one reorg, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn passes_on_a_linear_chain() {
+        let (_server, client) = node(&[]).await;
+        let mut tracker = ReorgTracker::new(ShardId(0));
+        for block in main_chain(2) {
+            let hash = block.hash.clone();
+            let events = tracker.process(block, &client).await.unwrap();
+            assert_eq!(new_blocks(&events), [hash.as_str()]);
+        }
+        let repeated = tracker.process(block("a", 2, "a"), &client).await.unwrap();
+        assert!(repeated.is_empty());
+    }
+
+    #[tokio::test]
+    async fn fills_skipped_blocks() {
+        let chain = main_chain(4);
+        let (_server, client) = node(&chain).await;
+        let mut tracker = tracker(chain[..2].to_vec(), &client).await;
+        let events = tracker.process(chain[4].clone(), &client).await.unwrap();
+        assert_eq!(new_blocks(&events), ["a2", "a3", "a4"]);
+    }
+
+    #[tokio::test]
+    async fn reports_a_fork_at_the_tip() {
+        let (_server, client) = node(&[]).await;
+        let mut tracker = tracker(main_chain(2), &client).await;
+        let events = tracker.process(block("b", 2, "a"), &client).await.unwrap();
+        assert_eq!(reorg(&events), (Some("a1"), vec!["a2"], vec!["b2"]));
+    }
+
+    #[tokio::test]
+    async fn reports_a_deep_fork() {
+        let (_server, client) = node(&[block("b", 2, "a"), block("b", 3, "b")]).await;
+        let mut tracker = tracker(main_chain(3), &client).await;
+        let events = tracker.process(block("b", 4, "b"), &client).await.unwrap();
+        assert_eq!(
+            reorg(&events),
+            (Some("a1"), vec!["a2", "a3"], vec!["b2", "b3", "b4"])
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_a_fork_behind_skipped_blocks() {
+        let fork = [block("b", 2, "a"), block("b", 3, "b"), block("b", 4, "b")];
+        let (_server, client) = node(&fork).await;
+        let mut tracker = tracker(main_chain(2), &client).await;
+        let events = tracker.process(block("b", 5, "b"), &client).await.unwrap();
+        assert_eq!(
+            reorg(&events),
+            (Some("a1"), vec!["a2"], vec!["b2", "b3", "b4", "b5"])
+        );
+
+        let next = tracker.process(block("b", 6, "b"), &client).await.unwrap();
+        assert_eq!(new_blocks(&next), ["b6"]);
+    }
+}
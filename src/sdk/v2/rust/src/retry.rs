@@ -0,0 +1,101 @@
+//! Retrying calls that fail transiently.
+//!
+//! Rate limits, unavailable nodes and connections that could not be opened are
+//! retried for every call, since the node never acted on them. Timeouts, dropped
+//! connections and other server errors may have reached the node, so only
+//! reads are retried after those, and a transaction is never submitted twice.
+//! Which errors are transient is `VernachainError::category`'s call. Each
+//! retry waits twice as long as the one before, up to `max_backoff`.
+
+use crate::error::{Result, VernachainError};
+use std::{future::Future, time::Duration};
+use tokio::time::sleep;
+use tracing::warn;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Retries a failed call up to `max_retries` times.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Never retries. This is what clients use unless configured otherwise.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Wait before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Longest wait between two attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Runs `call`, an HTTP `method` call, retrying it as the policy allows.
+    pub(crate) async fn run<T, F, Fut>(&self, method: &str, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match call().await {
+                Err(error) if retry < self.max_retries && should_retry(method, &error) => {
+                    let backoff = self.backoff(retry);
+                    warn!("Retrying in {:?} after: {}", backoff, error);
+                    sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RETRIES)
+    }
+}
+
+/// Whether `error` from an HTTP `method` call is retried: transient errors
+/// are for reads, and for writes only when the node can't have acted.
+pub fn should_retry(method: &str, error: &VernachainError) -> bool {
+    let read = method.eq_ignore_ascii_case("GET");
+    error.is_retryable() && (read || never_reached_node(error))
+}
+
+fn never_reached_node(error: &VernachainError) -> bool {
+    match error {
+        VernachainError::RateLimitError => true,
+        VernachainError::Api { status, .. } => *status == 503,
+        VernachainError::HttpError(e) => e.is_connect(),
+        _ => false,
+    }
+}
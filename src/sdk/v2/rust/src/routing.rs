@@ -0,0 +1,63 @@
+use crate::{client::VernachainClient, error::Result, types::ShardId};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::RwLock};
+
+#[derive(Deserialize)]
+struct ShardLookup {
+    shard_id: ShardId,
+}
+
+/// Resolves which shard an address lives on, caching answers from the node.
+///
+/// Shard assignment is stable for an address, so entries are kept until
+/// explicitly invalidated.
+#[derive(Debug, Default)]
+pub struct AddressRouter {
+    cache: RwLock<HashMap<String, ShardId>>,
+}
+
+impl AddressRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn resolve(&self, client: &VernachainClient, address: &str) -> Result<ShardId> {
+        let key = address.to_ascii_lowercase();
+        if let Some(shard_id) = self.cached(&key) {
+            return Ok(shard_id);
+        }
+
+        let lookup: ShardLookup = client.get(&format!("/accounts/{}/shard", address)).await?;
+        self.insert(&key, lookup.shard_id);
+        Ok(lookup.shard_id)
+    }
+
+    pub fn cached(&self, address: &str) -> Option<ShardId> {
+        self.cache
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&address.to_ascii_lowercase())
+            .copied()
+    }
+
+    pub fn insert(&self, address: &str, shard_id: ShardId) {
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(address.to_ascii_lowercase(), shard_id);
+    }
+
+    pub fn invalidate(&self, address: &str) {
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&address.to_ascii_lowercase());
+    }
+
+    pub fn clear(&self) {
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
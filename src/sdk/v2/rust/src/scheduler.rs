@@ -0,0 +1,210 @@
+//! Delayed submission of transactions at a block height or a point in time.
+//!
+//! Requests are held unsubmitted until their trigger fires. At submission the
+//! nonce is reserved fresh and any pinned gas price is replaced with the
+//! node's current price, so transactions scheduled long in advance don't go
+//! out stale.
+
+use crate::{
+    client::VernachainClient,
+    error::Result,
+    types::{ShardId, Transaction, TransactionRequest},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{sync::broadcast, time::sleep};
+use tracing::error;
+
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Trigger {
+    /// Once the shard's latest block reaches `number`.
+    BlockHeight {
+        shard_id: ShardId,
+        number: u64,
+    },
+    At {
+        time: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTransaction {
+    pub id: u64,
+    pub trigger: Trigger,
+    pub request: TransactionRequest,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledOutcome {
+    pub id: u64,
+    /// The submitted transaction, or why submission failed.
+    pub result: std::result::Result<Transaction, String>,
+}
+
+pub struct Scheduler {
+    /// Detached, so a spawned scheduler stops with the client instead of
+    /// keeping it alive.
+    client: VernachainClient,
+    pending: Mutex<Vec<ScheduledTransaction>>,
+    next_id: AtomicU64,
+    poll_interval: Duration,
+}
+
+impl Scheduler {
+    pub fn new(client: &VernachainClient) -> Self {
+        Self {
+            client: client.detached(),
+            pending: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(1),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Queues `request` and returns an id for cancelling it.
+    pub fn schedule(&self, request: TransactionRequest, trigger: Trigger) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.lock().push(ScheduledTransaction {
+            id,
+            trigger,
+            request,
+        });
+        id
+    }
+
+    /// Removes a transaction that has not been submitted yet.
+    pub fn cancel(&self, id: u64) -> Option<TransactionRequest> {
+        let mut pending = self.lock();
+        let index = pending.iter().position(|item| item.id == id)?;
+        Some(pending.remove(index).request)
+    }
+
+    pub fn pending(&self) -> Vec<ScheduledTransaction> {
+        self.lock().clone()
+    }
+
+    /// Submits every transaction whose trigger has fired and reports the
+    /// results. Failed submissions are not retried.
+    pub async fn tick(&self) -> Vec<ScheduledOutcome> {
+        let due = self.take_due().await;
+        let mut outcomes = Vec::with_capacity(due.len());
+        for item in due {
+            let result = self.submit(item.request).await;
+            outcomes.push(ScheduledOutcome {
+                id: item.id,
+                result: result.map_err(|e| e.to_string()),
+            });
+        }
+        outcomes
+    }
+
+    /// Ticks in the background until every receiver has been dropped or the
+    /// client is shut down or dropped. A tick in progress is finished first,
+    /// so no transaction is left half submitted, and `shutdown` waits for it.
+    pub fn spawn(self: Arc<Self>) -> broadcast::Receiver<ScheduledOutcome> {
+        let (tx, rx) = broadcast::channel(100);
+        let tasks = self.client.task_tracker().clone();
+        let shutdown = self.client.shutdown_token().clone();
+        tasks.spawn(async move {
+            while !shutdown.is_cancelled() {
+                for outcome in self.tick().await {
+                    if tx.send(outcome).is_err() {
+                        return;
+                    }
+                }
+                if tx.receiver_count() == 0 {
+                    return;
+                }
+                tokio::select! {
+                    _ = sleep(self.poll_interval) => {}
+                    _ = shutdown.cancelled() => return,
+                }
+            }
+        });
+        rx
+    }
+
+    async fn take_due(&self) -> Vec<ScheduledTransaction> {
+        let shards: Vec<ShardId> = {
+            let mut shards: Vec<ShardId> = self
+                .lock()
+                .iter()
+                .filter_map(|item| match item.trigger {
+                    Trigger::BlockHeight { shard_id, .. } => Some(shard_id),
+                    Trigger::At { .. } => None,
+                })
+                .collect();
+            shards.sort_unstable();
+            shards.dedup();
+            shards
+        };
+        let mut heights = HashMap::new();
+        for shard_id in shards {
+            match self.client.get_latest_block(shard_id).await {
+                Ok(block) => {
+                    heights.insert(shard_id, block.number);
+                }
+                Err(e) => error!("Failed to fetch latest block on shard {}: {}", shard_id, e),
+            }
+        }
+
+        let now = Utc::now();
+        let is_due = |trigger: &Trigger| match trigger {
+            Trigger::BlockHeight { shard_id, number } => {
+                heights.get(shard_id).is_some_and(|height| height >= number)
+            }
+            Trigger::At { time } => now >= *time,
+        };
+        let mut pending = self.lock();
+        let (due, waiting) = pending.drain(..).partition(|item| is_due(&item.trigger));
+        *pending = waiting;
+        due
+    }
+
+    async fn submit(&self, mut request: TransactionRequest) -> Result<Transaction> {
+        request.nonce = None;
+        if request.gas_price.is_some() {
+            request.gas_price = Some(self.client.get_gas_price().await?);
+        }
+        self.client.create_transaction(request).await
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<ScheduledTransaction>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn spawned_scheduler_stops_with_the_client() {
+        let client = VernachainClient::new("http://127.0.0.1:1", None).unwrap();
+        let scheduler =
+            Arc::new(Scheduler::new(&client).with_poll_interval(Duration::from_secs(3600)));
+        let mut outcomes = scheduler.clone().spawn();
+
+        drop(client);
+        let stopped = tokio::time::timeout(Duration::from_secs(5), outcomes.recv()).await;
+        assert!(matches!(
+            stopped,
+            Ok(Err(broadcast::error::RecvError::Closed))
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+//! Offline snapshots of chain segments.
+//!
+//! A snapshot is a 16-byte header (`VNSNAP`, format version, encoding byte,
+//! shard id as big-endian u64) followed by one frame per block: a big-endian
+//! u32 length and the encoded `SnapshotBlock`.
+
+use crate::{
+    error::{Result, VernachainError},
+    types::{Block, Log, ShardId},
+};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const MAGIC: &[u8; 6] = b"VNSNAP";
+const VERSION: u8 = 1;
+/// Upper bound on a single frame, so a corrupt length cannot exhaust memory.
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Cbor,
+}
+
+impl SnapshotFormat {
+    fn tag(self) -> u8 {
+        match self {
+            SnapshotFormat::Json => 0,
+            SnapshotFormat::Cbor => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(SnapshotFormat::Json),
+            1 => Ok(SnapshotFormat::Cbor),
+            other => Err(invalid(format!("unknown encoding {}", other))),
+        }
+    }
+}
+
+/// One block together with the logs it emitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotBlock {
+    pub block: Block,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+}
+
+pub struct SnapshotWriter<W> {
+    writer: W,
+    format: SnapshotFormat,
+}
+
+impl<W: AsyncWrite + Unpin> SnapshotWriter<W> {
+    pub async fn new(mut writer: W, shard_id: ShardId, format: SnapshotFormat) -> Result<Self> {
+        writer.write_all(MAGIC).await?;
+        writer.write_all(&[VERSION, format.tag()]).await?;
+        writer.write_all(&shard_id.get().to_be_bytes()).await?;
+        Ok(Self { writer, format })
+    }
+
+    pub async fn write(&mut self, entry: &SnapshotBlock) -> Result<()> {
+        let payload = match self.format {
+            SnapshotFormat::Json => serde_json::to_vec(entry)?,
+            SnapshotFormat::Cbor => {
+                let mut out = Vec::new();
+                ciborium::into_writer(entry, &mut out)
+                    .map_err(|e| VernachainError::InternalError(e.to_string()))?;
+                out
+            }
+        };
+        if payload.len() > MAX_FRAME_LEN {
+            return Err(invalid(format!(
+                "block {} is too large",
+                entry.block.number
+            )));
+        }
+        self.writer
+            .write_all(&(payload.len() as u32).to_be_bytes())
+            .await?;
+        self.writer.write_all(&payload).await?;
+        Ok(())
+    }
+
+    /// Flushes buffered frames and returns the underlying writer.
+    pub async fn finish(mut self) -> Result<W> {
+        self.writer.flush().await?;
+        Ok(self.writer)
+    }
+}
+
+pub struct SnapshotReader<R> {
+    reader: R,
+    format: SnapshotFormat,
+    shard_id: ShardId,
+}
+
+impl<R: AsyncRead + Unpin> SnapshotReader<R> {
+    pub async fn new(mut reader: R) -> Result<Self> {
+        let mut header = [0u8; 16];
+        reader.read_exact(&mut header).await?;
+        if &header[..6] != MAGIC {
+            return Err(invalid("not a Vernachain snapshot".to_string()));
+        }
+        if header[6] != VERSION {
+            return Err(invalid(format!("unsupported version {}", header[6])));
+        }
+        let format = SnapshotFormat::from_tag(header[7])?;
+        let shard_id = ShardId(u64::from_be_bytes(header[8..].try_into().unwrap()));
+        Ok(Self {
+            reader,
+            format,
+            shard_id,
+        })
+    }
+
+    pub fn shard_id(&self) -> ShardId {
+        self.shard_id
+    }
+
+    pub fn format(&self) -> SnapshotFormat {
+        self.format
+    }
+
+    /// Reads the next block, or `None` at a clean end of the snapshot. A
+    /// snapshot cut off inside a frame is invalid.
+    pub async fn next(&mut self) -> Result<Option<SnapshotBlock>> {
+        let mut len = [0u8; 4];
+        let mut filled = 0;
+        while filled < len.len() {
+            match self.reader.read(&mut len[filled..]).await? {
+                0 if filled == 0 => return Ok(None),
+                0 => {
+                    return Err(invalid(format!(
+                        "truncated frame length ({} of 4 bytes)",
+                        filled
+                    )))
+                }
+                read => filled += read,
+            }
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME_LEN {
+            return Err(invalid(format!("frame of {} bytes exceeds limit", len)));
+        }
+        let mut payload = vec![0u8; len];
+        match self.reader.read_exact(&mut payload).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Err(invalid(format!("truncated frame of {} bytes", len)))
+            }
+            Err(e) => return Err(e.into()),
+        }
+        let entry = match self.format {
+            SnapshotFormat::Json => serde_json::from_slice(&payload)?,
+            SnapshotFormat::Cbor => {
+                ciborium::from_reader(payload.as_slice()).map_err(|e| invalid(e.to_string()))?
+            }
+        };
+        Ok(Some(entry))
+    }
+}
+
+fn invalid(message: String) -> VernachainError {
+    VernachainError::ValidationError(format!("invalid snapshot: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    async fn snapshot(format: SnapshotFormat) -> Vec<u8> {
+        let block: Block = serde_json::from_value(json!({
+            "number": 7,
+            "hash": "hash7",
+            "previous_hash": "hash6",
+            "timestamp": "2024-01-01T00:00:00Z",
+            "transactions": [],
+            "validator": "validator",
+            "shard_id": 2,
+            "merkle_root": "",
+            "state_root": "",
+        }))
+        .unwrap();
+        let mut writer = SnapshotWriter::new(Vec::new(), ShardId(2), format)
+            .await
+            .unwrap();
+        writer
+            .write(&SnapshotBlock {
+                block,
+                logs: Vec::new(),
+            })
+            .await
+            .unwrap();
+        writer.finish().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_trips_blocks() {
+        for format in [SnapshotFormat::Json, SnapshotFormat::Cbor] {
+            let bytes = snapshot(format).await;
+            let mut reader = SnapshotReader::new(bytes.as_slice()).await.unwrap();
+            assert_eq!(reader.shard_id(), ShardId(2));
+            assert_eq!(reader.format(), format);
+            assert_eq!(reader.next().await.unwrap().unwrap().block.number, 7);
+            assert!(reader.next().await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_truncated_frame_length() {
+        let mut bytes = snapshot(SnapshotFormat::Json).await;
+        bytes.extend_from_slice(&[0, 0]);
+        let mut reader = SnapshotReader::new(bytes.as_slice()).await.unwrap();
+        assert!(reader.next().await.unwrap().is_some());
+        assert!(matches!(
+            reader.next().await,
+            Err(VernachainError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_truncated_frame() {
+        let mut bytes = snapshot(SnapshotFormat::Cbor).await;
+        bytes.truncate(bytes.len() - 1);
+        let mut reader = SnapshotReader::new(bytes.as_slice()).await.unwrap();
+        assert!(matches!(
+            reader.next().await,
+            Err(VernachainError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+//! Fee delegation: a sponsor co-signs a user's transaction and pays its gas.
+//!
+//! The sponsor signs the SHA-256 digest of the request's sorted-key JSON with
+//! the `sponsor` field left out, so the shard and nonce must be settled before
+//! signing.
+
+use crate::{
+    client::VernachainClient,
+    codec,
+    error::{Result, VernachainError},
+    keys::PublicKey,
+    types::{Sponsorship, TransactionRequest},
+};
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+
+pub struct SponsoredTransactionBuilder {
+    request: TransactionRequest,
+    max_fee: Option<f64>,
+}
+
+impl SponsoredTransactionBuilder {
+    pub fn new(request: TransactionRequest) -> Self {
+        Self {
+            request,
+            max_fee: None,
+        }
+    }
+
+    /// Fills in the shard and reserves a nonce, as `create_transaction` would,
+    /// so the request is final before the sponsor signs it.
+    pub async fn prepare(
+        client: &VernachainClient,
+        mut request: TransactionRequest,
+    ) -> Result<Self> {
+        if request.shard_id.is_none() {
+            request.shard_id = Some(client.resolve_shard(&request.sender).await?);
+        }
+        if request.nonce.is_none() {
+            request.nonce = Some(client.nonces().reserve(client, &request.sender).await?);
+        }
+        Ok(Self::new(request))
+    }
+
+    /// Caps the fee the sponsor agrees to pay.
+    pub fn max_fee(mut self, max_fee: f64) -> Self {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Signs as the sponsor and returns the request ready for
+    /// `submit_sponsored_transaction`.
+    pub fn sign(mut self, sponsor_key: &SigningKey) -> Result<TransactionRequest> {
+        if self.request.shard_id.is_none() || self.request.nonce.is_none() {
+            return Err(VernachainError::ValidationError(
+                "shard_id and nonce must be set before sponsoring".to_string(),
+            ));
+        }
+        self.request.sponsor = None;
+        let digest = sponsorship_digest(&self.request, self.max_fee)?;
+        self.request.sponsor = Some(Sponsorship {
+            sponsor: hex::encode(sponsor_key.verifying_key().as_bytes()),
+            max_fee: self.max_fee,
+            signature: hex::encode(sponsor_key.sign(digest.as_bytes()).to_bytes()),
+        });
+        Ok(self.request)
+    }
+}
+
+/// Checks the sponsor's signature on a request, under the strict check of
+/// `PublicKey::verify`.
+pub fn verify_sponsorship(request: &TransactionRequest) -> bool {
+    let Some(sponsorship) = &request.sponsor else {
+        return false;
+    };
+    let mut unsigned = request.clone();
+    unsigned.sponsor = None;
+    let Ok(digest) = sponsorship_digest(&unsigned, sponsorship.max_fee) else {
+        return false;
+    };
+    PublicKey::from_hex(&sponsorship.sponsor)
+        .is_ok_and(|key| key.verify(digest.as_bytes(), &sponsorship.signature))
+}
+
+fn sponsorship_digest(request: &TransactionRequest, max_fee: Option<f64>) -> Result<String> {
+    let payload = serde_json::json!({ "transaction": request, "max_fee": max_fee });
+    let encoded = codec::canonical_json(&payload)?;
+    Ok(hex::encode(Sha256::digest(encoded)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ShardId;
+    use rand_core::OsRng;
+
+    fn sponsored(key: &SigningKey) -> TransactionRequest {
+        let request = TransactionRequest::builder()
+            .sender("11".repeat(32))
+            .recipient("22".repeat(32))
+            .amount(1.0)
+            .shard_id(ShardId(0))
+            .nonce(4)
+            .build()
+            .unwrap();
+        SponsoredTransactionBuilder::new(request)
+            .max_fee(0.5)
+            .sign(key)
+            .unwrap()
+    }
+
+    #[test]
+    fn verifies_the_sponsor_signature() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut request = sponsored(&key);
+        assert!(verify_sponsorship(&request));
+
+        request.amount = 2.0;
+        assert!(!verify_sponsorship(&request));
+    }
+
+    #[test]
+    fn rejects_a_raised_max_fee() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut request = sponsored(&key);
+        if let Some(sponsorship) = &mut request.sponsor {
+            sponsorship.max_fee = Some(5.0);
+        }
+        assert!(!verify_sponsorship(&request));
+    }
+
+    #[test]
+    fn rejects_a_different_sponsor() {
+        let key = SigningKey::generate(&mut OsRng);
+        let other = SigningKey::generate(&mut OsRng);
+        let mut request = sponsored(&key);
+        if let Some(sponsorship) = &mut request.sponsor {
+            sponsorship.sponsor = hex::encode(other.verifying_key().as_bytes());
+        }
+        assert!(!verify_sponsorship(&request));
+    }
+
+    #[test]
+    fn rejects_small_order_sponsors() {
+        let key = SigningKey::generate(&mut OsRng);
+        let mut request = sponsored(&key);
+        let identity = format!("01{}", "00".repeat(31));
+        if let Some(sponsorship) = &mut request.sponsor {
+            sponsorship.signature = format!("{}{}", identity, "00".repeat(32));
+            sponsorship.sponsor = identity;
+        }
+        assert!(!verify_sponsorship(&request));
+    }
+}
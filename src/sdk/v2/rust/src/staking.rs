@@ -0,0 +1,50 @@
+//! Offline staking projections.
+
+use crate::types::{StakingParams, Validator};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RewardEstimate {
+    /// Rewards earned by the stake before the validator's commission.
+    pub gross_rewards: f64,
+    pub commission: f64,
+    pub net_rewards: f64,
+    /// Annualized net return implied by the parameters.
+    pub net_apr: f64,
+}
+
+/// Projects the rewards `stake` would earn with `validator` over `epochs`,
+/// using parameters fetched once with `VernachainClient::get_staking_params`.
+///
+/// The projection assumes parameters stay constant, rewards are not
+/// compounded, and the validator stays active for the whole period.
+pub fn estimate_rewards(
+    stake: f64,
+    validator: &Validator,
+    epochs: u64,
+    params: &StakingParams,
+) -> RewardEstimate {
+    if !validator.is_active
+        || stake <= 0.0
+        || params.total_staked <= 0.0
+        || params.epochs_per_year == 0
+    {
+        return RewardEstimate {
+            gross_rewards: 0.0,
+            commission: 0.0,
+            net_rewards: 0.0,
+            net_apr: 0.0,
+        };
+    }
+
+    let gross_apr = params.annual_inflation_rate * params.total_supply / params.total_staked;
+    let commission_rate = validator.commission_rate.unwrap_or(0.0).clamp(0.0, 1.0);
+    let gross_rewards = stake * gross_apr * epochs as f64 / params.epochs_per_year as f64;
+    let commission = gross_rewards * commission_rate;
+
+    RewardEstimate {
+        gross_rewards,
+        commission,
+        net_rewards: gross_rewards - commission,
+        net_apr: gross_apr * (1.0 - commission_rate),
+    }
+}
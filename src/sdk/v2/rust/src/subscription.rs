@@ -0,0 +1,47 @@
+//! Receiving end of WebSocket subscriptions.
+//!
+//! Subscription messages are buffered per receiver. A consumer that falls so
+//! far behind that the buffer overflows loses the oldest messages; instead of
+//! dropping them silently, the next `recv` reports how many were lost, so it
+//! can backfill from the HTTP API.
+
+use tokio::sync::broadcast::{self, error::RecvError};
+
+#[derive(Debug, Clone)]
+pub enum SubscriptionEvent<T> {
+    Message(T),
+    /// This many messages were dropped before reaching the consumer, either
+    /// because it was too slow or because a subscription is built on top of
+    /// another that lagged (`watch_address` counts missed blocks).
+    MissedMessages(u64),
+}
+
+pub struct Subscription<T> {
+    receiver: broadcast::Receiver<SubscriptionEvent<T>>,
+}
+
+impl<T: Clone> Subscription<T> {
+    pub(crate) fn new(receiver: broadcast::Receiver<SubscriptionEvent<T>>) -> Self {
+        Self { receiver }
+    }
+
+    /// Waits for the next event. Returns `None` once the subscription has
+    /// ended and every buffered message has been received.
+    pub async fn recv(&mut self) -> Option<SubscriptionEvent<T>> {
+        match self.receiver.recv().await {
+            Ok(event) => Some(event),
+            Err(RecvError::Lagged(skipped)) => Some(SubscriptionEvent::MissedMessages(skipped)),
+            Err(RecvError::Closed) => None,
+        }
+    }
+
+    /// Waits for the next message, skipping over gaps.
+    pub async fn next_message(&mut self) -> Option<T> {
+        loop {
+            match self.recv().await? {
+                SubscriptionEvent::Message(message) => return Some(message),
+                SubscriptionEvent::MissedMessages(_) => continue,
+            }
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Typed constructor parameters for the node's built-in contract templates.
+//!
+//! Each template validates its parameters locally and produces the
+//! `ContractDeployRequest` that `VernachainClient::deploy_contract` expects.
+
+use crate::{
+    error::{Result, VernachainError},
+    types::{ContractDeployRequest, ContractType, ShardId},
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+pub trait ContractTemplate {
+    fn contract_type(&self) -> ContractType;
+
+    fn validate(&self) -> Result<()>;
+
+    fn params(&self) -> HashMap<String, Value>;
+
+    fn to_request(&self, shard_id: ShardId) -> Result<ContractDeployRequest> {
+        self.validate()?;
+        Ok(ContractDeployRequest {
+            contract_type: self.contract_type(),
+            params: self.params(),
+            shard_id,
+            gas_limit: None,
+            salt: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenDeploy {
+    pub name: String,
+    pub symbol: String,
+    pub total_supply: f64,
+}
+
+impl TokenDeploy {
+    pub fn new(name: &str, symbol: &str, total_supply: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            total_supply,
+        }
+    }
+}
+
+impl ContractTemplate for TokenDeploy {
+    fn contract_type(&self) -> ContractType {
+        ContractType::Token
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_name_and_symbol(&self.name, &self.symbol)?;
+        if !self.total_supply.is_finite() || self.total_supply <= 0.0 {
+            return Err(invalid("total supply must be a positive number"));
+        }
+        Ok(())
+    }
+
+    fn params(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            ("name".to_string(), json!(self.name)),
+            ("symbol".to_string(), json!(self.symbol)),
+            ("total_supply".to_string(), json!(self.total_supply)),
+        ])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NftDeploy {
+    pub name: String,
+    pub symbol: String,
+    pub base_uri: String,
+}
+
+impl NftDeploy {
+    pub fn new(name: &str, symbol: &str, base_uri: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            base_uri: base_uri.to_string(),
+        }
+    }
+}
+
+impl ContractTemplate for NftDeploy {
+    fn contract_type(&self) -> ContractType {
+        ContractType::Nft
+    }
+
+    fn validate(&self) -> Result<()> {
+        check_name_and_symbol(&self.name, &self.symbol)?;
+        if self.base_uri.trim().is_empty() {
+            return Err(invalid("base URI must not be empty"));
+        }
+        Ok(())
+    }
+
+    fn params(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            ("name".to_string(), json!(self.name)),
+            ("symbol".to_string(), json!(self.symbol)),
+            ("base_uri".to_string(), json!(self.base_uri)),
+        ])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MultisigDeploy {
+    pub owners: Vec<String>,
+    pub threshold: usize,
+}
+
+impl MultisigDeploy {
+    pub fn new(owners: Vec<String>, threshold: usize) -> Self {
+        Self { owners, threshold }
+    }
+}
+
+impl ContractTemplate for MultisigDeploy {
+    fn contract_type(&self) -> ContractType {
+        ContractType::Multisig
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.owners.is_empty() {
+            return Err(invalid("a multisig needs at least one owner"));
+        }
+        let unique: HashSet<String> = self
+            .owners
+            .iter()
+            .map(|owner| owner.to_ascii_lowercase())
+            .collect();
+        if unique.len() != self.owners.len() {
+            return Err(invalid("multisig owners must be unique"));
+        }
+        if self.threshold == 0 || self.threshold > self.owners.len() {
+            return Err(invalid(&format!(
+                "threshold must be between 1 and {}, got {}",
+                self.owners.len(),
+                self.threshold
+            )));
+        }
+        Ok(())
+    }
+
+    fn params(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            ("owners".to_string(), json!(self.owners)),
+            ("threshold".to_string(), json!(self.threshold)),
+        ])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProxyDeploy {
+    /// The contract calls are forwarded to until the first upgrade.
+    pub implementation: String,
+    /// The only account allowed to upgrade the proxy.
+    pub admin: String,
+}
+
+impl ProxyDeploy {
+    pub fn new(implementation: &str, admin: &str) -> Self {
+        Self {
+            implementation: implementation.to_string(),
+            admin: admin.to_string(),
+        }
+    }
+}
+
+impl ContractTemplate for ProxyDeploy {
+    fn contract_type(&self) -> ContractType {
+        ContractType::Proxy
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.implementation.trim().is_empty() {
+            return Err(invalid("implementation address must not be empty"));
+        }
+        if self.admin.trim().is_empty() {
+            return Err(invalid("admin address must not be empty"));
+        }
+        Ok(())
+    }
+
+    fn params(&self) -> HashMap<String, Value> {
+        HashMap::from([
+            ("implementation".to_string(), json!(self.implementation)),
+            ("admin".to_string(), json!(self.admin)),
+        ])
+    }
+}
+
+fn check_name_and_symbol(name: &str, symbol: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(invalid("name must not be empty"));
+    }
+    if symbol.is_empty() || symbol.len() > 11 || !symbol.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(invalid("symbol must be 1-11 ASCII letters or digits"));
+    }
+    Ok(())
+}
+
+fn invalid(message: &str) -> VernachainError {
+    VernachainError::ValidationError(message.to_string())
+}
@@ -0,0 +1,89 @@
+//! Typed access to contracts deployed from the standard token template.
+
+use crate::{client::VernachainClient, error::Result};
+use serde_json::json;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub total_supply: f64,
+}
+
+/// Handle to a fungible token contract.
+#[derive(Clone)]
+pub struct Token {
+    client: VernachainClient,
+    address: String,
+}
+
+impl Token {
+    pub fn at(client: &VernachainClient, address: &str) -> Self {
+        Self {
+            client: client.clone(),
+            address: address.to_string(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub async fn name(&self) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "name", json!([]))
+            .await
+    }
+
+    pub async fn symbol(&self) -> Result<String> {
+        self.client
+            .call_contract_as(&self.address, "symbol", json!([]))
+            .await
+    }
+
+    pub async fn total_supply(&self) -> Result<f64> {
+        self.client
+            .call_contract_as(&self.address, "total_supply", json!([]))
+            .await
+    }
+
+    pub async fn metadata(&self) -> Result<TokenMetadata> {
+        let (name, symbol, total_supply) =
+            tokio::try_join!(self.name(), self.symbol(), self.total_supply())?;
+        Ok(TokenMetadata {
+            name,
+            symbol,
+            total_supply,
+        })
+    }
+
+    pub async fn balance_of(&self, owner: &str) -> Result<f64> {
+        self.client
+            .call_contract_as(&self.address, "balance_of", json!([owner]))
+            .await
+    }
+
+    pub async fn allowance(&self, owner: &str, spender: &str) -> Result<f64> {
+        self.client
+            .call_contract_as(&self.address, "allowance", json!([owner, spender]))
+            .await
+    }
+
+    pub async fn transfer(&self, to: &str, amount: f64) -> Result<bool> {
+        self.client
+            .call_contract_as(&self.address, "transfer", json!([to, amount]))
+            .await
+    }
+
+    pub async fn approve(&self, spender: &str, amount: f64) -> Result<bool> {
+        self.client
+            .call_contract_as(&self.address, "approve", json!([spender, amount]))
+            .await
+    }
+
+    pub async fn transfer_from(&self, from: &str, to: &str, amount: f64) -> Result<bool> {
+        self.client
+            .call_contract_as(&self.address, "transfer_from", json!([from, to, amount]))
+            .await
+    }
+}
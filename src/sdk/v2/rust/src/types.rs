@@ -1,6 +1,313 @@
+use crate::codec;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// Response fields without a counterpart in the SDK's types, by name, such as
+/// ones added by a newer node. Every response struct keeps them in its
+/// `extra` field; see the `decode` module.
+pub type ExtraFields = HashMap<String, serde_json::Value>;
+
+/// A shard of the chain. Sent as a bare number on the wire.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct ShardId(pub u64);
+
+impl ShardId {
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ShardId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ShardId> for u64 {
+    fn from(id: ShardId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for ShardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A chain the bridge connects to, by the name the node uses for it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChainId(Cow<'static, str>);
+
+impl ChainId {
+    pub const VERNACHAIN: ChainId = ChainId(Cow::Borrowed("vernachain"));
+    pub const ETHEREUM: ChainId = ChainId(Cow::Borrowed("ethereum"));
+    pub const BINANCE: ChainId = ChainId(Cow::Borrowed("binance"));
+    pub const POLYGON: ChainId = ChainId(Cow::Borrowed("polygon"));
+
+    /// A chain without a constant here, e.g. one added to the bridge after
+    /// this SDK was released.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(Cow::Owned(name.into()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiVersions {
+    pub versions: Vec<String>,
+    #[serde(default)]
+    pub features: HashSet<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub api_version: String,
+    pub features: HashSet<String>,
+}
+
+impl Capabilities {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.contains(feature)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainInfo {
+    pub chain_id: u64,
+    pub genesis_hash: String,
+    pub shard_count: u64,
+    pub protocol_version: String,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkStats {
+    pub total_blocks: u64,
+    pub total_transactions: u64,
+    pub total_addresses: u64,
+    pub total_validators: u64,
+    pub total_shards: u64,
+    pub average_block_time: f64,
+    pub current_tps: f64,
+    pub peak_tps: f64,
+    pub total_staked: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_difficulty: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub market_data: Option<HashMap<String, serde_json::Value>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsMetric {
+    Tps,
+    Transactions,
+    Fees,
+    BlockTime,
+    ActiveAddresses,
+    ValidatorCount,
+    TotalStaked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsResolution {
+    Minute,
+    Hour,
+    Day,
+    Week,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsHistory {
+    pub metric: StatsMetric,
+    pub resolution: StatsResolution,
+    /// Ordered by ascending timestamp, one point per resolution bucket.
+    pub points: Vec<StatsPoint>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// What is queued in a shard's mempool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolStats {
+    pub shard_id: ShardId,
+    pub pending: u64,
+    /// Transactions waiting on an earlier nonce from the same sender.
+    pub queued: u64,
+    pub size_bytes: u64,
+    pub min_gas_price: f64,
+    pub median_gas_price: f64,
+    pub max_gas_price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oldest_timestamp: Option<DateTime<Utc>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Gas prices paid and block fullness over one resolution bucket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasStatsBucket {
+    /// Start of the bucket.
+    pub timestamp: DateTime<Utc>,
+    pub blocks: u64,
+    pub transactions: u64,
+    pub min_gas_price: f64,
+    pub median_gas_price: f64,
+    pub p75_gas_price: f64,
+    pub p90_gas_price: f64,
+    pub p99_gas_price: f64,
+    pub max_gas_price: f64,
+    /// Mean share of each block's gas limit that was used, from 0 to 1.
+    pub block_fullness: f64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasStats {
+    pub resolution: StatsResolution,
+    /// Ordered by ascending timestamp; buckets without blocks are left out.
+    pub buckets: Vec<GasStatsBucket>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Synced,
+    Syncing,
+    Stalled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardSyncStatus {
+    pub shard_id: ShardId,
+    pub current_block: u64,
+    pub highest_block: u64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub version: String,
+    pub sync_state: SyncState,
+    pub peer_count: u64,
+    pub shards: Vec<ShardSyncStatus>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// A node this node is connected to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub id: String,
+    /// Host and port the peer is reached at.
+    pub address: String,
+    pub version: String,
+    #[serde(default)]
+    pub shards: Vec<ShardId>,
+    /// True if the peer opened the connection.
+    pub inbound: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connected_since: Option<DateTime<Utc>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// The network as seen from one shard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardTopology {
+    pub shard_id: ShardId,
+    pub peer_count: u64,
+    /// Number of peers running each node version.
+    #[serde(default)]
+    pub versions: HashMap<String, u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_latency_ms: Option<f64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkTopology {
+    /// Distinct nodes known to the network, across all shards.
+    pub total_peers: u64,
+    pub shards: Vec<ShardTopology>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl NodeStatus {
+    /// True when the node reports itself synced and no shard is behind its
+    /// highest known block.
+    pub fn is_synced(&self) -> bool {
+        self.sync_state == SyncState::Synced
+            && self
+                .shards
+                .iter()
+                .all(|shard| shard.current_block >= shard.highest_block)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardInfo {
+    pub shard_id: ShardId,
+    pub is_active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardStats {
+    pub shard_id: ShardId,
+    pub tps: f64,
+    pub pending_transactions: u64,
+    pub validator_count: u64,
+    pub latest_block: u64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
@@ -9,9 +316,11 @@ pub struct Transaction {
     pub recipient: String,
     pub amount: f64,
     pub timestamp: DateTime<Utc>,
-    pub shard_id: u64,
+    pub shard_id: ShardId,
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nonce: Option<u64>,
@@ -21,6 +330,33 @@ pub struct Transaction {
     pub gas_limit: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<HashMap<String, serde_json::Value>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl Transaction {
+    /// Recomputes the hash the node derives from this transaction's fields, to
+    /// check `hash` against what was actually sent.
+    pub fn compute_hash(&self) -> crate::Result<String> {
+        let nonce = self.nonce.ok_or_else(|| codec::missing_field("nonce"))?;
+        codec::transaction_hash(
+            &self.sender,
+            &self.recipient,
+            self.amount,
+            nonce,
+            &self.timestamp,
+        )
+    }
+
+    /// Still in the mempool, and so replaceable by a transaction with the same
+    /// nonce.
+    pub fn is_pending(&self) -> bool {
+        self.block_number.is_none() && self.status.eq_ignore_ascii_case("pending")
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("failed")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,7 +367,7 @@ pub struct Block {
     pub timestamp: DateTime<Utc>,
     pub transactions: Vec<Transaction>,
     pub validator: String,
-    pub shard_id: u64,
+    pub shard_id: ShardId,
     pub merkle_root: String,
     pub state_root: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -42,21 +378,364 @@ pub struct Block {
     pub gas_used: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs_bloom: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// A block without its transaction bodies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub number: u64,
+    pub hash: String,
+    pub previous_hash: String,
+    pub timestamp: DateTime<Utc>,
+    pub validator: String,
+    pub shard_id: ShardId,
+    pub merkle_root: String,
+    pub state_root: String,
+    pub transaction_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    /// Hex-encoded 2048-bit bloom over the block's log addresses and topics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs_bloom: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub address: String,
+    pub balance: f64,
+    pub nonce: u64,
+    pub shard_id: ShardId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_hash: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// A price reported by the network's oracle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTick {
+    pub symbol: String,
+    /// The currency `price` is given in, e.g. `USD`.
+    pub quote: String,
+    pub price: f64,
+    pub timestamp: DateTime<Utc>,
+    /// The feed or aggregation the oracle took the price from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// An account's balance of one token contract.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolding {
+    pub contract_address: String,
+    pub symbol: String,
+    pub balance: f64,
+    pub shard_id: ShardId,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedAccount {
+    /// 1-based position by balance, highest first.
+    pub rank: u64,
+    pub address: String,
+    pub balance: f64,
+    pub shard_id: ShardId,
+    /// Fraction of the circulating supply held by this account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_share: Option<f64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProofNode {
+    Leaf {
+        hash: String,
+        key: String,
+        value: serde_json::Value,
+    },
+    Extension {
+        hash: String,
+        prefix: String,
+    },
+    Branch {
+        hash: String,
+        value: Option<serde_json::Value>,
+        children: Vec<Option<String>>,
+    },
+}
+
+impl ProofNode {
+    pub fn hash(&self) -> &str {
+        match self {
+            ProofNode::Leaf { hash, .. }
+            | ProofNode::Extension { hash, .. }
+            | ProofNode::Branch { hash, .. } => hash,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageProof {
+    pub key: String,
+    pub value: serde_json::Value,
+    pub proof: Vec<ProofNode>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateProof {
+    pub address: String,
+    pub block_number: u64,
+    pub state_root: String,
+    /// Account state exactly as stored in the state trie.
+    pub account: serde_json::Value,
+    pub account_proof: Vec<ProofNode>,
+    #[serde(default)]
+    pub storage_proof: Vec<StorageProof>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Kind of contract, as named by the node's deploy templates. Types this SDK
+/// doesn't know are kept verbatim in `Custom`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ContractType {
+    Token,
+    Nft,
+    Multisig,
+    /// An upgradeable proxy forwarding calls to an implementation contract.
+    Proxy,
+    Custom(String),
+}
+
+impl ContractType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ContractType::Token => "ERC20",
+            ContractType::Nft => "ERC721",
+            ContractType::Multisig => "MULTISIG",
+            ContractType::Proxy => "PROXY",
+            ContractType::Custom(name) => name,
+        }
+    }
+}
+
+impl From<String> for ContractType {
+    fn from(name: String) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "ERC20" => ContractType::Token,
+            "ERC721" => ContractType::Nft,
+            "MULTISIG" => ContractType::Multisig,
+            "PROXY" => ContractType::Proxy,
+            _ => ContractType::Custom(name),
+        }
+    }
+}
+
+impl From<ContractType> for String {
+    fn from(contract_type: ContractType) -> Self {
+        match contract_type {
+            ContractType::Custom(name) => name,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ContractType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmartContract {
     pub address: String,
-    pub contract_type: String,
+    pub contract_type: ContractType,
     pub creator: String,
     pub creation_timestamp: DateTime<Utc>,
-    pub shard_id: u64,
+    pub shard_id: ShardId,
     pub abi: HashMap<String, serde_json::Value>,
     pub bytecode: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<HashMap<String, serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    /// Hash of the deployment transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// One read-only call in a `multicall`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractCall {
+    pub contract_address: String,
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
+impl ContractCall {
+    pub fn new(contract_address: &str, method: &str, params: serde_json::Value) -> Self {
+        Self {
+            contract_address: contract_address.to_string(),
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+/// A contract call's return value decoded into `T`, along with what the node
+/// reports about the call itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallResult<T> {
+    pub result: T,
+    /// Set when the method changed state and the node submitted a
+    /// transaction for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// The outcome of one call in a `multicall`, at the call's `index`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractCallResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl ContractCallResult {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Decodes the returned value into `T`, or fails with the call's error.
+    pub fn decode<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T> {
+        if let Some(error) = &self.error {
+            return Err(crate::VernachainError::UnexpectedResponseError(format!(
+                "call {} failed: {}",
+                self.index, error
+            )));
+        }
+        let value = self.value.clone().unwrap_or(serde_json::Value::Null);
+        serde_json::from_value(value).map_err(|e| {
+            crate::VernachainError::UnexpectedResponseError(format!(
+                "call {} returned an unexpected value: {}",
+                self.index, e
+            ))
+        })
+    }
+}
+
+/// A contract's `state` as of one block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractState {
+    pub address: String,
+    /// `None` for the latest state.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    pub values: HashMap<String, serde_json::Value>,
+}
+
+impl ContractState {
+    /// The keys that differ from `earlier`, sorted by key.
+    pub fn diff(&self, earlier: &ContractState) -> Vec<StateChange> {
+        let keys: HashSet<&String> = self.values.keys().chain(earlier.values.keys()).collect();
+        let mut changes: Vec<StateChange> = keys
+            .into_iter()
+            .filter_map(|key| {
+                let before = earlier.values.get(key);
+                let after = self.values.get(key);
+                (before != after).then(|| StateChange {
+                    key: key.clone(),
+                    before: before.cloned(),
+                    after: after.cloned(),
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| a.key.cmp(&b.key));
+        changes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// One key of a contract's state that changed between two blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChange {
+    pub key: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+impl StateChange {
+    pub fn kind(&self) -> StateChangeKind {
+        match (&self.before, &self.after) {
+            (None, _) => StateChangeKind::Added,
+            (_, None) => StateChangeKind::Removed,
+            _ => StateChangeKind::Modified,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractStateDiff {
+    pub address: String,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub changes: Vec<StateChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Pending,
+    Verified,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractVerification {
+    pub address: String,
+    pub status: VerificationStatus,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<DateTime<Utc>>,
+    /// Compiler output or mismatch details when verification failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,32 +746,415 @@ pub struct Validator {
     pub total_blocks_validated: u64,
     pub is_active: bool,
     pub last_active: DateTime<Utc>,
-    pub shard_id: u64,
+    pub shard_id: ShardId,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commission_rate: Option<f64>,
+    /// Delegations themselves are paged through `get_validator_delegators`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegator_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ValidatorMetadata>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorAttribute {
+    Stake,
+    CommissionRate,
+    Reputation,
+}
+
+/// One change to a validator's stake, commission rate or reputation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorChange {
+    pub timestamp: DateTime<Utc>,
+    pub epoch: u64,
+    pub attribute: ValidatorAttribute,
+    pub previous: f64,
+    pub current: f64,
+    /// The transaction that made the change, if one did; reputation changes
+    /// come from consensus.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorHistory {
+    pub validator_address: String,
+    /// Ordered by ascending timestamp.
+    pub changes: Vec<ValidatorChange>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl ValidatorHistory {
+    /// Changes to one attribute, in order, for charting it.
+    pub fn series(
+        &self,
+        attribute: ValidatorAttribute,
+    ) -> impl Iterator<Item = &ValidatorChange> + '_ {
+        self.changes
+            .iter()
+            .filter(move |change| change.attribute == attribute)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochPerformance {
+    pub epoch: u64,
+    pub uptime: f64,
+    pub blocks_proposed: u64,
+    pub missed_blocks: u64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorPerformance {
+    pub validator_address: String,
+    pub from_epoch: u64,
+    pub to_epoch: u64,
+    /// Fraction of expected blocks signed across the range.
+    pub uptime: f64,
+    pub blocks_proposed: u64,
+    pub missed_blocks: u64,
+    #[serde(default)]
+    pub epochs: Vec<EpochPerformance>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashingEvent {
+    pub validator_address: String,
+    pub epoch: u64,
+    pub block_number: u64,
+    pub shard_id: ShardId,
+    pub reason: String,
+    pub amount: f64,
+    pub jailed: bool,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlashingAlertKind {
+    Slashed,
+    /// The validator was removed from the active set; `event.amount` holds any
+    /// stake slashed along with it.
+    Jailed,
+}
+
+/// A slashing event against one of the validators passed to `watch_slashing`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashingAlert {
+    pub kind: SlashingAlertKind,
+    pub event: SlashingEvent,
+}
+
+impl From<SlashingEvent> for SlashingAlert {
+    fn from(event: SlashingEvent) -> Self {
+        let kind = if event.jailed {
+            SlashingAlertKind::Jailed
+        } else {
+            SlashingAlertKind::Slashed
+        };
+        SlashingAlert { kind, event }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidatorMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotation {
+    pub validator_address: String,
+    pub new_public_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_epoch: Option<u64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+// Event logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Log {
+    pub address: String,
+    pub topics: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Event name, when the node could decode the log against the contract ABI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub args: HashMap<String, serde_json::Value>,
+    pub block_number: u64,
+    pub block_hash: String,
+    pub tx_hash: String,
+    pub log_index: u64,
+    pub shard_id: ShardId,
+    /// Set when the log's block was dropped by a reorganization.
+    #[serde(default)]
+    pub removed: bool,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+// Execution traces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallFrame {
+    pub call_type: String,
+    pub from: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub value: f64,
+    pub gas: u64,
+    pub gas_used: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub calls: Vec<CallFrame>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceStep {
+    pub pc: u64,
+    pub op: String,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub depth: u32,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateDiff {
+    pub address: String,
+    /// Storage slot that changed, or `None` for account-level fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionTrace {
+    pub tx_hash: String,
+    pub gas_used: u64,
+    pub failed: bool,
+    pub call: CallFrame,
+    #[serde(default)]
+    pub steps: Vec<TraceStep>,
+    #[serde(default)]
+    pub state_diff: Vec<StateDiff>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochValidator {
+    pub address: String,
+    pub stake: f64,
+    pub shard_id: ShardId,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Epoch {
+    pub number: u64,
+    pub start_block: u64,
+    /// `None` while the epoch is still running.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub delegators: Option<Vec<HashMap<String, serde_json::Value>>>,
+    pub end_block: Option<u64>,
+    pub start_time: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<DateTime<Utc>>,
+    pub validators: Vec<EpochValidator>,
+    pub reward_pool: f64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// A block a validator is expected to propose.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalSlot {
+    pub block_number: u64,
+    pub proposer: String,
+    /// Estimated from the target block time; later slots drift the most.
+    pub expected_at: DateTime<Utc>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Who proposes each block of an epoch on one shard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProposalSchedule {
+    pub epoch: u64,
+    pub shard_id: ShardId,
+    pub slots: Vec<ProposalSlot>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl ProposalSchedule {
+    /// The slots assigned to `validator`, in block order.
+    pub fn slots_for<'a>(
+        &'a self,
+        validator: &'a str,
+    ) -> impl Iterator<Item = &'a ProposalSlot> + 'a {
+        self.slots
+            .iter()
+            .filter(move |slot| slot.proposer == validator)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakingParams {
+    pub annual_inflation_rate: f64,
+    pub total_supply: f64,
+    pub total_staked: f64,
+    pub epochs_per_year: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unbonding_epochs: Option<u64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delegation {
+    pub delegator: String,
+    pub validator_address: String,
+    pub amount: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// One page of a validator's delegations, largest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationPage {
+    pub delegations: Vec<Delegation>,
+    /// Pass to the next request to continue; `None` on the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorReward {
+    pub validator_address: String,
+    pub amount: f64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingRewards {
+    pub address: String,
+    pub total: f64,
+    #[serde(default)]
+    pub by_validator: Vec<ValidatorReward>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeResult {
+    pub tx_hash: String,
+    pub validator_address: String,
+    pub amount: f64,
+    /// Epoch from which the change applies; unbonding may delay it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_epoch: Option<u64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardClaim {
+    pub tx_hash: String,
+    pub amount: f64,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossShardTransfer {
     pub transfer_id: String,
-    pub from_shard: u64,
-    pub to_shard: u64,
+    pub from_shard: ShardId,
+    pub to_shard: ShardId,
     pub transaction: Transaction,
     pub status: String,
     pub initiated_at: DateTime<Utc>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub proof: Option<HashMap<String, serde_json::Value>>,
+    pub proof: Option<CrossShardProof>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Inclusion proof for the source-shard side of a cross-shard transfer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossShardProof {
+    pub transaction_hash: String,
+    pub from_shard: ShardId,
+    pub to_shard: ShardId,
+    /// Source-shard block whose `state_root` the path leads to.
+    pub block_number: u64,
+    pub state_root: String,
+    pub merkle_proof: Vec<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl CrossShardTransfer {
+    pub fn is_completed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("completed")
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("failed")
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_completed() || self.is_failed()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BridgeTransfer {
     pub transfer_id: String,
-    pub source_chain: String,
-    pub target_chain: String,
+    pub source_chain: ChainId,
+    pub target_chain: ChainId,
     pub amount: f64,
     pub sender: String,
     pub recipient: String,
@@ -101,47 +1163,622 @@ pub struct BridgeTransfer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub proof: Option<HashMap<String, serde_json::Value>>,
+    pub proof: Option<BridgeProof>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// A bridge transaction as the node's `/bridge/transaction/{tx_hash}` route
+/// reports it, with the transactions on either chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeTransactionDetail {
+    pub bridge_tx_hash: String,
+    pub from_chain_tx: String,
+    /// `None` until the transfer reaches the target chain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_chain_tx: Option<String>,
+    pub status: String,
+    /// In UTC; the node sends it without an offset.
+    pub timestamp: chrono::NaiveDateTime,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeAttestation {
+    pub validator: String,
+    /// Hex-encoded ed25519 public key of the validator.
+    pub public_key: String,
+    /// Hex-encoded signature over the transfer's `tx_hash`.
+    pub signature: String,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeProof {
+    pub transfer_id: String,
+    pub tx_hash: String,
+    pub source_chain: ChainId,
+    pub target_chain: ChainId,
+    pub root: String,
+    pub merkle_proof: Vec<String>,
+    pub signatures: Vec<BridgeAttestation>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl BridgeTransfer {
+    pub fn is_completed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("completed")
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("failed")
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_completed() || self.is_failed()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeChain {
+    pub chain: ChainId,
+    pub name: String,
+    pub is_active: bool,
+    pub confirmations: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeFeeSchedule {
+    pub flat_fee: f64,
+    /// Fraction of the transferred amount, e.g. `0.001` for 0.1%.
+    pub percentage_fee: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_fee: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee: Option<f64>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
 }
 
-// Request types
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeToken {
+    pub symbol: String,
+    pub name: String,
+    pub chain: ChainId,
+    pub decimals: u8,
+    pub min_amount: f64,
+    pub max_amount: f64,
+    pub fee: BridgeFeeSchedule,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeQuote {
+    pub source_chain: ChainId,
+    pub target_chain: ChainId,
+    pub amount: f64,
+    pub fee: f64,
+    /// Amount credited on the target chain after fees and conversion.
+    pub amount_received: f64,
+    pub exchange_rate: f64,
+    pub estimated_time_seconds: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Incoming,
+    Outgoing,
+    /// A transaction from the watched address carrying contract call data.
+    ContractInteraction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressActivity {
+    pub kind: ActivityKind,
+    pub transaction: Transaction,
+    pub block_number: u64,
+    pub block_hash: String,
+    /// Set when the block holding this activity was dropped by a reorg.
+    #[serde(default)]
+    pub removed: bool,
+}
+
+// Webhooks
+/// Which events a webhook is delivered for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookFilter {
+    NewBlock {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        shard_id: Option<ShardId>,
+    },
+    AddressActivity {
+        address: String,
+    },
+    Logs(LogFilter),
+    CrossShardTransfer {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        address: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub filter: WebhookFilter,
+    pub created_at: DateTime<Utc>,
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// One page of an account's transactions, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionPage {
+    pub transactions: Vec<Transaction>,
+    /// Pass to the next request to continue; `None` on the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Funds paid out by a test network's faucet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetGrant {
+    pub tx_hash: String,
+    pub address: String,
+    pub amount: f64,
+    /// When the faucet will next fund this address, if it enforces a cooldown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_request_at: Option<DateTime<Utc>>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+/// Outcome of one entry in a batch submission, in request order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<Transaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(flatten, deserialize_with = "crate::decode::capture_extra")]
+    pub extra: ExtraFields,
+}
+
+impl BatchItemResult {
+    pub fn is_success(&self) -> bool {
+        self.transaction.is_some()
+    }
+}
+
+// Search
+/// What a free-form query resolved to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "result", rename_all = "snake_case")]
+pub enum SearchResult {
+    /// A block height, with the matching block from every shard that has one.
+    Blocks(Vec<Block>),
+    Block(Block),
+    Transaction(Transaction),
+    Account(Account),
+    Contract(SmartContract),
+    NotFound,
+}
+
+// Request types
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TransactionRequest {
     pub sender: String,
     pub recipient: String,
     pub amount: f64,
-    #[serde(default)]
-    pub shard_id: u64,
+    /// Resolved from the sender's address when left unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<ShardId>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_limit: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<HashMap<String, serde_json::Value>>,
+    /// Reserved from the client's `NonceManager` when left unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Set by `SponsoredTransactionBuilder` when a third party pays the gas.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sponsor: Option<Sponsorship>,
+    /// Pins the transaction's timestamp so its hash is known before sending;
+    /// the node assigns one when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// The sender's signature, set by `Keypair::sign_transaction`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl TransactionRequest {
+    /// The hash the node will assign, available once `nonce` and `timestamp`
+    /// are set.
+    pub fn compute_hash(&self) -> crate::Result<String> {
+        let nonce = self.nonce.ok_or_else(|| codec::missing_field("nonce"))?;
+        let timestamp = self
+            .timestamp
+            .as_ref()
+            .ok_or_else(|| codec::missing_field("timestamp"))?;
+        codec::transaction_hash(&self.sender, &self.recipient, self.amount, nonce, timestamp)
+    }
+
+    /// Builds a request that is checked for obvious mistakes before anything
+    /// is sent.
+    pub fn builder() -> TransactionRequestBuilder {
+        TransactionRequestBuilder::default()
+    }
+}
+
+/// Highest gas limit a builder accepts for a single transaction.
+pub const MAX_GAS_LIMIT: u64 = 30_000_000;
+
+#[derive(Debug, Clone, Default)]
+pub struct TransactionRequestBuilder {
+    request: TransactionRequest,
+}
+
+impl TransactionRequestBuilder {
+    pub fn sender(mut self, sender: impl Into<String>) -> Self {
+        self.request.sender = sender.into();
+        self
+    }
+
+    pub fn recipient(mut self, recipient: impl Into<String>) -> Self {
+        self.request.recipient = recipient.into();
+        self
+    }
+
+    /// Sets the recipient to the address saved under `label`, failing if
+    /// the book has no such entry.
+    pub fn recipient_named(
+        self,
+        book: &crate::address_book::AddressBook,
+        label: &str,
+    ) -> crate::Result<Self> {
+        Ok(self.recipient(book.address(label)?))
+    }
+
+    pub fn amount(mut self, amount: f64) -> Self {
+        self.request.amount = amount;
+        self
+    }
+
+    pub fn shard_id(mut self, shard_id: ShardId) -> Self {
+        self.request.shard_id = Some(shard_id);
+        self
+    }
+
+    pub fn gas_price(mut self, gas_price: f64) -> Self {
+        self.request.gas_price = Some(gas_price);
+        self
+    }
+
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.request.gas_limit = Some(gas_limit);
+        self
+    }
+
+    pub fn data(mut self, data: HashMap<String, serde_json::Value>) -> Self {
+        self.request.data = Some(data);
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.request.nonce = Some(nonce);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.request.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Fails with `ValidationError` if an address is empty or fails its
+    /// checksum, the amount isn't a positive number, the shard is unset, or
+    /// the gas price or limit is out of range.
+    pub fn build(self) -> crate::Result<TransactionRequest> {
+        let request = self.request;
+        if request.sender.trim().is_empty() {
+            return Err(invalid("sender address is empty"));
+        }
+        if request.recipient.trim().is_empty() {
+            return Err(invalid("recipient address is empty"));
+        }
+        crate::address::check(&request.sender)?;
+        crate::address::check(&request.recipient)?;
+        if !(request.amount.is_finite() && request.amount > 0.0) {
+            return Err(invalid(format!("amount must be positive, got {}", request.amount)));
+        }
+        if request.shard_id.is_none() {
+            return Err(invalid("shard_id is required"));
+        }
+        if let Some(gas_price) = request.gas_price {
+            if !(gas_price.is_finite() && gas_price > 0.0) {
+                return Err(invalid(format!("gas price must be positive, got {}", gas_price)));
+            }
+        }
+        if let Some(gas_limit) = request.gas_limit {
+            if gas_limit == 0 || gas_limit > MAX_GAS_LIMIT {
+                return Err(invalid(format!(
+                    "gas limit must be between 1 and {}, got {}",
+                    MAX_GAS_LIMIT, gas_limit
+                )));
+            }
+        }
+        Ok(request)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> crate::VernachainError {
+    crate::VernachainError::ValidationError(message.into())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sponsorship {
+    /// Hex-encoded ed25519 public key of the account paying the fee.
+    pub sponsor: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee: Option<f64>,
+    pub signature: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContractDeployRequest {
-    pub contract_type: String,
+    pub contract_type: ContractType,
     pub params: HashMap<String, serde_json::Value>,
     #[serde(default)]
-    pub shard_id: u64,
+    pub shard_id: ShardId,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_limit: Option<u64>,
+    /// Hex-encoded 32-byte salt. When set, the address is derived from the
+    /// deployer and salt instead of the deployer's nonce, on nodes reporting
+    /// the `deterministic_addresses` feature; see
+    /// `codec::predict_contract_address`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrossShardTransferRequest {
-    pub from_shard: u64,
-    pub to_shard: u64,
+    pub from_shard: ShardId,
+    pub to_shard: ShardId,
     pub transaction: TransactionRequest,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BridgeTransferRequest {
-    pub target_chain: String,
+    pub target_chain: ChainId,
     pub amount: f64,
     pub recipient: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas_limit: Option<u64>,
-} 
\ No newline at end of file
+}
+
+// Subscription events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChainEvent {
+    NewBlock(Block),
+    /// The canonical chain switched branches. `dropped` and `added` are ordered by
+    /// ascending block number; `common_ancestor` is `None` when the fork is deeper
+    /// than the blocks the subscription has kept track of.
+    Reorg {
+        common_ancestor: Option<Block>,
+        dropped: Vec<Block>,
+        added: Vec<Block>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeRequest {
+    pub validator_address: String,
+    pub amount: f64,
+    /// Restake rewards as they are paid out; the node's default when `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_compound: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnstakeRequest {
+    pub validator_address: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelegationRequest {
+    pub validator_address: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaucetRequest {
+    pub address: String,
+    pub amount: f64,
+    /// Solved captcha or access token, for faucets open to the public.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captcha_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimRewardsRequest {
+    /// Claims from every validator when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validator_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorRegistrationRequest {
+    pub public_key: String,
+    pub stake: f64,
+    /// Fraction of rewards kept by the validator, between 0 and 1.
+    pub commission_rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<ShardId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ValidatorMetadata>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorUpdateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ValidatorMetadata>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlashingFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validator_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<ShardId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_epoch: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_epoch: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractVerificationRequest {
+    pub source: String,
+    pub compiler_version: String,
+    pub optimization: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogFilter {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<String>,
+    /// Positional topic filters; `None` matches any topic in that position.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard_id: Option<ShardId>,
+}
+
+impl LogFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn address(mut self, address: &str) -> Self {
+        self.addresses.push(address.to_string());
+        self
+    }
+
+    pub fn topic(mut self, position: usize, topic: &str) -> Self {
+        if self.topics.len() <= position {
+            self.topics.resize(position + 1, None);
+        }
+        self.topics[position] = Some(topic.to_string());
+        self
+    }
+
+    pub fn event(mut self, name: &str) -> Self {
+        self.event = Some(name.to_string());
+        self
+    }
+
+    pub fn from_block(mut self, block: u64) -> Self {
+        self.from_block = Some(block);
+        self
+    }
+
+    pub fn to_block(mut self, block: u64) -> Self {
+        self.to_block = Some(block);
+        self
+    }
+
+    pub fn shard(mut self, shard_id: ShardId) -> Self {
+        self.shard_id = Some(shard_id);
+        self
+    }
+}
+
+/// Narrows `get_pending_transactions`; an empty filter matches everything
+/// queued on the shard.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MempoolFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_gas_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl MempoolFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sender(mut self, address: &str) -> Self {
+        self.sender = Some(address.to_string());
+        self
+    }
+
+    pub fn recipient(mut self, address: &str) -> Self {
+        self.recipient = Some(address.to_string());
+        self
+    }
+
+    pub fn min_gas_price(mut self, gas_price: f64) -> Self {
+        self.min_gas_price = Some(gas_price);
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookRegistrationRequest {
+    pub filter: WebhookFilter,
+    pub url: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTransactionRequest {
+    pub transactions: Vec<TransactionRequest>,
+    /// Apply all transactions or none of them.
+    pub atomic: bool,
+}
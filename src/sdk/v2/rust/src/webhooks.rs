@@ -0,0 +1,80 @@
+//! Receiving side of node webhooks.
+//!
+//! Each delivery carries `X-Vernachain-Signature: sha256=<hex>`, the
+//! HMAC-SHA256 of the raw request body keyed with the secret given at
+//! registration.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub const SIGNATURE_HEADER: &str = "X-Vernachain-Signature";
+
+/// Checks a delivery's signature header, `sha256=<hex>`, against the raw
+/// body. The comparison runs in constant time.
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(digest) = signature.trim().strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(digest) else {
+        return false;
+    };
+    mac(secret, body).verify_slice(&expected).is_ok()
+}
+
+/// Computes the signature header value the node sends for `body`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    format!(
+        "sha256={}",
+        hex::encode(mac(secret, body).finalize().into_bytes())
+    )
+}
+
+fn mac(secret: &str, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = br#"{"event":"block"}"#;
+    // hmac.new(b"topsecret", b'{"event":"block"}', hashlib.sha256).hexdigest()
+    const SIGNATURE: &str =
+        "sha256=4ac1237b7cfcd6c66cfc09b020fafeb25a0fc0a17ff331e84379623b18fcce06";
+
+    #[test]
+    fn signs_like_the_node() {
+        assert_eq!(sign("topsecret", BODY), SIGNATURE);
+        assert!(verify_signature("topsecret", BODY, SIGNATURE));
+        assert!(verify_signature(
+            "topsecret",
+            BODY,
+            &format!(" {} ", SIGNATURE)
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let digest = SIGNATURE.trim_start_matches("sha256=");
+        assert!(!verify_signature("topsecret", BODY, digest));
+        assert!(!verify_signature("topsecret", BODY, "sha256=not-hex"));
+        assert!(!verify_signature(
+            "topsecret",
+            BODY,
+            &SIGNATURE[..SIGNATURE.len() - 2]
+        ));
+    }
+
+    #[test]
+    fn rejects_other_secrets_and_bodies() {
+        assert!(!verify_signature("topsecret2", BODY, SIGNATURE));
+        assert!(!verify_signature(
+            "topsecret",
+            br#"{"event":"blocks"}"#,
+            SIGNATURE
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+//! WebSocket plumbing behind the client's subscriptions.
+//!
+//! By default every subscription opens its own socket on a dedicated path.
+//! Nodes that report the `ws_multiplex` feature also serve a shared event
+//! socket. With multiplexing enabled, subscriptions to such a node share one
+//! authenticated connection per client instead: the `ConnectionManager` sends
+//! a `subscribe` message per topic and routes incoming
+//! `{"type": topic, "data": ...}` messages to the subscribers of that topic.
+
+use crate::{client::VernachainClient, error::Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::{collections::HashMap, io, time::Duration};
+use tokio::{
+    net::TcpStream,
+    sync::{mpsc, Mutex},
+    time::{interval_at, sleep_until, Instant},
+};
+use tokio_tungstenite::{
+    tungstenite::{self, protocol::Message},
+    MaybeTlsStream, WebSocketStream,
+};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{error, info};
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// The feature a node reports when it serves the shared event socket.
+pub(crate) const MULTIPLEX_FEATURE: &str = "ws_multiplex";
+/// Path of the node's shared event socket.
+const MULTIPLEX_PATH: &str = "/ws";
+
+/// How subscription sockets detect dead connections.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Keepalive {
+    pub(crate) ping_interval: Duration,
+    pub(crate) idle_timeout: Duration,
+}
+
+/// Where a subscription's messages come from.
+pub(crate) enum MessageSource {
+    /// A socket of its own.
+    Socket(Box<WsStream>),
+    /// A topic on the shared connection.
+    Topic(mpsc::UnboundedReceiver<String>),
+}
+
+impl MessageSource {
+    /// Waits for the next text payload. Returns `None` once the source has
+    /// closed, failed or the client is shutting down; failures are logged.
+    pub(crate) async fn next_text(
+        &mut self,
+        shutdown: &CancellationToken,
+        keepalive: Keepalive,
+    ) -> Option<String> {
+        match self {
+            MessageSource::Socket(socket) => loop {
+                match next_message(socket, shutdown, keepalive).await? {
+                    Ok(Message::Text(text)) => return Some(text),
+                    Ok(Message::Close(_)) => return None,
+                    Err(e) => {
+                        error!("WebSocket error: {}", e);
+                        return None;
+                    }
+                    _ => {}
+                }
+            },
+            MessageSource::Topic(messages) => tokio::select! {
+                text = messages.recv() => text,
+                _ = shutdown.cancelled() => None,
+            },
+        }
+    }
+}
+
+enum Command {
+    Subscribe {
+        topic: String,
+        messages: mpsc::UnboundedSender<String>,
+    },
+}
+
+/// Keeps at most one shared connection open and reopens it on demand after
+/// it drops. Subscriptions on a dropped connection end like any other closed
+/// socket.
+pub(crate) struct ConnectionManager {
+    commands: Mutex<Option<mpsc::UnboundedSender<Command>>>,
+    tasks: TaskTracker,
+    shutdown: CancellationToken,
+    keepalive: Keepalive,
+}
+
+impl ConnectionManager {
+    pub(crate) fn new(
+        tasks: TaskTracker,
+        shutdown: CancellationToken,
+        keepalive: Keepalive,
+    ) -> Self {
+        Self {
+            commands: Mutex::new(None),
+            tasks,
+            shutdown,
+            keepalive,
+        }
+    }
+
+    pub(crate) async fn subscribe(
+        &self,
+        client: &VernachainClient,
+        topic: String,
+    ) -> Result<MessageSource> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut command = Command::Subscribe {
+            topic,
+            messages: tx,
+        };
+        let mut commands = self.commands.lock().await;
+        if let Some(sender) = commands.as_ref() {
+            match sender.send(command) {
+                Ok(()) => return Ok(MessageSource::Topic(rx)),
+                // The connection task has exited; reconnect below.
+                Err(mpsc::error::SendError(unsent)) => command = unsent,
+            }
+        }
+
+        let socket = client.open_socket(MULTIPLEX_PATH).await?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        // The receiver is alive until the task below starts, so this can't fail.
+        let _ = sender.send(command);
+        self.tasks.spawn(run_connection(
+            socket,
+            receiver,
+            self.shutdown.clone(),
+            self.keepalive,
+        ));
+        *commands = Some(sender);
+        Ok(MessageSource::Topic(rx))
+    }
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    #[serde(rename = "type")]
+    topic: String,
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+async fn run_connection(
+    mut socket: WsStream,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    shutdown: CancellationToken,
+    keepalive: Keepalive,
+) {
+    let mut routes: HashMap<String, Vec<mpsc::UnboundedSender<String>>> = HashMap::new();
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let Some(Command::Subscribe { topic, messages }) = command else {
+                    break;
+                };
+                let subscribers = routes.entry(topic.clone()).or_default();
+                subscribers.push(messages);
+                if subscribers.len() == 1 {
+                    if let Err(e) = socket.send(control("subscribe", &topic)).await {
+                        error!("Failed to subscribe to {}: {}", topic, e);
+                        break;
+                    }
+                }
+            }
+            message = next_message(&mut socket, &shutdown, keepalive) => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        error!("WebSocket error: {}", e);
+                        break;
+                    }
+                    Some(Ok(_)) => continue,
+                };
+                let envelope = match serde_json::from_str::<Envelope>(&text) {
+                    Ok(envelope) => envelope,
+                    Err(e) => {
+                        error!("Failed to parse multiplexed message: {}", e);
+                        continue;
+                    }
+                };
+                let Some(subscribers) = routes.get_mut(&envelope.topic) else {
+                    continue;
+                };
+                let data = envelope.data.to_string();
+                subscribers.retain(|subscriber| subscriber.send(data.clone()).is_ok());
+                if subscribers.is_empty() {
+                    routes.remove(&envelope.topic);
+                    if let Err(e) = socket.send(control("unsubscribe", &envelope.topic)).await {
+                        error!("Failed to unsubscribe from {}: {}", envelope.topic, e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    info!("Shared WebSocket connection closed");
+}
+
+fn control(kind: &str, topic: &str) -> Message {
+    Message::Text(json!({ "type": kind, "event": topic }).to_string())
+}
+
+/// Waits for the next message on `socket`, pinging the node while it is quiet.
+/// Fails with a timeout if nothing, not even a pong, arrives within the idle
+/// timeout, and closes the socket and returns `None` once `shutdown` is
+/// cancelled.
+pub(crate) async fn next_message(
+    socket: &mut WsStream,
+    shutdown: &CancellationToken,
+    keepalive: Keepalive,
+) -> Option<tungstenite::Result<Message>> {
+    let start = Instant::now();
+    let deadline = sleep_until(start + keepalive.idle_timeout);
+    tokio::pin!(deadline);
+    let mut pings = interval_at(start + keepalive.ping_interval, keepalive.ping_interval);
+
+    loop {
+        tokio::select! {
+            message = socket.next() => return message,
+            _ = pings.tick() => {
+                if let Err(e) = socket.send(Message::Ping(Vec::new())).await {
+                    return Some(Err(e));
+                }
+            }
+            _ = &mut deadline => {
+                let idle = io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no message from the node for {:?}", keepalive.idle_timeout),
+                );
+                return Some(Err(tungstenite::Error::Io(idle)));
+            }
+            _ = shutdown.cancelled() => {
+                if let Err(e) = socket.close(None).await {
+                    error!("Failed to close WebSocket: {}", e);
+                }
+                return None;
+            }
+        }
+    }
+}